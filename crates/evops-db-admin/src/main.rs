@@ -0,0 +1,326 @@
+use std::io::{self, IsTerminal as _};
+
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHasher as _, SaltString};
+use clap::{Parser, Subcommand};
+use eyre::eyre;
+use tracing_subscriber::EnvFilter;
+use uuid::Uuid;
+
+use evops_db::{Database, DatabaseConfig, MigrationMode};
+
+/// Maintenance tool for the EvOps database.
+///
+/// The connection is configured through the same `DATABASE_*` environment
+/// variables as the server.
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Apply, revert or list schema migrations.
+    #[command(subcommand)]
+    Migrations(MigrationsCommand),
+    /// Manage user accounts.
+    #[command(subcommand)]
+    Users(UsersCommand),
+    /// Inspect and revoke refresh tokens.
+    #[command(subcommand)]
+    Tokens(TokensCommand),
+    /// Delete events or transfer their ownership.
+    #[command(subcommand)]
+    Events(EventsCommand),
+    /// Delete tags or transfer their ownership.
+    #[command(subcommand)]
+    Tags(TagsCommand),
+    /// Print a report of inconsistent data.
+    Integrity,
+}
+
+#[derive(Subcommand)]
+enum MigrationsCommand {
+    /// Apply all pending migrations.
+    Run,
+    /// Revert the most recently applied migration.
+    Revert,
+    /// List applied and pending migrations.
+    List,
+}
+
+#[derive(Subcommand)]
+enum UsersCommand {
+    /// Create a user. The password is read from standard input.
+    Create {
+        #[arg(long)]
+        login: String,
+        #[arg(long)]
+        display_name: String,
+    },
+    /// Delete a user along with their events and comments.
+    Delete { user_id: Uuid },
+    /// Make another user the author of all events of a user.
+    TransferEvents {
+        user_id: Uuid,
+        #[arg(long)]
+        to: Uuid,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokensCommand {
+    /// List the refresh tokens of a user.
+    List { user_id: Uuid },
+    /// Revoke one refresh token of a user, or all of them.
+    Revoke {
+        user_id: Uuid,
+        #[arg(long)]
+        token_id: Option<Uuid>,
+    },
+}
+
+#[derive(Subcommand)]
+enum EventsCommand {
+    /// Delete an event regardless of its author.
+    Delete { event_id: Uuid },
+    /// Make another user the author of an event.
+    Transfer {
+        event_id: Uuid,
+        #[arg(long)]
+        to: Uuid,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagsCommand {
+    /// Delete a tag regardless of its owner.
+    Delete { tag_id: Uuid },
+    /// Make another user the owner of a tag.
+    Transfer {
+        tag_id: Uuid,
+        #[arg(long)]
+        to: Uuid,
+    },
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+    let mut config = DatabaseConfig::from_env()?;
+
+    let db = match cli.command {
+        Command::Migrations(command) => return self::run_migrations_command(&config, &command),
+        _ => {
+            // Only the `migrations` subcommand is allowed to touch the schema.
+            config.migrations = MigrationMode::FailIfPending;
+            Database::establish_connection(&config).await?
+        }
+    };
+    match cli.command {
+        Command::Migrations(_) => Ok(()),
+        Command::Users(command) => self::run_users_command(&db, command).await,
+        Command::Tokens(command) => self::run_tokens_command(&db, command).await,
+        Command::Events(command) => self::run_events_command(&db, command).await,
+        Command::Tags(command) => self::run_tags_command(&db, command).await,
+        Command::Integrity => self::run_integrity_command(&db).await,
+    }
+}
+
+fn run_migrations_command(
+    config: &DatabaseConfig,
+    command: &MigrationsCommand,
+) -> eyre::Result<()> {
+    match command {
+        MigrationsCommand::Run => {
+            let applied = Database::run_migrations(config)?;
+            if applied.is_empty() {
+                println!("No pending migrations.");
+            }
+            for version in applied {
+                println!("Applied {version}");
+            }
+        }
+        MigrationsCommand::Revert => {
+            let version = Database::revert_last_migration(config)?;
+            println!("Reverted {version}");
+        }
+        MigrationsCommand::List => {
+            let status = Database::migration_status_blocking(config)?;
+            for migration in status.applied {
+                println!("[x] {}", migration.name);
+            }
+            for migration in status.pending {
+                println!("[ ] {}", migration.name);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_users_command(db: &Database, command: UsersCommand) -> eyre::Result<()> {
+    match command {
+        UsersCommand::Create {
+            login,
+            display_name,
+        } => {
+            let login = evops_models::UserLogin::try_new(login)?;
+            let display_name = evops_models::UserDisplayName::try_new(display_name)?;
+            let password_hash = {
+                let password = self::read_password()?;
+                let salt = SaltString::generate(&mut OsRng);
+                Argon2::default()
+                    .hash_password(password.as_bytes(), &salt)
+                    .map_err(|e| eyre!("failed to hash the password: {e}"))?
+                    .to_string()
+            };
+
+            let user_id = evops_models::UserId::new(Uuid::now_v7());
+            db.create_user(
+                user_id,
+                &login,
+                &evops_models::UserPasswordHash::new(password_hash),
+                &display_name,
+            )
+            .await?;
+            println!("{}", user_id.into_inner());
+        }
+        UsersCommand::Delete { user_id } => {
+            let user_id = evops_models::UserId::new(user_id);
+            let image_ids = db.delete_user(user_id).await?;
+            println!("Deleted user {user_id}. Orphaned images:");
+            for image_id in image_ids {
+                println!("{}", image_id.into_inner());
+            }
+        }
+        UsersCommand::TransferEvents { user_id, to } => {
+            let user_id = evops_models::UserId::new(user_id);
+            let count = {
+                db.transfer_user_events(user_id, evops_models::UserId::new(to))
+                    .await?
+            };
+            println!("Transferred {count} events of {user_id} to {to}.");
+        }
+    }
+    Ok(())
+}
+
+async fn run_tokens_command(db: &Database, command: TokensCommand) -> eyre::Result<()> {
+    match command {
+        TokensCommand::List { user_id } => {
+            let user_id = evops_models::UserId::new(user_id);
+            for token_id in db.list_refresh_token_ids(user_id).await? {
+                println!("{token_id}");
+            }
+        }
+        TokensCommand::Revoke { user_id, token_id } => {
+            let user_id = evops_models::UserId::new(user_id);
+            match token_id {
+                Some(token_id) => {
+                    db.revoke_refresh_token(user_id, token_id).await?;
+                    println!("Revoked 1 refresh token.");
+                }
+                None => {
+                    let count = db.revoke_all_refresh_tokens(user_id).await?;
+                    println!("Revoked {count} refresh tokens.");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_events_command(db: &Database, command: EventsCommand) -> eyre::Result<()> {
+    match command {
+        EventsCommand::Delete { event_id } => {
+            let event_id = evops_models::EventId::new(event_id);
+            let image_ids = db.delete_event_as_admin(event_id).await?;
+            println!("Deleted event {event_id}. Orphaned images:");
+            for image_id in image_ids.into_inner() {
+                println!("{}", image_id.into_inner());
+            }
+        }
+        EventsCommand::Transfer { event_id, to } => {
+            let event_id = evops_models::EventId::new(event_id);
+            db.transfer_event(event_id, evops_models::UserId::new(to))
+                .await?;
+            println!("Transferred event {event_id} to {to}.");
+        }
+    }
+    Ok(())
+}
+
+async fn run_tags_command(db: &Database, command: TagsCommand) -> eyre::Result<()> {
+    match command {
+        TagsCommand::Delete { tag_id } => {
+            let tag_id = evops_models::TagId::new(tag_id);
+            db.delete_tag_as_admin(tag_id).await?;
+            println!("Deleted tag {tag_id}.");
+        }
+        TagsCommand::Transfer { tag_id, to } => {
+            let tag_id = evops_models::TagId::new(tag_id);
+            db.transfer_tag(tag_id, evops_models::UserId::new(to))
+                .await?;
+            println!("Transferred tag {tag_id} to {to}.");
+        }
+    }
+    Ok(())
+}
+
+async fn run_integrity_command(db: &Database) -> eyre::Result<()> {
+    let report = db.integrity_report().await?;
+
+    println!("Tags without an owner: {}", report.tags_without_owner.len());
+    for tag_id in &report.tags_without_owner {
+        println!("  {tag_id}");
+    }
+    println!("Tags not used by any event: {}", report.unused_tags.len());
+    for tag_id in &report.unused_tags {
+        println!("  {tag_id}");
+    }
+    println!(
+        "Aliases shadowing a tag name: {}",
+        report.aliases_shadowing_tags.len(),
+    );
+    for (tag_id, alias) in &report.aliases_shadowing_tags {
+        println!("  {tag_id}: {alias}");
+    }
+    println!(
+        "Events with gaps in image positions: {}",
+        report.events_with_image_gaps.len(),
+    );
+    for event_id in &report.events_with_image_gaps {
+        println!("  {event_id}");
+    }
+    println!(
+        "Events modified before they were created: {}",
+        report.events_modified_before_created.len(),
+    );
+    for event_id in &report.events_modified_before_created {
+        println!("  {event_id}");
+    }
+    Ok(())
+}
+
+/// Prompts for the password without echoing it, or reads it from the first
+/// line of stdin when that isn't a terminal, e.g. in scripts.
+fn read_password() -> eyre::Result<String> {
+    let password = if io::stdin().is_terminal() {
+        rpassword::prompt_password("Password: ")?
+    } else {
+        let mut password = String::new();
+        io::stdin().read_line(&mut password)?;
+        password.trim_end_matches(['\r', '\n']).to_owned()
+    };
+    if password.is_empty() {
+        return Err(eyre!("the password must not be empty"));
+    }
+    Ok(password)
+}
@@ -0,0 +1,7 @@
+ALTER TABLE events
+    ADD COLUMN search_vector tsvector NOT NULL GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', title), 'A')
+        || setweight(to_tsvector('english', description), 'B')
+    ) STORED;
+
+CREATE INDEX events_search_vector_idx ON events USING GIN (search_vector);
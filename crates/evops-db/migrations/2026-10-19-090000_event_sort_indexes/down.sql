@@ -0,0 +1,2 @@
+DROP INDEX events_modified_at_idx;
+DROP INDEX events_created_at_idx;
@@ -0,0 +1,3 @@
+-- The ID breaks ties, so that keyset pagination can scan these as ranges.
+CREATE INDEX events_created_at_idx ON events (created_at DESC, id DESC);
+CREATE INDEX events_modified_at_idx ON events (modified_at DESC, id DESC);
@@ -0,0 +1 @@
+DROP INDEX events_author_id_idx;
@@ -0,0 +1 @@
+CREATE INDEX events_author_id_idx ON events (author_id);
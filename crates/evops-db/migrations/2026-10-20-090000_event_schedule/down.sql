@@ -0,0 +1,7 @@
+DROP INDEX events_ends_at_idx;
+DROP INDEX events_starts_at_idx;
+
+ALTER TABLE events
+    DROP COLUMN timezone,
+    DROP COLUMN ends_at,
+    DROP COLUMN starts_at;
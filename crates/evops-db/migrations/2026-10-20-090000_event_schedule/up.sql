@@ -0,0 +1,18 @@
+ALTER TABLE events
+    ADD COLUMN starts_at timestamptz,
+    ADD COLUMN ends_at timestamptz,
+    ADD COLUMN timezone text NOT NULL DEFAULT 'UTC';
+
+-- Existing events didn't have a schedule, so they happened when they were
+-- created.
+UPDATE events SET starts_at = created_at, ends_at = created_at;
+
+ALTER TABLE events
+    ALTER COLUMN starts_at SET NOT NULL,
+    ALTER COLUMN ends_at SET NOT NULL,
+    ALTER COLUMN timezone DROP DEFAULT,
+    ADD CONSTRAINT events_ends_at_check CHECK (ends_at >= starts_at);
+
+-- Scanned in both directions by the start time orders.
+CREATE INDEX events_starts_at_idx ON events (starts_at, id);
+CREATE INDEX events_ends_at_idx ON events (ends_at);
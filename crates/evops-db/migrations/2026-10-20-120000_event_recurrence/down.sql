@@ -0,0 +1,5 @@
+DROP TABLE event_occurrence_overrides;
+DROP TABLE event_exception_dates;
+
+DROP INDEX events_recurring_starts_at_idx;
+ALTER TABLE events DROP COLUMN recurrence_rule;
@@ -0,0 +1,23 @@
+ALTER TABLE events ADD COLUMN recurrence_rule text;
+
+CREATE INDEX events_recurring_starts_at_idx ON events (starts_at)
+    WHERE recurrence_rule IS NOT NULL;
+
+-- Occurrences are identified by the start the rule gives them.
+CREATE TABLE event_exception_dates (
+    event_id uuid NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    occurs_at timestamptz NOT NULL,
+    PRIMARY KEY (event_id, occurs_at)
+);
+
+-- Null columns follow the series.
+CREATE TABLE event_occurrence_overrides (
+    event_id uuid NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    occurs_at timestamptz NOT NULL,
+    title text,
+    description text,
+    starts_at timestamptz,
+    ends_at timestamptz,
+    PRIMARY KEY (event_id, occurs_at),
+    CONSTRAINT event_occurrence_overrides_ends_at_check CHECK (ends_at >= starts_at)
+);
@@ -0,0 +1,8 @@
+DROP FUNCTION haversine_km(float8, float8, float8, float8);
+
+DROP INDEX events_venue_id_idx;
+ALTER TABLE events
+    DROP COLUMN online_url,
+    DROP COLUMN venue_id;
+
+DROP TABLE venues;
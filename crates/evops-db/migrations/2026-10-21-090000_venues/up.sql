@@ -0,0 +1,33 @@
+CREATE TABLE venues (
+    id uuid PRIMARY KEY,
+    name text NOT NULL,
+    address text NOT NULL,
+    latitude float8 NOT NULL CHECK (latitude BETWEEN -90 AND 90),
+    longitude float8 NOT NULL CHECK (longitude BETWEEN -180 AND 180)
+);
+
+-- Bounding boxes narrow the latitude first.
+CREATE INDEX venues_location_idx ON venues (latitude, longitude);
+
+ALTER TABLE events
+    ADD COLUMN venue_id uuid REFERENCES venues (id),
+    ADD COLUMN online_url text;
+
+CREATE INDEX events_venue_id_idx ON events (venue_id);
+
+-- Great-circle distance on a spherical Earth with the mean radius. Strict, so
+-- that a missing point gives a missing distance.
+CREATE FUNCTION haversine_km(
+    latitude1 float8,
+    longitude1 float8,
+    latitude2 float8,
+    longitude2 float8
+) RETURNS float8
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    AS $$
+        SELECT 2 * 6371.0088 * asin(sqrt(least(1,
+            sin(radians(latitude2 - latitude1) / 2) ^ 2
+            + cos(radians(latitude1)) * cos(radians(latitude2))
+            * sin(radians(longitude2 - longitude1) / 2) ^ 2
+        )))
+    $$;
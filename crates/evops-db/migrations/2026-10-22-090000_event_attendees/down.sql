@@ -0,0 +1,3 @@
+DROP TABLE event_attendees;
+
+ALTER TABLE events DROP COLUMN capacity;
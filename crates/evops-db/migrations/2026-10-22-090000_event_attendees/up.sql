@@ -0,0 +1,17 @@
+-- Null for events without a limit.
+ALTER TABLE events ADD COLUMN capacity integer CHECK (capacity > 0);
+
+CREATE TABLE event_attendees (
+    event_id uuid NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    status text NOT NULL,
+    -- Orders the waitlist.
+    responded_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (event_id, user_id),
+    CONSTRAINT event_attendees_status_check
+        CHECK (status IN ('going', 'interested', 'declined', 'waitlisted'))
+);
+
+-- Counts by status and the waitlist in order.
+CREATE INDEX event_attendees_status_idx ON event_attendees (event_id, status, responded_at);
+CREATE INDEX event_attendees_user_id_idx ON event_attendees (user_id);
@@ -0,0 +1,3 @@
+ALTER TABLE event_attendees
+    DROP COLUMN checked_in_at,
+    DROP COLUMN check_in_token_blake3;
@@ -0,0 +1,4 @@
+-- Only the hash of a check-in token is stored, like for refresh tokens.
+ALTER TABLE event_attendees
+    ADD COLUMN check_in_token_blake3 bytea UNIQUE,
+    ADD COLUMN checked_in_at timestamptz;
@@ -0,0 +1,19 @@
+-- Deleted comments stay as placeholders, so that their replies keep their
+-- place in the thread, but lose their body.
+CREATE TABLE event_comments (
+    id uuid PRIMARY KEY,
+    event_id uuid NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    author_id uuid NOT NULL REFERENCES users (id),
+    parent_id uuid REFERENCES event_comments (id) ON DELETE CASCADE,
+    body text NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    edited_at timestamptz,
+    deleted_at timestamptz,
+    CONSTRAINT event_comments_body_check CHECK ((body = '') = (deleted_at IS NOT NULL))
+);
+
+-- Threads are listed by their top-level comment, oldest first.
+CREATE INDEX event_comments_threads_idx ON event_comments (event_id, id)
+    WHERE parent_id IS NULL;
+CREATE INDEX event_comments_parent_id_idx ON event_comments (parent_id);
+CREATE INDEX event_comments_author_id_idx ON event_comments (author_id);
@@ -0,0 +1,2 @@
+DROP TABLE event_reactions;
+DROP TABLE event_bookmarks;
@@ -0,0 +1,22 @@
+CREATE TABLE event_bookmarks (
+    user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    event_id uuid NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, event_id)
+);
+
+-- Bookmarks are listed newest first.
+CREATE INDEX event_bookmarks_user_id_created_at_idx
+    ON event_bookmarks (user_id, created_at, event_id);
+CREATE INDEX event_bookmarks_event_id_idx ON event_bookmarks (event_id);
+
+CREATE TABLE event_reactions (
+    event_id uuid NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    reaction text NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (event_id, user_id, reaction),
+    CONSTRAINT event_reactions_reaction_check CHECK (char_length(reaction) BETWEEN 1 AND 32)
+);
+
+CREATE INDEX event_reactions_user_id_idx ON event_reactions (user_id);
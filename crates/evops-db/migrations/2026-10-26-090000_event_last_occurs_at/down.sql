@@ -0,0 +1 @@
+ALTER TABLE events DROP COLUMN last_occurs_at;
@@ -0,0 +1,5 @@
+-- A moment no occurrence of a recurring event starts after, so that listing
+-- occurrences can skip series that ended before the window. Null when the
+-- rule repeats forever. Rules set before this column existed stay null until
+-- they're set again, which only makes them slower to list.
+ALTER TABLE events ADD COLUMN last_occurs_at timestamptz;
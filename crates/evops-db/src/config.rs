@@ -0,0 +1,374 @@
+use std::env;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use eyre::{Context as _, eyre};
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+    pub url: Url,
+    pub statement_timeout: Option<Duration>,
+    /// Queries running at least this long are logged at warn level.
+    pub slow_query_threshold: Option<Duration>,
+    pub application_name: Option<String>,
+    pub tls: TlsConfig,
+    /// Schemas to put on the `search_path` of every connection, in order.
+    pub search_path: Vec<String>,
+    pub migrations: MigrationMode,
+    pub pool: PoolConfig,
+    pub retry: RetryConfig,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    pub mode: TlsMode,
+    /// PEM-encoded certificate to trust in addition to the system roots,
+    /// e.g. the self-signed certificate of a local Postgres.
+    pub root_cert: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TlsMode {
+    #[default]
+    Disable,
+    /// Encrypt the connection without verifying the server certificate.
+    Require,
+    /// Encrypt the connection and verify both the certificate chain and the
+    /// host name.
+    VerifyFull,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MigrationMode {
+    /// Apply all pending migrations on startup.
+    #[default]
+    Run,
+    /// Don't look at migrations at all.
+    Skip,
+    /// Refuse to start if there are pending migrations, leaving them to a
+    /// dedicated deploy job.
+    FailIfPending,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    pub max_size: u32,
+    pub min_idle: Option<u32>,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Option<Duration>,
+    /// Ping connections before handing them out, so that connections broken
+    /// by a backend restart are discarded and replaced transparently.
+    pub test_on_check_out: bool,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 10,
+            min_idle: None,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            test_on_check_out: true,
+        }
+    }
+}
+
+/// Retry policy for transactions failing with serialization failures,
+/// deadlocks or connections lost before the commit.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryConfig {
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(20),
+            max_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+impl DatabaseConfig {
+    #[must_use]
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            statement_timeout: None,
+            slow_query_threshold: Some(Duration::from_millis(500)),
+            application_name: None,
+            tls: TlsConfig::default(),
+            search_path: Vec::new(),
+            migrations: MigrationMode::default(),
+            pool: PoolConfig::default(),
+            retry: RetryConfig::default(),
+        }
+    }
+
+    /// Reads the configuration from `DATABASE_*` environment variables.
+    ///
+    /// `DATABASE_URL` is required; every other variable falls back to the
+    /// default when unset.
+    pub fn from_env() -> eyre::Result<Self> {
+        let url = self::required_env_var("DATABASE_URL")?;
+        let mut config = Self::new(url);
+
+        if let Some(ms) = self::env_var("DATABASE_STATEMENT_TIMEOUT_MS")? {
+            config.statement_timeout = Some(Duration::from_millis(ms));
+        }
+        if let Some(ms) = self::env_var::<u64>("DATABASE_SLOW_QUERY_THRESHOLD_MS")? {
+            // Zero turns the slow-query log off.
+            config.slow_query_threshold = (ms > 0).then(|| Duration::from_millis(ms));
+        }
+        config.application_name = self::env_var("DATABASE_APPLICATION_NAME")?;
+        if let Some(mode) = self::env_var("DATABASE_TLS_MODE")? {
+            config.tls.mode = mode;
+        }
+        config.tls.root_cert = self::env_var("DATABASE_TLS_ROOT_CERT")?;
+        if let Some(search_path) = self::env_var::<String>("DATABASE_SEARCH_PATH")? {
+            config.search_path = {
+                search_path
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|schema| !schema.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect()
+            };
+        }
+        if let Some(mode) = self::env_var("DATABASE_MIGRATIONS")? {
+            config.migrations = mode;
+        }
+
+        if let Some(max_size) = self::env_var("DATABASE_POOL_MAX_SIZE")? {
+            config.pool.max_size = max_size;
+        }
+        if let Some(min_idle) = self::env_var("DATABASE_POOL_MIN_IDLE")? {
+            config.pool.min_idle = Some(min_idle);
+        }
+        if let Some(ms) = self::env_var("DATABASE_POOL_ACQUIRE_TIMEOUT_MS")? {
+            config.pool.acquire_timeout = Duration::from_millis(ms);
+        }
+        if let Some(ms) = self::env_var("DATABASE_POOL_IDLE_TIMEOUT_MS")? {
+            config.pool.idle_timeout = Some(Duration::from_millis(ms));
+        }
+        if let Some(max_attempts) = self::env_var("DATABASE_RETRY_MAX_ATTEMPTS")? {
+            config.retry.max_attempts = max_attempts;
+        }
+
+        Ok(config)
+    }
+
+    /// URL for the synchronous libpq connection used to run migrations.
+    /// The statement timeout is left out on purpose, since migrations may
+    /// legitimately run for a long time.
+    pub(crate) fn migration_url(&self) -> Url {
+        let mut url = self.url_with_session_options(None);
+        self::set_query_pair(&mut url, "sslmode", self.tls.mode.libpq_name());
+        if let Some(root_cert) = &self.tls.root_cert {
+            self::set_query_pair(&mut url, "sslrootcert", &root_cert.to_string_lossy());
+        }
+        url
+    }
+
+    /// URL for the pooled `tokio-postgres` connections. TLS is negotiated by
+    /// the connector itself, since `tokio-postgres` only knows the `disable`,
+    /// `prefer` and `require` SSL modes.
+    pub(crate) fn pool_url(&self) -> Url {
+        let mut url = self.url_with_session_options(self.statement_timeout);
+        self::set_query_pair(
+            &mut url,
+            "sslmode",
+            match self.tls.mode {
+                TlsMode::Disable => "disable",
+                TlsMode::Require | TlsMode::VerifyFull => "require",
+            },
+        );
+        url
+    }
+
+    fn url_with_session_options(&self, statement_timeout: Option<Duration>) -> Url {
+        let mut url = self.url.clone();
+
+        let mut options = Vec::new();
+        if let Some(timeout) = statement_timeout {
+            options.push(format!("-c statement_timeout={}", timeout.as_millis()));
+        }
+        if !self.search_path.is_empty() {
+            options.push(format!("-c search_path={}", self.search_path.join(",")));
+        }
+
+        {
+            let mut query = url.query_pairs_mut();
+            if let Some(application_name) = &self.application_name {
+                query.append_pair("application_name", application_name);
+            }
+            if !options.is_empty() {
+                query.append_pair("options", &options.join(" "));
+            }
+        }
+        url
+    }
+}
+
+impl TlsMode {
+    const fn libpq_name(self) -> &'static str {
+        match self {
+            Self::Disable => "disable",
+            Self::Require => "require",
+            Self::VerifyFull => "verify-full",
+        }
+    }
+}
+
+impl FromStr for TlsMode {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "disable" => Ok(Self::Disable),
+            "require" => Ok(Self::Require),
+            "verify-full" => Ok(Self::VerifyFull),
+            _ => Err(eyre!("unknown TLS mode `{s}`")),
+        }
+    }
+}
+
+impl FromStr for MigrationMode {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "run" => Ok(Self::Run),
+            "skip" => Ok(Self::Skip),
+            "fail-if-pending" => Ok(Self::FailIfPending),
+            _ => Err(eyre!("unknown migration mode `{s}`")),
+        }
+    }
+}
+
+/// Sets the `key` query parameter of `url` to `value`, replacing the values
+/// the URL already had for it.
+fn set_query_pair(url: &mut Url, key: &str, value: &str) {
+    let pairs: Vec<(String, String)> = {
+        url.query_pairs()
+            .filter(|(name, _)| name != key)
+            .map(|(name, value)| (name.into_owned(), value.into_owned()))
+            .collect()
+    };
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(key, value);
+}
+
+fn env_var<T>(name: &str) -> eyre::Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match env::var(name) {
+        Ok(value) => {
+            let parsed = value
+                .parse()
+                .map_err(|e| eyre!("{e}"))
+                .wrap_err(name.to_owned())?;
+            Ok(Some(parsed))
+        }
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(e) => Err(e).wrap_err(name.to_owned()),
+    }
+}
+
+fn required_env_var<T>(name: &str) -> eyre::Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    self::env_var(name)?.ok_or_else(|| eyre!("{name} is not set"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(url: &str) -> DatabaseConfig {
+        DatabaseConfig::new(url.parse().unwrap())
+    }
+
+    fn values<'a>(url: &'a Url, key: &str) -> Vec<std::borrow::Cow<'a, str>> {
+        url.query_pairs()
+            .filter(|(name, _)| name == key)
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    #[test]
+    fn urls_replace_the_ssl_mode_of_the_configured_url() {
+        let mut config =
+            self::config("postgres://localhost/evops?sslmode=prefer&connect_timeout=5");
+        config.tls.mode = TlsMode::VerifyFull;
+
+        let migration_url = config.migration_url();
+        assert_eq!(self::values(&migration_url, "sslmode"), ["verify-full"]);
+        assert_eq!(self::values(&migration_url, "connect_timeout"), ["5"]);
+        let pool_url = config.pool_url();
+        assert_eq!(self::values(&pool_url, "sslmode"), ["require"]);
+        assert_eq!(self::values(&pool_url, "connect_timeout"), ["5"]);
+    }
+
+    #[test]
+    fn migration_url_replaces_the_root_certificate_of_the_configured_url() {
+        let mut config = self::config("postgres://localhost/evops?sslrootcert=old.pem");
+        config.tls.root_cert = Some(PathBuf::from("new.pem"));
+
+        let url = config.migration_url();
+        assert_eq!(self::values(&url, "sslrootcert"), ["new.pem"]);
+    }
+
+    #[test]
+    fn urls_disable_tls_by_default() {
+        let config = self::config("postgres://localhost/evops");
+
+        let migration_url = config.migration_url();
+        assert_eq!(self::values(&migration_url, "sslmode"), ["disable"]);
+        let pool_url = config.pool_url();
+        assert_eq!(self::values(&pool_url, "sslmode"), ["disable"]);
+    }
+
+    #[test]
+    fn backoff_doubles_from_the_initial_backoff() {
+        let retry = RetryConfig {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(20),
+            max_backoff: Duration::from_secs(10),
+        };
+        let backoffs: Vec<_> = (1..=4).map(|attempt| retry.backoff(attempt)).collect();
+        assert_eq!(backoffs, [20, 40, 80, 160].map(Duration::from_millis));
+    }
+
+    #[test]
+    fn backoff_is_capped_at_the_max_backoff() {
+        let retry = RetryConfig::default();
+        assert_eq!(retry.backoff(5), Duration::from_millis(320));
+        assert_eq!(retry.backoff(6), retry.max_backoff);
+        // The factor saturates instead of overflowing.
+        assert_eq!(retry.backoff(100), retry.max_backoff);
+        assert_eq!(retry.backoff(u32::MAX), retry.max_backoff);
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+
+use diesel::result::DatabaseErrorKind;
+
+use evops_models::ApiError;
+
+mod constraint;
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+/// Service-layer error that keeps raw diesel errors around until they leave
+/// the crate, so that the transaction retry loop can still classify them.
+#[derive(Debug)]
+pub(crate) enum Error {
+    Api(ApiError),
+    Diesel(diesel::result::Error),
+}
+
+impl Error {
+    /// Whether Postgres rolled the transaction back in a way that running it
+    /// again might fix, that is after a serialization failure or a deadlock.
+    pub(crate) fn is_transient(&self) -> bool {
+        match self {
+            Self::Api(_) => false,
+            Self::Diesel(e) => match e {
+                diesel::result::Error::DatabaseError(kind, info) => match kind {
+                    DatabaseErrorKind::SerializationFailure => true,
+                    DatabaseErrorKind::Unknown => info.message().starts_with("deadlock detected"),
+                    _ => false,
+                },
+                _ => false,
+            },
+        }
+    }
+
+    /// Whether the connection broke, including when the server terminated
+    /// it. The open transaction went down with it, unless it broke while
+    /// committing, when the commit may have gone through.
+    pub(crate) fn is_connection_lost(&self) -> bool {
+        match self {
+            Self::Api(_) => false,
+            Self::Diesel(e) => match e {
+                diesel::result::Error::DatabaseError(kind, info) => match kind {
+                    DatabaseErrorKind::ClosedConnection
+                    | DatabaseErrorKind::UnableToSendCommand => true,
+                    DatabaseErrorKind::Unknown => {
+                        info.message().starts_with("terminating connection")
+                    }
+                    _ => false,
+                },
+                _ => false,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api(e) => e.fmt(f),
+            Self::Diesel(e) => e.fmt(f),
+        }
+    }
+}
+
+impl From<ApiError> for Error {
+    fn from(value: ApiError) -> Self {
+        Self::Api(value)
+    }
+}
+
+/// Constraint violations are translated into the matching [`ApiError`] right
+/// away, every other diesel error is kept as is.
+impl From<diesel::result::Error> for Error {
+    fn from(value: diesel::result::Error) -> Self {
+        let translated = match &value {
+            diesel::result::Error::DatabaseError(kind, info) => {
+                self::constraint::translate(kind, info.as_ref())
+            }
+            _ => None,
+        };
+        translated.map_or(Self::Diesel(value), Self::Api)
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(value: Error) -> Self {
+        match value {
+            Error::Api(e) => e,
+            Error::Diesel(e) => e.into(),
+        }
+    }
+}
@@ -0,0 +1,350 @@
+//! Translation of constraint violations reported by Postgres into
+//! [`ApiError`]s that name the offending entity.
+
+use diesel::result::{DatabaseErrorInformation, DatabaseErrorKind};
+
+use evops_models::ApiError;
+
+/// Returns `None` for errors that aren't constraint violations.
+pub(super) fn translate(
+    kind: &DatabaseErrorKind,
+    info: &(dyn DatabaseErrorInformation + Send + Sync),
+) -> Option<ApiError> {
+    let constraint = info.constraint_name().unwrap_or_default();
+    let key = info.details().map(self::parse_key).unwrap_or_default();
+    let value = |column: &str| {
+        key.iter()
+            .find(|(key_column, _)| *key_column == column)
+            .map_or("?", |&(_, value)| value)
+    };
+
+    let api_error = match kind {
+        DatabaseErrorKind::ForeignKeyViolation => {
+            let details = info.details().unwrap_or_default();
+            let (_, id) = key.first().copied().unwrap_or(("", "?"));
+            if let Some(referenced_table) = self::quoted_after(details, "is not present in table ")
+            {
+                let entity = self::entity_name(referenced_table);
+                ApiError::NotFound(format!("No {entity} with ID {id} found."))
+            } else {
+                let referenced_table = {
+                    self::quoted_after(info.message(), "update or delete on table ")
+                        .unwrap_or_default()
+                };
+                let entity = self::capitalized(self::entity_name(referenced_table));
+                let referencing_table = info.table_name().unwrap_or_default();
+                ApiError::InvalidArgument(format!(
+                    "{entity} {id} is still referenced from {referencing_table}.",
+                ))
+            }
+        }
+        DatabaseErrorKind::UniqueViolation => ApiError::AlreadyExists(match constraint {
+            "users_user_login_key" => {
+                format!("User with login {} already exists.", value("user_login"))
+            }
+            "refresh_tokens_pkey" | "refresh_tokens_token_blake3_key" => {
+                "Refresh token already exists.".to_owned()
+            }
+            "event_attendees_check_in_token_blake3_key" => {
+                "Check-in token already exists.".to_owned()
+            }
+            "tags_name_key" => format!("Tag with name {} already exists.", value("name")),
+            "tag_aliases_pkey" => format!(
+                "Tag {} already has alias {}.",
+                value("tag_id"),
+                value("alias"),
+            ),
+            "events_to_tags_pkey" => format!(
+                "Event {} already has tag {}.",
+                value("event_id"),
+                value("tag_id"),
+            ),
+            "event_images_event_id_position_key" => format!(
+                "Event {} already has an image at position {}.",
+                value("event_id"),
+                value("position"),
+            ),
+            _ => {
+                let table = info.table_name().unwrap_or_default();
+                let entity = self::capitalized(self::entity_name(table));
+                format!("{entity} with ID {} already exists.", value("id"))
+            }
+        }),
+        DatabaseErrorKind::CheckViolation => ApiError::InvalidArgument(match constraint {
+            "events_ends_at_check" | "event_occurrence_overrides_ends_at_check" => {
+                "An event can't end before it starts.".to_owned()
+            }
+            _ => {
+                let table = info.table_name().unwrap_or_default();
+                format!(
+                    "Invalid {}: {constraint} is violated.",
+                    self::entity_name(table)
+                )
+            }
+        }),
+        DatabaseErrorKind::NotNullViolation => ApiError::InvalidArgument({
+            let table = info.table_name().unwrap_or_default();
+            let column = info.column_name().unwrap_or_default();
+            format!("The {} {column} must be set.", self::entity_name(table))
+        }),
+        _ => return None,
+    };
+    Some(api_error)
+}
+
+/// Parses details like `Key (event_id, tag_id)=(…, …) already exists.` into
+/// column-value pairs. The last value takes the rest of the key, so it may
+/// contain commas.
+fn parse_key(details: &str) -> Vec<(&str, &str)> {
+    let key = details.strip_prefix("Key (");
+    let Some((columns, rest)) = key.and_then(|key| key.split_once(")=(")) else {
+        return Vec::new();
+    };
+    let Some((values, _)) = rest.rsplit_once(") ") else {
+        return Vec::new();
+    };
+    let columns: Vec<_> = columns.split(", ").map(self::unquoted).collect();
+    columns
+        .iter()
+        .copied()
+        .zip(values.splitn(columns.len(), ", "))
+        .collect()
+}
+
+/// Strips the quotes Postgres puts around column names that are keywords,
+/// like `"position"`.
+fn unquoted(column: &str) -> &str {
+    column
+        .strip_prefix('"')
+        .and_then(|column| column.strip_suffix('"'))
+        .unwrap_or(column)
+}
+
+/// Returns the double-quoted name following `prefix` in `text`.
+fn quoted_after<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let (_, rest) = text.split_once(prefix)?;
+    let rest = rest.strip_prefix('"')?;
+    let (name, _) = rest.split_once('"')?;
+    Some(name)
+}
+
+fn entity_name(table: &str) -> &str {
+    match table {
+        "event_attendees" => "attendee",
+        "event_bookmarks" => "bookmark",
+        "event_comments" => "comment",
+        "event_exception_dates" => "exception date",
+        "event_images" => "image",
+        "event_occurrence_overrides" => "occurrence override",
+        "event_reactions" => "reaction",
+        "events_to_tags" => "event tag",
+        "refresh_tokens" => "refresh token",
+        "tag_aliases" => "tag alias",
+        _ => table.strip_suffix('s').unwrap_or(table),
+    }
+}
+
+fn capitalized(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENT_ID: &str = "0190c1a2-7b3c-7d4e-8f50-617283940a1b";
+    const TAG_ID: &str = "0190c1a2-7b3c-7d4e-8f50-617283940a1c";
+
+    #[derive(Default)]
+    struct Info {
+        message: &'static str,
+        details: Option<&'static str>,
+        table_name: Option<&'static str>,
+        column_name: Option<&'static str>,
+        constraint_name: Option<&'static str>,
+    }
+
+    impl DatabaseErrorInformation for Info {
+        fn message(&self) -> &str {
+            self.message
+        }
+
+        fn details(&self) -> Option<&str> {
+            self.details
+        }
+
+        fn hint(&self) -> Option<&str> {
+            None
+        }
+
+        fn table_name(&self) -> Option<&str> {
+            self.table_name
+        }
+
+        fn column_name(&self) -> Option<&str> {
+            self.column_name
+        }
+
+        fn constraint_name(&self) -> Option<&str> {
+            self.constraint_name
+        }
+
+        fn statement_position(&self) -> Option<i32> {
+            None
+        }
+    }
+
+    #[test]
+    fn parse_key_pairs_composite_keys() {
+        let details = format!("Key (event_id, tag_id)=({EVENT_ID}, {TAG_ID}) already exists.");
+        assert_eq!(
+            self::parse_key(&details),
+            [("event_id", EVENT_ID), ("tag_id", TAG_ID)],
+        );
+    }
+
+    #[test]
+    fn parse_key_leaves_commas_and_parentheses_to_the_last_value() {
+        let details = format!("Key (tag_id, alias)=({TAG_ID}, rock (live), pop) already exists.");
+        assert_eq!(
+            self::parse_key(&details),
+            [("tag_id", TAG_ID), ("alias", "rock (live), pop")],
+        );
+    }
+
+    #[test]
+    fn parse_key_unquotes_keyword_columns() {
+        let details = format!("Key (event_id, \"position\")=({EVENT_ID}, 1) already exists.");
+        assert_eq!(
+            self::parse_key(&details),
+            [("event_id", EVENT_ID), ("position", "1")],
+        );
+    }
+
+    #[test]
+    fn parse_key_ignores_other_details() {
+        assert!(self::parse_key("Failing row contains (1, 2).").is_empty());
+    }
+
+    #[test]
+    fn unique_violations_name_the_duplicate_key() {
+        let info = Info {
+            details: Some("Key (event_id, \"position\")=(e, 2) already exists."),
+            table_name: Some("event_images"),
+            constraint_name: Some("event_images_event_id_position_key"),
+            ..Info::default()
+        };
+        let error = self::translate(&DatabaseErrorKind::UniqueViolation, &info);
+        assert!(matches!(
+            error,
+            Some(ApiError::AlreadyExists(message))
+                if message == "Event e already has an image at position 2.",
+        ));
+    }
+
+    #[test]
+    fn unique_violations_of_unknown_constraints_name_the_id() {
+        let info = Info {
+            details: Some("Key (id)=(v) already exists."),
+            table_name: Some("venues"),
+            constraint_name: Some("venues_pkey"),
+            ..Info::default()
+        };
+        let error = self::translate(&DatabaseErrorKind::UniqueViolation, &info);
+        assert!(matches!(
+            error,
+            Some(ApiError::AlreadyExists(message)) if message == "Venue with ID v already exists.",
+        ));
+    }
+
+    #[test]
+    fn missing_references_are_not_found() {
+        let info = Info {
+            message: "insert or update on table \"events_to_tags\" violates foreign key \
+                      constraint \"events_to_tags_tag_id_fkey\"",
+            details: Some("Key (tag_id)=(t) is not present in table \"tags\"."),
+            table_name: Some("events_to_tags"),
+            constraint_name: Some("events_to_tags_tag_id_fkey"),
+            ..Info::default()
+        };
+        let error = self::translate(&DatabaseErrorKind::ForeignKeyViolation, &info);
+        assert!(matches!(
+            error,
+            Some(ApiError::NotFound(message)) if message == "No tag with ID t found.",
+        ));
+    }
+
+    #[test]
+    fn rows_still_referenced_are_invalid_arguments() {
+        let info = Info {
+            message: "update or delete on table \"events\" violates foreign key constraint \
+                      \"events_to_tags_event_id_fkey\" on table \"events_to_tags\"",
+            details: Some("Key (id)=(e) is still referenced from table \"events_to_tags\"."),
+            table_name: Some("events_to_tags"),
+            constraint_name: Some("events_to_tags_event_id_fkey"),
+            ..Info::default()
+        };
+        let error = self::translate(&DatabaseErrorKind::ForeignKeyViolation, &info);
+        assert!(matches!(
+            error,
+            Some(ApiError::InvalidArgument(message))
+                if message == "Event e is still referenced from events_to_tags.",
+        ));
+    }
+
+    #[test]
+    fn check_violations_explain_known_constraints() {
+        let info = Info {
+            table_name: Some("events"),
+            constraint_name: Some("events_ends_at_check"),
+            ..Info::default()
+        };
+        let error = self::translate(&DatabaseErrorKind::CheckViolation, &info);
+        assert!(matches!(
+            error,
+            Some(ApiError::InvalidArgument(message))
+                if message == "An event can't end before it starts.",
+        ));
+    }
+
+    #[test]
+    fn check_violations_name_unknown_constraints() {
+        let info = Info {
+            table_name: Some("venues"),
+            constraint_name: Some("venues_latitude_check"),
+            ..Info::default()
+        };
+        let error = self::translate(&DatabaseErrorKind::CheckViolation, &info);
+        assert!(matches!(
+            error,
+            Some(ApiError::InvalidArgument(message))
+                if message == "Invalid venue: venues_latitude_check is violated.",
+        ));
+    }
+
+    #[test]
+    fn not_null_violations_name_the_column() {
+        let info = Info {
+            table_name: Some("event_comments"),
+            column_name: Some("body"),
+            ..Info::default()
+        };
+        let error = self::translate(&DatabaseErrorKind::NotNullViolation, &info);
+        assert!(matches!(
+            error,
+            Some(ApiError::InvalidArgument(message)) if message == "The comment body must be set.",
+        ));
+    }
+
+    #[test]
+    fn other_errors_are_left_alone() {
+        let info = Info {
+            message: "could not serialize access due to concurrent update",
+            ..Info::default()
+        };
+        assert!(self::translate(&DatabaseErrorKind::SerializationFailure, &info).is_none());
+    }
+}
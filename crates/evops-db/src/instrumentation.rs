@@ -0,0 +1,142 @@
+//! Per-query timing, slow-query logging and the metrics exported through the
+//! [`metrics`] facade.
+//!
+//! Exported metrics:
+//! - `evops_db_queries_total` (counter, labels: `outcome`)
+//! - `evops_db_query_duration_seconds` (histogram)
+//! - `evops_db_slow_queries_total` (counter)
+//! - `evops_db_transaction_retries_total` (counter, labels: `operation`)
+//! - `evops_db_connection_checkout_failures_total` (counter)
+
+use std::time::{Duration, Instant};
+
+use diesel::connection::{Instrumentation, InstrumentationEvent};
+use diesel_async::pooled_connection::ManagerConfig;
+use diesel_async::{AsyncConnection as _, AsyncPgConnection};
+use tracing::{debug, warn};
+
+pub(crate) const QUERIES_TOTAL: &str = "evops_db_queries_total";
+pub(crate) const QUERY_DURATION_SECONDS: &str = "evops_db_query_duration_seconds";
+pub(crate) const SLOW_QUERIES_TOTAL: &str = "evops_db_slow_queries_total";
+pub(crate) const TRANSACTION_RETRIES_TOTAL: &str = "evops_db_transaction_retries_total";
+pub(crate) const CONNECTION_CHECKOUT_FAILURES_TOTAL: &str =
+    "evops_db_connection_checkout_failures_total";
+
+/// Installed on every pooled connection.
+pub(crate) struct QueryInstrumentation {
+    slow_query_threshold: Option<Duration>,
+    /// Start of the query currently running on the connection.
+    started_at: Option<Instant>,
+}
+
+impl QueryInstrumentation {
+    pub(crate) const fn new(slow_query_threshold: Option<Duration>) -> Self {
+        Self {
+            slow_query_threshold,
+            started_at: None,
+        }
+    }
+
+    fn is_slow(&self, elapsed: Duration) -> bool {
+        self.slow_query_threshold
+            .is_some_and(|threshold| elapsed >= threshold)
+    }
+}
+
+/// Cuts the bind values off the debug representation of a query.
+fn strip_binds(query: &str) -> &str {
+    query
+        .split_once(" -- binds: ")
+        .map_or(query, |(sql, _)| sql)
+}
+
+impl Instrumentation for QueryInstrumentation {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { .. } => {
+                self.started_at = Some(Instant::now());
+            }
+            InstrumentationEvent::FinishQuery { query, error, .. } => {
+                let Some(started_at) = self.started_at.take() else {
+                    return;
+                };
+                let elapsed = started_at.elapsed();
+                let outcome = if error.is_some() { "error" } else { "ok" };
+                metrics::counter!(QUERIES_TOTAL, "outcome" => outcome).increment(1);
+                metrics::histogram!(QUERY_DURATION_SECONDS).record(elapsed.as_secs_f64());
+
+                if self.is_slow(elapsed) {
+                    metrics::counter!(SLOW_QUERIES_TOTAL).increment(1);
+                    // Bind values may hold password and token hashes, so only
+                    // the statement itself is logged.
+                    let query = query.to_string();
+                    let sql = self::strip_binds(&query);
+                    warn!(?elapsed, sql, "slow query");
+                } else {
+                    debug!(?elapsed, outcome, "query finished");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Wraps the connection setup of `manager_config` so that every new
+/// connection gets a [`QueryInstrumentation`].
+pub(crate) fn instrument_connections(
+    mut manager_config: ManagerConfig<AsyncPgConnection>,
+    slow_query_threshold: Option<Duration>,
+) -> ManagerConfig<AsyncPgConnection> {
+    let establish = manager_config.custom_setup;
+    manager_config.custom_setup = Box::new(move |url| {
+        let connecting = establish(url);
+        Box::pin(async move {
+            let mut conn = connecting.await?;
+            conn.set_instrumentation(QueryInstrumentation::new(slow_query_threshold));
+            Ok(conn)
+        })
+    });
+    manager_config
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::pg::Pg;
+    use diesel::{ExpressionMethods as _, QueryDsl as _};
+
+    use super::*;
+    use crate::schema::users;
+
+    #[test]
+    fn queries_are_slow_from_the_threshold_on() {
+        let instrumentation = QueryInstrumentation::new(Some(Duration::from_millis(100)));
+        assert!(!instrumentation.is_slow(Duration::from_millis(99)));
+        assert!(instrumentation.is_slow(Duration::from_millis(100)));
+        assert!(instrumentation.is_slow(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn no_query_is_slow_without_a_threshold() {
+        let instrumentation = QueryInstrumentation::new(None);
+        assert!(!instrumentation.is_slow(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn bind_values_are_stripped() {
+        let query = users::table
+            .filter(users::password_argon2.eq("secret-hash"))
+            .select(users::id);
+        let query = diesel::debug_query::<Pg, _>(&query).to_string();
+        assert!(query.contains("secret-hash"));
+
+        let sql = self::strip_binds(&query);
+        assert!(sql.starts_with("SELECT"));
+        assert!(sql.contains("\"password_argon2\" = $1"));
+        assert!(!sql.contains("secret-hash"));
+    }
+
+    #[test]
+    fn queries_without_binds_are_kept_whole() {
+        assert_eq!(self::strip_binds("SELECT 1"), "SELECT 1");
+    }
+}
@@ -1,36 +1,105 @@
-use diesel::{Connection, PgConnection};
-use diesel_async::{AsyncConnection as _, AsyncPgConnection};
-use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
-use eyre::{Context, eyre};
-use tracing::debug;
-use url::Url;
+use std::sync::Arc;
 
+use diesel::result::DatabaseErrorKind;
+use diesel_async::AsyncPgConnection;
+use diesel_async::pooled_connection::AsyncDieselConnectionManager;
+use diesel_async::pooled_connection::bb8::{Pool, PooledConnection, RunError};
+use diesel_migrations::{EmbeddedMigrations, embed_migrations};
+use eyre::Context as _;
+
+use evops_models::{ApiError, ApiResult};
+
+/// Needed to box the callbacks passed to [`Database::transaction`].
+pub use diesel_async::scoped_futures;
+
+pub use self::config::{
+    DatabaseConfig, MigrationMode, PoolConfig, RetryConfig, TlsConfig, TlsMode,
+};
+pub use self::migrations::{MigrationInfo, MigrationStatus};
+pub use self::pagination::{Cursor, Page, Total, TotalCount};
+pub use self::services::{
+    AttendanceStatus, CheckInReport, CheckInTokenHash, CommentId, EventAttendance, EventComment,
+    EventFilter, EventLocation, EventOccurrence, EventPhase, EventSchedule, EventSearchHit,
+    EventSort, EventTimezone, GeoPoint, GeoRadius, HighlightOptions, IntegrityReport,
+    NewCommentForm, NewVenueForm, OccurrenceOverride, Recurrence, RecurrenceRule, TagFacet,
+    TagFilter, TagMatch, TimeRange, Venue, VenueId,
+};
+pub use self::store::{AuthStore, EventStore, MemoryStore, TagStore, UserStore};
+pub use self::transaction::Transaction;
+
+mod config;
+mod error;
+mod instrumentation;
+mod migrations;
 mod models;
+mod pagination;
+mod retry;
 mod schema;
 mod services;
+mod store;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod tls;
+mod transaction;
 
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/");
 
+#[derive(Clone)]
 pub struct Database {
-    conn: AsyncPgConnection,
+    pool: Pool<AsyncPgConnection>,
+    config: Arc<DatabaseConfig>,
 }
 
 impl Database {
-    fn run_migrations(
-        connection: &mut impl MigrationHarness<diesel::pg::Pg>,
-    ) -> diesel::migration::Result<()> {
-        debug!("running database migrations");
-        connection.run_pending_migrations(MIGRATIONS)?;
-        Ok(())
-    }
+    pub async fn establish_connection(config: &DatabaseConfig) -> eyre::Result<Self> {
+        // Migrating uses a blocking connection, which may also wait a long
+        // time for the migration lock while another instance migrates.
+        let migration_config = config.clone();
+        tokio::task::spawn_blocking(move || Self::prepare_migrations(&migration_config)).await??;
 
-    pub async fn establish_connection(database_url: &Url) -> eyre::Result<Self> {
-        Self::run_migrations(&mut PgConnection::establish(database_url.as_str())?)
-            .map_err(|e| eyre!("{e}"))
-            .wrap_err("failed to run migrations")?;
+        let pool_config = &config.pool;
+        let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(
+            config.pool_url(),
+            self::instrumentation::instrument_connections(
+                self::tls::manager_config(&config.tls)?,
+                config.slow_query_threshold,
+            ),
+        );
+        let pool = {
+            Pool::builder()
+                .max_size(pool_config.max_size)
+                .min_idle(pool_config.min_idle)
+                .connection_timeout(pool_config.acquire_timeout)
+                .idle_timeout(pool_config.idle_timeout)
+                .test_on_check_out(pool_config.test_on_check_out)
+                .build(manager)
+                .await
+                .wrap_err("failed to build the connection pool")?
+        };
 
         Ok(Self {
-            conn: AsyncPgConnection::establish(database_url.as_str()).await?,
+            pool,
+            config: Arc::new(config.clone()),
         })
     }
+
+    pub(crate) async fn conn(&self) -> ApiResult<PooledConnection<'_, AsyncPgConnection>> {
+        self.pool.get().await.map_err(self::checkout_error)
+    }
+
+    /// Like [`Self::conn`], but the connection doesn't borrow the pool.
+    pub(crate) async fn owned_conn(
+        &self,
+    ) -> ApiResult<PooledConnection<'static, AsyncPgConnection>> {
+        self.pool.get_owned().await.map_err(self::checkout_error)
+    }
+}
+
+fn checkout_error(e: RunError) -> ApiError {
+    metrics::counter!(self::instrumentation::CONNECTION_CHECKOUT_FAILURES_TOTAL).increment(1);
+    diesel::result::Error::DatabaseError(
+        DatabaseErrorKind::UnableToSendCommand,
+        Box::new(format!("failed to check out a connection: {e}")),
+    )
+    .into()
 }
@@ -0,0 +1,162 @@
+use std::collections::HashSet;
+use std::process;
+
+use diesel::migration::MigrationSource as _;
+use diesel::sql_types::{BigInt, Bool};
+use diesel::{Connection as _, PgConnection, QueryableByName, RunQueryDsl as _};
+use diesel_migrations::MigrationHarness as _;
+use eyre::{Context as _, bail, eyre};
+use tracing::info;
+
+use crate::config::{DatabaseConfig, MigrationMode};
+
+/// Key of the session-level advisory lock serializing migrations between
+/// server instances (the ASCII bytes of `evops_db`).
+pub(crate) const MIGRATION_LOCK_KEY: i64 = 0x6576_6f70_735f_6462;
+
+#[derive(Debug, Clone)]
+pub struct MigrationInfo {
+    pub version: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub applied: Vec<MigrationInfo>,
+    pub pending: Vec<MigrationInfo>,
+}
+
+#[derive(QueryableByName)]
+struct LockAcquired {
+    #[diesel(sql_type = Bool)]
+    acquired: bool,
+}
+
+impl crate::Database {
+    pub(crate) fn prepare_migrations(config: &DatabaseConfig) -> eyre::Result<()> {
+        match config.migrations {
+            MigrationMode::Skip => Ok(()),
+            MigrationMode::Run => Self::run_migrations(config).map(|_| ()),
+            MigrationMode::FailIfPending => {
+                let status = Self::migration_status_blocking(config)?;
+                if !status.pending.is_empty() {
+                    bail!(
+                        "the database has {} pending migrations",
+                        status.pending.len(),
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Applies all pending migrations while holding the migration lock and
+    /// returns the versions that were applied by this instance.
+    pub fn run_migrations(config: &DatabaseConfig) -> eyre::Result<Vec<String>> {
+        let mut conn = PgConnection::establish(config.migration_url().as_str())?;
+        let instance = self::instance_name(config);
+        self::with_migration_lock(&mut conn, &instance, |conn| {
+            let applied = {
+                conn.run_pending_migrations(crate::MIGRATIONS)
+                    .map_err(|e| eyre!("{e}"))
+                    .wrap_err("failed to run migrations")?
+                    .into_iter()
+                    .map(|version| version.to_string())
+                    .collect::<Vec<_>>()
+            };
+            if applied.is_empty() {
+                info!(%instance, "no pending migrations");
+            }
+            for version in &applied {
+                info!(%instance, %version, "applied migration");
+            }
+            Ok(applied)
+        })
+    }
+
+    /// Reverts the most recently applied migration while holding the
+    /// migration lock and returns its version.
+    pub fn revert_last_migration(config: &DatabaseConfig) -> eyre::Result<String> {
+        let mut conn = PgConnection::establish(config.migration_url().as_str())?;
+        let instance = self::instance_name(config);
+        self::with_migration_lock(&mut conn, &instance, |conn| {
+            let version = {
+                conn.revert_last_migration(crate::MIGRATIONS)
+                    .map_err(|e| eyre!("{e}"))
+                    .wrap_err("failed to revert the last migration")?
+                    .to_string()
+            };
+            info!(%instance, %version, "reverted migration");
+            Ok(version)
+        })
+    }
+
+    pub async fn migration_status(&self) -> eyre::Result<MigrationStatus> {
+        let config = self.config.clone();
+        tokio::task::spawn_blocking(move || Self::migration_status_blocking(&config)).await?
+    }
+
+    pub fn migration_status_blocking(config: &DatabaseConfig) -> eyre::Result<MigrationStatus> {
+        let mut conn = PgConnection::establish(config.migration_url().as_str())?;
+        let applied_versions: HashSet<String> = {
+            conn.applied_migrations()
+                .map_err(|e| eyre!("{e}"))?
+                .into_iter()
+                .map(|version| version.to_string())
+                .collect()
+        };
+
+        let mut status = MigrationStatus {
+            applied: Vec::new(),
+            pending: Vec::new(),
+        };
+        let migrations = crate::MIGRATIONS.migrations().map_err(|e| eyre!("{e}"))?;
+        for migration in migrations {
+            let name = migration.name();
+            let info = MigrationInfo {
+                version: name.version().to_string(),
+                name: name.to_string(),
+            };
+            if applied_versions.contains(&info.version) {
+                status.applied.push(info);
+            } else {
+                status.pending.push(info);
+            }
+        }
+        Ok(status)
+    }
+}
+
+pub(crate) fn with_migration_lock<T>(
+    conn: &mut PgConnection,
+    instance: &str,
+    f: impl FnOnce(&mut PgConnection) -> eyre::Result<T>,
+) -> eyre::Result<T> {
+    let LockAcquired { acquired } = {
+        diesel::sql_query("SELECT pg_try_advisory_lock($1) AS acquired")
+            .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+            .get_result(conn)
+            .wrap_err("failed to acquire the migration lock")?
+    };
+    if !acquired {
+        info!(%instance, "another instance is migrating the database, waiting");
+        diesel::sql_query("SELECT pg_advisory_lock($1)")
+            .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+            .execute(conn)
+            .wrap_err("failed to acquire the migration lock")?;
+    }
+    info!(%instance, "acquired the migration lock");
+
+    let result = f(conn);
+
+    diesel::sql_query("SELECT pg_advisory_unlock($1)")
+        .bind::<BigInt, _>(MIGRATION_LOCK_KEY)
+        .execute(conn)
+        .wrap_err("failed to release the migration lock")?;
+    result
+}
+
+pub(crate) fn instance_name(config: &DatabaseConfig) -> String {
+    let application_name = config.application_name.as_deref().unwrap_or("evops-db");
+    format!("{application_name}[{}]", process::id())
+}
@@ -55,6 +55,13 @@ pub struct Event {
     pub author_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub modified_at: DateTime<Utc>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub timezone: String,
+    pub recurrence_rule: Option<String>,
+    pub venue_id: Option<Uuid>,
+    pub online_url: Option<String>,
+    pub capacity: Option<i32>,
 }
 
 #[derive(Queryable, Selectable, Identifiable, Associations)]
@@ -77,3 +84,28 @@ pub struct EventToTag {
     pub event_id: Uuid,
     pub tag_id: Uuid,
 }
+
+#[derive(Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::venues)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Venue {
+    pub id: Uuid,
+    pub name: String,
+    pub address: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Queryable, Selectable, Identifiable)]
+#[diesel(table_name = schema::event_comments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct EventComment {
+    pub id: Uuid,
+    pub event_id: Uuid,
+    pub author_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
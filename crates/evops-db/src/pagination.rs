@@ -0,0 +1,247 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use diesel::QueryResult;
+use diesel::pg::Pg;
+use diesel::query_builder::{AstPass, Query, QueryFragment, QueryId};
+use diesel::sql_types::{BigInt, Text};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+/// Opaque position in a listing. Every page that may be followed by another
+/// one comes with a cursor, which is passed back to get that page.
+///
+/// A cursor carries the whole sort key of the last item on its page, so that
+/// paging stays stable while items are added or removed. It is only valid
+/// for the kind of listing and sort order it was made for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    scope: String,
+    key: Vec<CursorValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum CursorValue {
+    Float(f32),
+    Double(f64),
+    Int(i64),
+    Timestamp(DateTime<Utc>),
+    Uuid(Uuid),
+}
+
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Whether more items follow this page.
+    pub has_more: bool,
+    /// Passed back to get the next page. `None` when no more items follow,
+    /// or when the page is empty.
+    pub next_cursor: Option<Cursor>,
+    /// The number of items on all pages together, if asked for with
+    /// [`TotalCount`].
+    pub total: Option<Total>,
+}
+
+/// Whether and how a listing counts the items on all of its pages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TotalCount {
+    #[default]
+    Skip,
+    /// Costs about as much as listing every item.
+    Exact,
+    /// Takes the query planner's guess, which is cheap but may be far off,
+    /// especially for narrow filters.
+    Estimated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Total {
+    Exact(u64),
+    Estimated(u64),
+}
+
+impl Cursor {
+    pub(crate) const BOOKMARKS: &str = "bookmarks";
+    pub(crate) const COMMENTS: &str = "comments";
+    pub(crate) const TAGS: &str = "tags";
+    pub(crate) const USERS: &str = "users";
+
+    pub(crate) fn new(scope: &str, key: Vec<CursorValue>) -> Self {
+        Self {
+            scope: scope.to_owned(),
+            key,
+        }
+    }
+
+    /// Returns the sort key, making sure the cursor was made for `scope`.
+    pub(crate) fn key(&self, scope: &str) -> ApiResult<&[CursorValue]> {
+        if self.scope != scope {
+            return Err(ApiError::InvalidArgument(format!(
+                "The cursor belongs to a {} listing, not a {scope} one.",
+                self.scope,
+            )));
+        }
+        Ok(&self.key)
+    }
+
+    pub(crate) fn invalid() -> ApiError {
+        ApiError::InvalidArgument("Invalid cursor.".to_owned())
+    }
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut plain = self.scope.clone();
+        for value in &self.key {
+            plain.push(';');
+            plain.push_str(&match value {
+                CursorValue::Float(value) => format!("f{:08x}", value.to_bits()),
+                CursorValue::Double(value) => format!("d{:016x}", value.to_bits()),
+                CursorValue::Int(value) => format!("i{value}"),
+                CursorValue::Timestamp(value) => format!("t{}", value.timestamp_micros()),
+                CursorValue::Uuid(value) => format!("u{}", value.simple()),
+            });
+        }
+        f.write_str(&URL_SAFE_NO_PAD.encode(plain))
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let plain = {
+            URL_SAFE_NO_PAD
+                .decode(s)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or_else(Self::invalid)?
+        };
+        let mut parts = plain.split(';');
+        let scope = parts.next().unwrap_or_default().to_owned();
+        let key = {
+            parts
+                .map(|part| self::parse_value(part).ok_or_else(Self::invalid))
+                .collect::<ApiResult<_>>()?
+        };
+        Ok(Self { scope, key })
+    }
+}
+
+fn parse_value(part: &str) -> Option<CursorValue> {
+    let (kind, value) = part.split_at_checked(1)?;
+    let value = match kind {
+        "f" => CursorValue::Float(f32::from_bits(u32::from_str_radix(value, 16).ok()?)),
+        "d" => CursorValue::Double(f64::from_bits(u64::from_str_radix(value, 16).ok()?)),
+        "i" => CursorValue::Int(value.parse().ok()?),
+        "t" => CursorValue::Timestamp(DateTime::from_timestamp_micros(value.parse().ok()?)?),
+        "u" => CursorValue::Uuid(Uuid::try_parse(value).ok()?),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Listings fetch one item more than `limit`, which tells whether another
+/// page follows.
+pub(crate) fn fetch_limit(limit: Option<i64>) -> Option<i64> {
+    limit.map(|limit| limit.saturating_add(1))
+}
+
+/// Trims `keys`, fetched with [`fetch_limit`], down to `limit`. Returns
+/// whether more items follow and the cursor to them.
+pub(crate) fn split_page<K>(
+    keys: &mut Vec<K>,
+    limit: Option<i64>,
+    cursor: impl FnOnce(&K) -> Cursor,
+) -> (bool, Option<Cursor>) {
+    let Some(limit) = limit.and_then(|limit| usize::try_from(limit).ok()) else {
+        return (false, None);
+    };
+    if keys.len() <= limit {
+        return (false, None);
+    }
+    keys.truncate(limit);
+    (true, keys.last().map(cursor))
+}
+
+/// Counts the rows `query` returns, as asked for by `count`. `query` should
+/// apply the listing's filters, but not its cursor or limit.
+pub(crate) async fn total<Q>(
+    conn: &mut AsyncPgConnection,
+    query: Q,
+    count: TotalCount,
+) -> QueryResult<Option<Total>>
+where
+    Q: QueryFragment<Pg> + Send + 'static,
+{
+    let total = match count {
+        TotalCount::Skip => None,
+        TotalCount::Exact => {
+            let total: i64 = CountRows(query).get_result(conn).await?;
+            Some(Total::Exact(total.unsigned_abs()))
+        }
+        TotalCount::Estimated => {
+            let plan: Vec<String> = Explain(query).load(conn).await?;
+            let total = plan.first().and_then(|line| self::planned_rows(line));
+            Some(Total::Estimated(total.unwrap_or_default()))
+        }
+    };
+    Ok(total)
+}
+
+/// Reads the row estimate off the top node of a query plan, like
+/// `Seq Scan on events  (cost=0.00..1.50 rows=50 width=16)`.
+fn planned_rows(line: &str) -> Option<u64> {
+    let (_, rest) = line.split_once(" rows=")?;
+    let rows = rest.split(' ').next()?;
+    rows.parse().ok()
+}
+
+/// `SELECT count(*) FROM (query) AS counted`
+struct CountRows<Q>(Q);
+
+/// `EXPLAIN query`, which returns the plan as lines of text.
+struct Explain<Q>(Q);
+
+impl<Q> QueryId for CountRows<Q> {
+    type QueryId = ();
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<Q> Query for CountRows<Q> {
+    type SqlType = BigInt;
+}
+
+impl<Q: QueryFragment<Pg>> QueryFragment<Pg> for CountRows<Q> {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.push_sql("SELECT count(*) FROM (");
+        self.0.walk_ast(out.reborrow())?;
+        out.push_sql(") AS counted");
+        Ok(())
+    }
+}
+
+impl<Q, Conn> RunQueryDsl<Conn> for CountRows<Q> {}
+
+impl<Q> QueryId for Explain<Q> {
+    type QueryId = ();
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<Q> Query for Explain<Q> {
+    type SqlType = Text;
+}
+
+impl<Q: QueryFragment<Pg>> QueryFragment<Pg> for Explain<Q> {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.push_sql("EXPLAIN ");
+        self.0.walk_ast(out.reborrow())
+    }
+}
+
+impl<Q, Conn> RunQueryDsl<Conn> for Explain<Q> {}
@@ -0,0 +1,101 @@
+use diesel_async::scoped_futures::ScopedBoxFuture;
+use diesel_async::{AnsiTransactionManager, AsyncPgConnection, TransactionManager as _};
+use tracing::warn;
+
+use evops_models::ApiResult;
+
+use crate::error;
+
+/// A failed attempt at running a transaction.
+struct Failure {
+    error: error::Error,
+    /// Whether the transaction is known to be rolled back and running it
+    /// again might succeed.
+    retryable: bool,
+}
+
+impl crate::Database {
+    pub(crate) async fn retrying_transaction<'a, R, F>(
+        &self,
+        operation: &'static str,
+        callback: F,
+    ) -> ApiResult<R>
+    where
+        F: for<'r> FnOnce(&'r mut AsyncPgConnection) -> ScopedBoxFuture<'a, 'r, error::Result<R>>
+            + Clone
+            + Send
+            + 'a,
+        R: Send + 'a,
+    {
+        let mut attempt = 1;
+        loop {
+            let result = {
+                let mut conn = self.conn().await?;
+                self::attempt_transaction(&mut conn, callback.clone()).await
+            };
+            match result {
+                Err(failure) if failure.retryable && attempt < self.config.retry.max_attempts => {
+                    let backoff = self.config.retry.backoff(attempt);
+                    metrics::counter!(
+                        crate::instrumentation::TRANSACTION_RETRIES_TOTAL,
+                        "operation" => operation,
+                    )
+                    .increment(1);
+                    warn!(
+                        operation,
+                        attempt,
+                        ?backoff,
+                        error = %failure.error,
+                        "retrying transaction after a transient failure",
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result.map_err(|failure| failure.error.into()),
+            }
+        }
+    }
+}
+
+async fn attempt_transaction<'a, R, F>(
+    conn: &mut AsyncPgConnection,
+    callback: F,
+) -> Result<R, Failure>
+where
+    F: for<'r> FnOnce(&'r mut AsyncPgConnection) -> ScopedBoxFuture<'a, 'r, error::Result<R>>
+        + Send
+        + 'a,
+    R: Send + 'a,
+{
+    let before_commit = |error: error::Error| Failure {
+        retryable: error.is_transient() || error.is_connection_lost(),
+        error,
+    };
+    AnsiTransactionManager::begin_transaction(conn)
+        .await
+        .map_err(|e| before_commit(e.into()))?;
+    let value = match callback(conn).await {
+        Ok(value) => value,
+        Err(e) => {
+            // A connection left inside a transaction is discarded by the
+            // pool, so a failed rollback only needs to be reported.
+            if let Err(rollback_error) = AnsiTransactionManager::rollback_transaction(conn).await {
+                warn!(error = %rollback_error, "failed to roll back a transaction");
+            }
+            return Err(before_commit(e));
+        }
+    };
+    // Running the callback again after a commit that went through would
+    // apply its writes twice, so a connection lost while committing isn't
+    // retried.
+    AnsiTransactionManager::commit_transaction(conn)
+        .await
+        .map_err(|e| {
+            let error = error::Error::from(e);
+            Failure {
+                retryable: error.is_transient(),
+                error,
+            }
+        })?;
+    Ok(value)
+}
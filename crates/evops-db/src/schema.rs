@@ -1,5 +1,50 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tsvector", schema = "pg_catalog"))]
+    pub struct Tsvector;
+}
+
+diesel::table! {
+    event_attendees (event_id, user_id) {
+        event_id -> Uuid,
+        user_id -> Uuid,
+        status -> Text,
+        responded_at -> Timestamptz,
+        check_in_token_blake3 -> Nullable<Bytea>,
+        checked_in_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    event_bookmarks (user_id, event_id) {
+        user_id -> Uuid,
+        event_id -> Uuid,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    event_comments (id) {
+        id -> Uuid,
+        event_id -> Uuid,
+        author_id -> Uuid,
+        parent_id -> Nullable<Uuid>,
+        body -> Text,
+        created_at -> Timestamptz,
+        edited_at -> Nullable<Timestamptz>,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    event_exception_dates (event_id, occurs_at) {
+        event_id -> Uuid,
+        occurs_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     event_images (id) {
         id -> Uuid,
@@ -9,6 +54,29 @@ diesel::table! {
 }
 
 diesel::table! {
+    event_occurrence_overrides (event_id, occurs_at) {
+        event_id -> Uuid,
+        occurs_at -> Timestamptz,
+        title -> Nullable<Text>,
+        description -> Nullable<Text>,
+        starts_at -> Nullable<Timestamptz>,
+        ends_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    event_reactions (event_id, user_id, reaction) {
+        event_id -> Uuid,
+        user_id -> Uuid,
+        reaction -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Tsvector;
+
     events (id) {
         id -> Uuid,
         title -> Text,
@@ -16,6 +84,15 @@ diesel::table! {
         author_id -> Uuid,
         created_at -> Timestamptz,
         modified_at -> Timestamptz,
+        search_vector -> Tsvector,
+        starts_at -> Timestamptz,
+        ends_at -> Timestamptz,
+        timezone -> Text,
+        recurrence_rule -> Nullable<Text>,
+        venue_id -> Nullable<Uuid>,
+        online_url -> Nullable<Text>,
+        capacity -> Nullable<Int4>,
+        last_occurs_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -58,8 +135,29 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    venues (id) {
+        id -> Uuid,
+        name -> Text,
+        address -> Text,
+        latitude -> Float8,
+        longitude -> Float8,
+    }
+}
+
+diesel::joinable!(event_attendees -> events (event_id));
+diesel::joinable!(event_attendees -> users (user_id));
+diesel::joinable!(event_bookmarks -> events (event_id));
+diesel::joinable!(event_bookmarks -> users (user_id));
+diesel::joinable!(event_comments -> events (event_id));
+diesel::joinable!(event_comments -> users (author_id));
+diesel::joinable!(event_exception_dates -> events (event_id));
 diesel::joinable!(event_images -> events (event_id));
+diesel::joinable!(event_occurrence_overrides -> events (event_id));
+diesel::joinable!(event_reactions -> events (event_id));
+diesel::joinable!(event_reactions -> users (user_id));
 diesel::joinable!(events -> users (author_id));
+diesel::joinable!(events -> venues (venue_id));
 diesel::joinable!(events_to_tags -> events (event_id));
 diesel::joinable!(events_to_tags -> tags (tag_id));
 diesel::joinable!(refresh_tokens -> users (user_id));
@@ -67,11 +165,18 @@ diesel::joinable!(tag_aliases -> tags (tag_id));
 diesel::joinable!(tags -> users (owner_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    event_attendees,
+    event_bookmarks,
+    event_comments,
+    event_exception_dates,
     event_images,
+    event_occurrence_overrides,
+    event_reactions,
     events,
     events_to_tags,
     refresh_tokens,
     tag_aliases,
     tags,
     users,
+    venues,
 );
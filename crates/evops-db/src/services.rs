@@ -1,3 +1,17 @@
+pub use self::admin::IntegrityReport;
+pub use self::comment::{CommentId, EventComment, NewCommentForm};
+pub(crate) use self::event::EventKey;
+pub use self::event::{
+    AttendanceStatus, CheckInReport, CheckInTokenHash, EventAttendance, EventFilter, EventLocation,
+    EventOccurrence, EventPhase, EventSchedule, EventSearchHit, EventSort, EventTimezone,
+    GeoRadius, HighlightOptions, OccurrenceOverride, Recurrence, RecurrenceRule, TagFacet,
+    TagFilter, TagMatch, TimeRange,
+};
+pub use self::venue::{GeoPoint, NewVenueForm, Venue, VenueId};
+
+mod admin;
 mod auth;
+mod comment;
 mod event;
 mod tag;
+mod venue;
@@ -0,0 +1,5 @@
+pub use self::integrity::IntegrityReport;
+
+mod integrity;
+mod ownership;
+mod users;
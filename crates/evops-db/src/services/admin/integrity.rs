@@ -0,0 +1,116 @@
+use diesel::sql_types;
+use diesel::{ExpressionMethods as _, QueryDsl as _, QueryableByName};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::ApiResult;
+
+use crate::{error, schema};
+
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    pub tags_without_owner: Vec<evops_models::TagId>,
+    pub unused_tags: Vec<evops_models::TagId>,
+    /// Aliases that are also the name of some tag, making lookups ambiguous.
+    pub aliases_shadowing_tags: Vec<(evops_models::TagId, String)>,
+    /// Events whose image positions are not exactly `1..=n`.
+    pub events_with_image_gaps: Vec<evops_models::EventId>,
+    pub events_modified_before_created: Vec<evops_models::EventId>,
+}
+
+#[derive(QueryableByName)]
+struct EventIdRow {
+    #[diesel(sql_type = sql_types::Uuid)]
+    event_id: Uuid,
+}
+
+impl crate::Database {
+    #[instrument(skip_all)]
+    pub async fn integrity_report(&self) -> ApiResult<IntegrityReport> {
+        let mut conn = self.conn().await?;
+        Self::integrity_report_inner(&mut conn)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn integrity_report_inner(
+        conn: &mut AsyncPgConnection,
+    ) -> error::Result<IntegrityReport> {
+        let tags_without_owner = {
+            schema::tags::table
+                .filter(schema::tags::owner_id.is_null())
+                .select(schema::tags::id)
+                .order(schema::tags::id.asc())
+                .load(conn)
+                .await?
+                .into_iter()
+                .map(evops_models::TagId::new)
+                .collect()
+        };
+
+        let unused_tags = {
+            schema::tags::table
+                .filter(diesel::dsl::not(diesel::dsl::exists({
+                    schema::events_to_tags::table
+                        .filter(schema::events_to_tags::tag_id.eq(schema::tags::id))
+                })))
+                .select(schema::tags::id)
+                .order(schema::tags::id.asc())
+                .load(conn)
+                .await?
+                .into_iter()
+                .map(evops_models::TagId::new)
+                .collect()
+        };
+
+        let aliases_shadowing_tags = {
+            schema::tag_aliases::table
+                .filter(
+                    schema::tag_aliases::alias
+                        .eq_any(schema::tags::table.select(schema::tags::name)),
+                )
+                .select((schema::tag_aliases::tag_id, schema::tag_aliases::alias))
+                .order(schema::tag_aliases::tag_id.asc())
+                .load::<(Uuid, String)>(conn)
+                .await?
+                .into_iter()
+                .map(|(tag_id, alias)| (evops_models::TagId::new(tag_id), alias))
+                .collect()
+        };
+
+        let events_with_image_gaps = {
+            diesel::sql_query(
+                "SELECT event_id FROM event_images \
+                 GROUP BY event_id \
+                 HAVING min(position) <> 1 OR max(position) <> count(*) \
+                 ORDER BY event_id",
+            )
+            .load::<EventIdRow>(conn)
+            .await?
+            .into_iter()
+            .map(|row| evops_models::EventId::new(row.event_id))
+            .collect()
+        };
+
+        let events_modified_before_created = {
+            schema::events::table
+                .filter(schema::events::modified_at.lt(schema::events::created_at))
+                .select(schema::events::id)
+                .order(schema::events::id.asc())
+                .load(conn)
+                .await?
+                .into_iter()
+                .map(evops_models::EventId::new)
+                .collect()
+        };
+
+        Ok(IntegrityReport {
+            tags_without_owner,
+            unused_tags,
+            aliases_shadowing_tags,
+            events_with_image_gaps,
+            events_modified_before_created,
+        })
+    }
+}
@@ -0,0 +1,109 @@
+use chrono::Utc;
+use diesel::{ExpressionMethods as _, QueryDsl as _};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+
+use evops_models::ApiResult;
+
+use crate::{error, schema};
+
+impl crate::Database {
+    #[instrument(skip_all, fields(%event_id, %new_author_id))]
+    pub async fn transfer_event(
+        &self,
+        event_id: evops_models::EventId,
+        new_author_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        self.retrying_transaction("transfer_event", move |conn| {
+            async move { Self::transfer_event_unatomic(conn, event_id, new_author_id).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn transfer_event_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        new_author_id: evops_models::UserId,
+    ) -> error::Result<()> {
+        Self::find_event_model(conn, event_id).await?;
+        Self::find_user_model(conn, new_author_id).await?;
+
+        diesel::update(schema::events::table.find(event_id.into_inner()))
+            .set((
+                schema::events::author_id.eq(new_author_id.into_inner()),
+                schema::events::modified_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(%tag_id, %new_owner_id))]
+    pub async fn transfer_tag(
+        &self,
+        tag_id: evops_models::TagId,
+        new_owner_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        self.retrying_transaction("transfer_tag", move |conn| {
+            async move { Self::transfer_tag_unatomic(conn, tag_id, new_owner_id).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn transfer_tag_unatomic(
+        conn: &mut AsyncPgConnection,
+        tag_id: evops_models::TagId,
+        new_owner_id: evops_models::UserId,
+    ) -> error::Result<()> {
+        Self::find_tag_model(conn, tag_id).await?;
+        Self::find_user_model(conn, new_owner_id).await?;
+
+        diesel::update(schema::tags::table.find(tag_id.into_inner()))
+            .set(schema::tags::owner_id.eq(new_owner_id.into_inner()))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes an event regardless of who authored it.
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn delete_event_as_admin(
+        &self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<evops_models::EventImageIds> {
+        self.retrying_transaction("delete_event_as_admin", move |conn| {
+            async move { Self::delete_event_as_admin_unatomic(conn, event_id).await }.scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn delete_event_as_admin_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+    ) -> error::Result<evops_models::EventImageIds> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        let author_id = evops_models::UserId::new(event_model.author_id);
+        Self::delete_event_unatomic(conn, event_id, author_id).await
+    }
+
+    /// Deletes a tag regardless of who owns it, including tags without an
+    /// owner.
+    #[instrument(skip_all, fields(%tag_id))]
+    pub async fn delete_tag_as_admin(&self, tag_id: evops_models::TagId) -> ApiResult<()> {
+        self.retrying_transaction("delete_tag_as_admin", move |conn| {
+            async move { Self::delete_tag_as_admin_unatomic(conn, tag_id).await }.scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn delete_tag_as_admin_unatomic(
+        conn: &mut AsyncPgConnection,
+        tag_id: evops_models::TagId,
+    ) -> error::Result<()> {
+        Self::find_tag_model(conn, tag_id).await?;
+        Self::delete_tag_records(conn, tag_id).await
+    }
+}
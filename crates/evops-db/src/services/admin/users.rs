@@ -0,0 +1,108 @@
+use chrono::Utc;
+use diesel::{ExpressionMethods as _, QueryDsl as _};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::ApiResult;
+
+use crate::{error, schema};
+
+impl crate::Database {
+    /// Makes another user the author of every event of the user and returns
+    /// how many events that was.
+    #[instrument(skip_all, fields(%user_id, %new_author_id))]
+    pub async fn transfer_user_events(
+        &self,
+        user_id: evops_models::UserId,
+        new_author_id: evops_models::UserId,
+    ) -> ApiResult<usize> {
+        self.retrying_transaction("transfer_user_events", move |conn| {
+            async move { Self::transfer_user_events_unatomic(conn, user_id, new_author_id).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn transfer_user_events_unatomic(
+        conn: &mut AsyncPgConnection,
+        user_id: evops_models::UserId,
+        new_author_id: evops_models::UserId,
+    ) -> error::Result<usize> {
+        Self::find_user_model(conn, user_id).await?;
+        Self::find_user_model(conn, new_author_id).await?;
+
+        let transferred_count = {
+            diesel::update({
+                schema::events::table.filter(schema::events::author_id.eq(user_id.into_inner()))
+            })
+            .set((
+                schema::events::author_id.eq(new_author_id.into_inner()),
+                schema::events::modified_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .await?
+        };
+        Ok(transferred_count)
+    }
+
+    /// Deletes the user along with their events and comments, and leaves their
+    /// tags without an owner. Refresh tokens, RSVPs, bookmarks and reactions
+    /// go with the user.
+    ///
+    /// Returns the IDs of the images of the deleted events, which are left for
+    /// the caller to remove from the image storage.
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn delete_user(
+        &self,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<Vec<evops_models::EventImageId>> {
+        self.retrying_transaction("delete_user", move |conn| {
+            async move { Self::delete_user_unatomic(conn, user_id).await }.scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn delete_user_unatomic(
+        conn: &mut AsyncPgConnection,
+        user_id: evops_models::UserId,
+    ) -> error::Result<Vec<evops_models::EventImageId>> {
+        Self::find_user_model(conn, user_id).await?;
+
+        let event_ids: Vec<Uuid> = {
+            schema::events::table
+                .filter(schema::events::author_id.eq(user_id.into_inner()))
+                .select(schema::events::id)
+                .order(schema::events::id.asc())
+                .load(conn)
+                .await?
+        };
+        let mut image_ids = Vec::new();
+        for event_id in event_ids {
+            let event_id = evops_models::EventId::new(event_id);
+            let event_image_ids = Self::delete_event_unatomic(conn, event_id, user_id).await?;
+            image_ids.extend(event_image_ids.into_inner());
+        }
+
+        // Replies to the comments are deleted along with them.
+        diesel::delete({
+            schema::event_comments::table
+                .filter(schema::event_comments::author_id.eq(user_id.into_inner()))
+        })
+        .execute(conn)
+        .await?;
+
+        diesel::update({
+            schema::tags::table.filter(schema::tags::owner_id.eq(user_id.into_inner()))
+        })
+        .set(schema::tags::owner_id.eq(None::<Uuid>))
+        .execute(conn)
+        .await?;
+
+        diesel::delete(schema::users::table.find(user_id.into_inner()))
+            .execute(conn)
+            .await?;
+        Ok(image_ids)
+    }
+}
@@ -1,4 +1,5 @@
 mod find;
 mod list;
 mod log_in;
+mod refresh_token;
 mod sign_up;
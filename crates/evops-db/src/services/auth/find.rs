@@ -1,15 +1,28 @@
 use diesel::{QueryDsl as _, SelectableHelper as _};
 use diesel_async::AsyncPgConnection;
 use diesel_async::RunQueryDsl as _;
+use tracing::instrument;
 
 use evops_models::{ApiError, ApiResult};
 
+use crate::error;
 use crate::models;
 use crate::schema;
 
 impl crate::Database {
-    pub async fn find_user(&mut self, id: evops_models::UserId) -> ApiResult<evops_models::User> {
-        let user_model = Self::find_user_model(&mut self.conn, id).await?;
+    #[instrument(skip_all, fields(user_id = %id))]
+    pub async fn find_user(&self, id: evops_models::UserId) -> ApiResult<evops_models::User> {
+        let mut conn = self.conn().await?;
+        Self::find_user_inner(&mut conn, id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_user_inner(
+        conn: &mut AsyncPgConnection,
+        id: evops_models::UserId,
+    ) -> error::Result<evops_models::User> {
+        let user_model = Self::find_user_model(conn, id).await?;
 
         let user = evops_models::User {
             id,
@@ -21,10 +34,10 @@ impl crate::Database {
         Ok(user)
     }
 
-    async fn find_user_model(
+    pub(crate) async fn find_user_model(
         conn: &mut AsyncPgConnection,
         id: evops_models::UserId,
-    ) -> ApiResult<models::User> {
+    ) -> error::Result<models::User> {
         schema::users::table
             .find(id.into_inner())
             .select(models::User::as_select())
@@ -32,7 +45,7 @@ impl crate::Database {
             .await
             .map_err(|e| match e {
                 diesel::result::Error::NotFound => {
-                    ApiError::NotFound(format!("No user with ID {id} found."))
+                    ApiError::NotFound(format!("No user with ID {id} found.")).into()
                 }
                 _ => e.into(),
             })
@@ -1,15 +1,58 @@
-use diesel::result::QueryResult;
-use diesel::{QueryDsl as _, SelectableHelper as _};
+use diesel::{ExpressionMethods as _, QueryDsl as _, SelectableHelper as _};
 use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
 
 use evops_models::ApiResult;
 
+use crate::error;
 use crate::models;
+use crate::pagination::{self, Cursor, CursorValue, Page, TotalCount};
 use crate::schema;
 
 impl crate::Database {
-    pub async fn list_users(&mut self) -> ApiResult<Vec<evops_models::User>> {
-        let user_models = Self::list_all_user_models(&mut self.conn).await?;
+    #[instrument(skip_all)]
+    pub async fn list_users(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::User>> {
+        let mut conn = self.conn().await?;
+        Self::list_users_inner(&mut conn, cursor, limit, count)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn list_users_inner(
+        conn: &mut AsyncPgConnection,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> error::Result<Page<evops_models::User>> {
+        let limit = limit.map(i64::from);
+        let mut user_models: Vec<models::User> = {
+            let mut query = {
+                schema::users::table
+                    .select(models::User::as_select())
+                    .order(schema::users::id.asc())
+                    .into_boxed()
+            };
+            if let Some(cursor) = cursor {
+                let &[CursorValue::Uuid(last_id)] = cursor.key(Cursor::USERS)? else {
+                    return Err(error::Error::Api(Cursor::invalid()));
+                };
+                query = query.filter(schema::users::id.gt(last_id));
+            }
+            if let Some(limit) = pagination::fetch_limit(limit) {
+                query = query.limit(limit);
+            }
+            query.load(conn).await?
+        };
+        let (has_more, next_cursor) = pagination::split_page(&mut user_models, limit, |user| {
+            Cursor::new(Cursor::USERS, vec![CursorValue::Uuid(user.id)])
+        });
+        let total =
+            pagination::total(conn, schema::users::table.select(schema::users::id), count).await?;
 
         let users = {
             user_models
@@ -23,13 +66,11 @@ impl crate::Database {
                 })
                 .collect()
         };
-        Ok(users)
-    }
-
-    async fn list_all_user_models(conn: &mut AsyncPgConnection) -> QueryResult<Vec<models::User>> {
-        schema::users::table
-            .select(models::User::as_select())
-            .get_results(conn)
-            .await
+        Ok(Page {
+            items: users,
+            has_more,
+            next_cursor,
+            total,
+        })
     }
 }
@@ -1,20 +1,32 @@
 use diesel::{ExpressionMethods as _, QueryDsl as _};
-use diesel_async::RunQueryDsl as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
 use uuid::Uuid;
 
 use evops_models::{ApiError, ApiResult};
 
-use crate::schema;
+use crate::{error, schema};
 
 impl crate::Database {
+    #[instrument(skip_all)]
     pub async fn get_password_hash(
-        &mut self,
+        &self,
         login: &evops_models::UserLogin,
     ) -> ApiResult<(evops_models::UserId, evops_models::UserPasswordHash)> {
+        let mut conn = self.conn().await?;
+        Self::get_password_hash_inner(&mut conn, login)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn get_password_hash_inner(
+        conn: &mut AsyncPgConnection,
+        login: &evops_models::UserLogin,
+    ) -> error::Result<(evops_models::UserId, evops_models::UserPasswordHash)> {
         schema::users::table
             .filter(schema::users::user_login.eq(login.as_ref()))
             .select((schema::users::id, schema::users::password_argon2))
-            .get_result(&mut self.conn)
+            .get_result(conn)
             .await
             .map(|(id, hash): (Uuid, String)| {
                 (
@@ -24,39 +36,54 @@ impl crate::Database {
             })
             .map_err(|e| match e {
                 diesel::result::Error::NotFound => {
-                    ApiError::Forbidden("Wrong credentials.".to_string())
+                    ApiError::Forbidden("Wrong credentials.".to_string()).into()
                 }
                 _ => e.into(),
             })
     }
 
+    #[instrument(skip_all, fields(%user_id))]
     pub async fn insert_refresh_token(
-        &mut self,
+        &self,
         token_hash: &evops_models::JsonWebTokenHash,
         user_id: evops_models::UserId,
     ) -> ApiResult<()> {
-        Self::insert_refresh_token_inner(&mut self.conn, token_hash, user_id)
+        let mut conn = self.conn().await?;
+        Self::insert_refresh_token_inner(&mut conn, token_hash, user_id)
             .await
             .map_err(Into::into)
     }
 
+    #[instrument(skip_all)]
     pub async fn check_refresh_token(
-        &mut self,
+        &self,
         token_hash: &evops_models::JsonWebTokenHash,
     ) -> ApiResult<()> {
+        let mut conn = self.conn().await?;
+        Self::check_refresh_token_inner(&mut conn, token_hash)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn check_refresh_token_inner(
+        conn: &mut AsyncPgConnection,
+        token_hash: &evops_models::JsonWebTokenHash,
+    ) -> error::Result<()> {
         let exists: bool = {
             diesel::select(diesel::dsl::exists({
                 schema::refresh_tokens::table
                     .filter(schema::refresh_tokens::token_blake3.eq(token_hash.as_ref()))
             }))
-            .get_result(&mut self.conn)
+            .get_result(conn)
             .await?
         };
 
         if exists {
             Ok(())
         } else {
-            Err(ApiError::Auth("Invalid refresh JWT token.".to_owned()))
+            Err(error::Error::Api(ApiError::Auth({
+                "Invalid refresh JWT token.".to_owned()
+            })))
         }
     }
 }
@@ -0,0 +1,104 @@
+use diesel::{ExpressionMethods as _, QueryDsl as _};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::{error, schema};
+
+impl crate::Database {
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn list_refresh_token_ids(
+        &self,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<Vec<Uuid>> {
+        let mut conn = self.conn().await?;
+        Self::list_refresh_token_ids_inner(&mut conn, user_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn list_refresh_token_ids_inner(
+        conn: &mut AsyncPgConnection,
+        user_id: evops_models::UserId,
+    ) -> error::Result<Vec<Uuid>> {
+        Self::find_user_model(conn, user_id).await?;
+
+        let token_ids = {
+            schema::refresh_tokens::table
+                .filter(schema::refresh_tokens::user_id.eq(user_id.into_inner()))
+                .select(schema::refresh_tokens::id)
+                .order(schema::refresh_tokens::id.asc())
+                .load(conn)
+                .await?
+        };
+        Ok(token_ids)
+    }
+
+    #[instrument(skip_all, fields(%user_id, %token_id))]
+    pub async fn revoke_refresh_token(
+        &self,
+        user_id: evops_models::UserId,
+        token_id: Uuid,
+    ) -> ApiResult<()> {
+        self.retrying_transaction("revoke_refresh_token", move |conn| {
+            async move { Self::revoke_refresh_token_unatomic(conn, user_id, token_id).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn revoke_refresh_token_unatomic(
+        conn: &mut AsyncPgConnection,
+        user_id: evops_models::UserId,
+        token_id: Uuid,
+    ) -> error::Result<()> {
+        let deleted_count = {
+            diesel::delete({
+                schema::refresh_tokens::table
+                    .filter(schema::refresh_tokens::id.eq(token_id))
+                    .filter(schema::refresh_tokens::user_id.eq(user_id.into_inner()))
+            })
+            .execute(conn)
+            .await?
+        };
+        if deleted_count == 0 {
+            return Err(error::Error::Api(ApiError::NotFound(format!(
+                "No refresh token with ID {token_id} found for user {user_id}.",
+            ))));
+        }
+        Ok(())
+    }
+
+    /// Revokes every refresh token of the user, returning how many there were.
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn revoke_all_refresh_tokens(
+        &self,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<usize> {
+        self.retrying_transaction("revoke_all_refresh_tokens", move |conn| {
+            async move { Self::revoke_all_refresh_tokens_unatomic(conn, user_id).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn revoke_all_refresh_tokens_unatomic(
+        conn: &mut AsyncPgConnection,
+        user_id: evops_models::UserId,
+    ) -> error::Result<usize> {
+        Self::find_user_model(conn, user_id).await?;
+
+        let deleted_count = {
+            diesel::delete({
+                schema::refresh_tokens::table
+                    .filter(schema::refresh_tokens::user_id.eq(user_id.into_inner()))
+            })
+            .execute(conn)
+            .await?
+        };
+        Ok(deleted_count)
+    }
+}
@@ -1,12 +1,13 @@
-use diesel::{Insertable, QueryResult, SelectableHelper as _};
+use diesel::{Insertable, SelectableHelper as _};
 use diesel_async::scoped_futures::ScopedFutureExt as _;
-use diesel_async::{AsyncConnection as _, AsyncPgConnection, RunQueryDsl as _};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
 use uuid::Uuid;
 
 use evops_models::ApiResult;
 
 use crate::models;
-use crate::schema;
+use crate::{error, schema};
 
 #[derive(Insertable)]
 #[diesel(table_name = schema::users)]
@@ -28,42 +29,66 @@ struct NewRefreshToken<'a> {
 }
 
 impl crate::Database {
+    #[instrument(skip_all, fields(%user_id))]
     pub async fn sign_up(
-        &mut self,
+        &self,
         user_id: evops_models::UserId,
         login: &evops_models::UserLogin,
         password_hash: &evops_models::UserPasswordHash,
         display_name: &evops_models::UserDisplayName,
         refresh_token_hash: &evops_models::JsonWebTokenHash,
     ) -> ApiResult<()> {
-        self.conn
-            .transaction(|conn| {
-                async {
-                    unsafe {
-                        Self::sign_up_unatomic(
-                            conn,
-                            user_id,
-                            login,
-                            password_hash,
-                            display_name,
-                            refresh_token_hash,
-                        )
-                        .await
-                    }
-                }
-                .scope_boxed()
-            })
-            .await
+        self.retrying_transaction("sign_up", move |conn| {
+            async move {
+                Self::sign_up_unatomic(
+                    conn,
+                    user_id,
+                    login,
+                    password_hash,
+                    display_name,
+                    refresh_token_hash,
+                )
+                .await
+            }
+            .scope_boxed()
+        })
+        .await
     }
 
-    pub async unsafe fn sign_up_unatomic(
+    pub(crate) async fn sign_up_unatomic(
         conn: &mut AsyncPgConnection,
         user_id: evops_models::UserId,
         login: &evops_models::UserLogin,
         password_hash: &evops_models::UserPasswordHash,
         display_name: &evops_models::UserDisplayName,
         refresh_token_hash: &evops_models::JsonWebTokenHash,
+    ) -> error::Result<()> {
+        Self::insert_user(conn, user_id, login, password_hash, display_name).await?;
+        Self::insert_refresh_token_inner(conn, refresh_token_hash, user_id).await?;
+        Ok(())
+    }
+
+    /// Creates a user without logging them in, e.g. from admin tooling.
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn create_user(
+        &self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
     ) -> ApiResult<()> {
+        let mut conn = self.conn().await?;
+        Self::insert_user(&mut conn, user_id, login, password_hash, display_name).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn insert_user(
+        conn: &mut AsyncPgConnection,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+    ) -> error::Result<()> {
         diesel::insert_into(schema::users::table)
             .values(self::NewUser {
                 id: user_id.into_inner(),
@@ -74,9 +99,6 @@ impl crate::Database {
             .returning(models::User::as_returning())
             .execute(conn)
             .await?;
-
-        Self::insert_refresh_token_inner(conn, refresh_token_hash, user_id).await?;
-
         Ok(())
     }
 
@@ -84,7 +106,7 @@ impl crate::Database {
         conn: &mut AsyncPgConnection,
         token_hash: &evops_models::JsonWebTokenHash,
         user_id: evops_models::UserId,
-    ) -> QueryResult<()> {
+    ) -> error::Result<()> {
         diesel::insert_into(schema::refresh_tokens::table)
             .values(self::NewRefreshToken {
                 id: Uuid::now_v7(),
@@ -0,0 +1,74 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+mod delete;
+mod edit;
+mod find;
+mod list;
+mod post;
+
+/// The longest comment body, in characters.
+const MAX_BODY_CHARS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CommentId(Uuid);
+
+impl CommentId {
+    #[must_use]
+    pub const fn new(id: Uuid) -> Self {
+        Self(id)
+    }
+
+    #[must_use]
+    pub const fn into_inner(self) -> Uuid {
+        self.0
+    }
+}
+
+impl fmt::Display for CommentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A comment on an event, with the whole thread of replies below it.
+#[derive(Debug, Clone)]
+pub struct EventComment {
+    pub id: CommentId,
+    pub event_id: evops_models::EventId,
+    pub author: evops_models::User,
+    /// `None` for comments on the event itself.
+    pub parent_id: Option<CommentId>,
+    /// `None` once the comment is deleted.
+    pub body: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Oldest first.
+    pub replies: Vec<EventComment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewCommentForm {
+    /// The comment replied to, `None` to comment on the event itself.
+    pub parent_id: Option<CommentId>,
+    pub body: String,
+}
+
+fn check_body(body: &str) -> ApiResult<()> {
+    if body.trim().is_empty() {
+        return Err(ApiError::InvalidArgument({
+            "A comment can't be empty.".to_owned()
+        }));
+    }
+    if body.chars().count() > MAX_BODY_CHARS {
+        return Err(ApiError::InvalidArgument(format!(
+            "A comment can't be longer than {MAX_BODY_CHARS} characters.",
+        )));
+    }
+    Ok(())
+}
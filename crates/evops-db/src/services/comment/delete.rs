@@ -0,0 +1,58 @@
+use diesel::dsl;
+use diesel::{ExpressionMethods as _, QueryDsl as _};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::services::comment::CommentId;
+use crate::{error, schema};
+
+impl crate::Database {
+    /// Drops the body of the comment, leaving its replies in place. Both the
+    /// author of the comment and the author of the event can do that.
+    #[instrument(skip_all, fields(%comment_id, %user_id))]
+    pub async fn delete_comment(
+        &self,
+        comment_id: CommentId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        self.retrying_transaction("delete_comment", move |conn| {
+            async move { Self::delete_comment_unatomic(conn, comment_id, user_id).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn delete_comment_unatomic(
+        conn: &mut AsyncPgConnection,
+        comment_id: CommentId,
+        user_id: evops_models::UserId,
+    ) -> error::Result<()> {
+        let comment_model = Self::find_comment_model(conn, comment_id).await?;
+        let event_model = {
+            let event_id = evops_models::EventId::new(comment_model.event_id);
+            Self::find_event_model(conn, event_id).await?
+        };
+        if user_id.into_inner() != comment_model.author_id
+            && user_id.into_inner() != event_model.author_id
+        {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't delete this comment.".to_owned()
+            })));
+        }
+        if comment_model.deleted_at.is_some() {
+            return Ok(());
+        }
+
+        diesel::update(schema::event_comments::table.find(comment_id.into_inner()))
+            .set((
+                schema::event_comments::body.eq(""),
+                schema::event_comments::deleted_at.eq(dsl::now),
+            ))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use diesel::dsl;
+use diesel::{ExpressionMethods as _, QueryDsl as _};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::services::comment::{self, CommentId};
+use crate::{error, schema};
+
+impl crate::Database {
+    /// Replaces the body of the comment, which only its author can do.
+    #[instrument(skip_all, fields(%comment_id, %user_id))]
+    pub async fn edit_comment(
+        &self,
+        comment_id: CommentId,
+        user_id: evops_models::UserId,
+        body: String,
+    ) -> ApiResult<()> {
+        let body = &body;
+        self.retrying_transaction("edit_comment", move |conn| {
+            async move { Self::edit_comment_unatomic(conn, comment_id, user_id, body).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn edit_comment_unatomic(
+        conn: &mut AsyncPgConnection,
+        comment_id: CommentId,
+        user_id: evops_models::UserId,
+        body: &str,
+    ) -> error::Result<()> {
+        comment::check_body(body)?;
+        let comment_model = Self::find_comment_model(conn, comment_id).await?;
+        if user_id.into_inner() != comment_model.author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't modify this comment.".to_owned()
+            })));
+        }
+        if comment_model.deleted_at.is_some() {
+            return Err(error::Error::Api(ApiError::InvalidArgument({
+                "Deleted comments can't be edited.".to_owned()
+            })));
+        }
+
+        diesel::update(schema::event_comments::table.find(comment_id.into_inner()))
+            .set((
+                schema::event_comments::body.eq(body),
+                schema::event_comments::edited_at.eq(dsl::now),
+            ))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+}
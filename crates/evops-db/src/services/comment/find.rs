@@ -0,0 +1,50 @@
+use diesel::{QueryDsl as _, SelectableHelper as _};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::error;
+use crate::models;
+use crate::schema;
+use crate::services::comment::{CommentId, EventComment};
+
+impl crate::Database {
+    /// Returns the comment with its replies.
+    #[instrument(skip_all, fields(comment_id = %id))]
+    pub async fn find_comment(&self, id: CommentId) -> ApiResult<EventComment> {
+        let mut conn = self.conn().await?;
+        Self::find_comment_inner(&mut conn, id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_comment_inner(
+        conn: &mut AsyncPgConnection,
+        id: CommentId,
+    ) -> error::Result<EventComment> {
+        let comment_model = Self::find_comment_model(conn, id).await?;
+        let mut comments = Self::comment_threads(conn, vec![comment_model.id]).await?;
+        comments.pop().ok_or_else(|| {
+            let message = format!("No comment with ID {id} found.");
+            error::Error::Api(ApiError::NotFound(message))
+        })
+    }
+
+    pub(crate) async fn find_comment_model(
+        conn: &mut AsyncPgConnection,
+        id: CommentId,
+    ) -> error::Result<models::EventComment> {
+        schema::event_comments::table
+            .find(id.into_inner())
+            .select(models::EventComment::as_select())
+            .get_result(conn)
+            .await
+            .map_err(|e| match e {
+                diesel::result::Error::NotFound => {
+                    ApiError::NotFound(format!("No comment with ID {id} found.")).into()
+                }
+                _ => e.into(),
+            })
+    }
+}
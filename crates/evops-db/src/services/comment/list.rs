@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use diesel::sql_types::{self, Array};
+use diesel::{ExpressionMethods as _, QueryDsl as _, QueryableByName, SelectableHelper as _};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::ApiResult;
+
+use crate::error;
+use crate::models;
+use crate::pagination::{self, Cursor, CursorValue, Page, TotalCount};
+use crate::schema;
+use crate::services::comment::{CommentId, EventComment};
+
+#[derive(QueryableByName)]
+struct CommentIdRow {
+    #[diesel(sql_type = sql_types::Uuid)]
+    id: Uuid,
+}
+
+impl crate::Database {
+    /// Lists the threads on the event, oldest first. The pages are made of
+    /// the comments on the event itself, each with all of its replies.
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn list_comments(
+        &self,
+        event_id: evops_models::EventId,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> ApiResult<Page<EventComment>> {
+        let mut conn = self.conn().await?;
+        Self::list_comments_inner(&mut conn, event_id, cursor, limit, count)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn list_comments_inner(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> error::Result<Page<EventComment>> {
+        Self::find_event_model(conn, event_id).await?;
+        let limit = limit.map(i64::from);
+        let threads = || {
+            schema::event_comments::table
+                .filter(schema::event_comments::event_id.eq(event_id.into_inner()))
+                .filter(schema::event_comments::parent_id.is_null())
+                .select(schema::event_comments::id)
+                .into_boxed()
+        };
+        let mut thread_ids: Vec<Uuid> = {
+            let mut query = threads().order(schema::event_comments::id.asc());
+            if let Some(cursor) = cursor {
+                let &[CursorValue::Uuid(last_id)] = cursor.key(Cursor::COMMENTS)? else {
+                    return Err(error::Error::Api(Cursor::invalid()));
+                };
+                query = query.filter(schema::event_comments::id.gt(last_id));
+            }
+            if let Some(limit) = pagination::fetch_limit(limit) {
+                query = query.limit(limit);
+            }
+            query.load(conn).await?
+        };
+        let (has_more, next_cursor) = pagination::split_page(&mut thread_ids, limit, |&id| {
+            Cursor::new(Cursor::COMMENTS, vec![CursorValue::Uuid(id)])
+        });
+        let total = pagination::total(conn, threads(), count).await?;
+
+        let comments = Self::comment_threads(conn, thread_ids).await?;
+        Ok(Page {
+            items: comments,
+            has_more,
+            next_cursor,
+            total,
+        })
+    }
+
+    /// Loads the comments with the IDs, in the same order, each with all of
+    /// its replies.
+    pub(crate) async fn comment_threads(
+        conn: &mut AsyncPgConnection,
+        ids: Vec<Uuid>,
+    ) -> error::Result<Vec<EventComment>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let reply_ids = {
+            diesel::sql_query(
+                "WITH RECURSIVE replies AS ( \
+                     SELECT id FROM event_comments WHERE parent_id = ANY($1) \
+                     UNION ALL \
+                     SELECT event_comments.id FROM event_comments \
+                     JOIN replies ON event_comments.parent_id = replies.id \
+                 ) \
+                 SELECT id FROM replies",
+            )
+            .bind::<Array<sql_types::Uuid>, _>(ids.clone())
+            .load::<CommentIdRow>(conn)
+            .await?
+            .into_iter()
+            .map(|row| row.id)
+        };
+        let all_ids: Vec<_> = ids.iter().copied().chain(reply_ids).collect();
+        let comments_with_authors: Vec<(models::EventComment, models::User)> = {
+            schema::event_comments::table
+                .inner_join(schema::users::table)
+                .filter(schema::event_comments::id.eq_any(all_ids))
+                .select((models::EventComment::as_select(), models::User::as_select()))
+                .order(schema::event_comments::id.asc())
+                .load(conn)
+                .await?
+        };
+
+        let mut comments = HashMap::new();
+        let mut replies: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (comment_model, author_model) in comments_with_authors {
+            if let Some(parent_id) = comment_model.parent_id {
+                replies.entry(parent_id).or_default().push(comment_model.id);
+            }
+            let comment = EventComment {
+                id: CommentId::new(comment_model.id),
+                event_id: evops_models::EventId::new(comment_model.event_id),
+                author: evops_models::User {
+                    id: evops_models::UserId::new(author_model.id),
+                    login: unsafe {
+                        evops_models::UserLogin::new_unchecked(author_model.user_login)
+                    },
+                    display_name: unsafe {
+                        evops_models::UserDisplayName::new_unchecked(author_model.display_name)
+                    },
+                },
+                parent_id: comment_model.parent_id.map(CommentId::new),
+                body: comment_model
+                    .deleted_at
+                    .is_none()
+                    .then_some(comment_model.body),
+                created_at: comment_model.created_at,
+                edited_at: comment_model.edited_at,
+                deleted_at: comment_model.deleted_at,
+                replies: Vec::new(),
+            };
+            comments.insert(comment_model.id, comment);
+        }
+        let threads = {
+            ids.into_iter()
+                .filter_map(|id| self::take_thread(&mut comments, &replies, id))
+                .collect()
+        };
+        Ok(threads)
+    }
+}
+
+/// Moves the comment and its replies out of `comments` into a tree.
+fn take_thread(
+    comments: &mut HashMap<Uuid, EventComment>,
+    replies: &HashMap<Uuid, Vec<Uuid>>,
+    id: Uuid,
+) -> Option<EventComment> {
+    let mut comment = comments.remove(&id)?;
+    comment.replies = {
+        replies
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|&reply_id| self::take_thread(comments, replies, reply_id))
+            .collect()
+    };
+    Some(comment)
+}
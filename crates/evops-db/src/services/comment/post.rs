@@ -0,0 +1,74 @@
+use diesel::Insertable;
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::services::comment::{self, CommentId, NewCommentForm};
+use crate::{error, schema};
+
+#[derive(Insertable)]
+#[diesel(table_name = schema::event_comments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+struct NewComment<'a> {
+    id: Uuid,
+    event_id: Uuid,
+    author_id: Uuid,
+    parent_id: Option<Uuid>,
+    body: &'a str,
+}
+
+impl crate::Database {
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn post_comment(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        form: NewCommentForm,
+    ) -> ApiResult<CommentId> {
+        let form = &form;
+        self.retrying_transaction("post_comment", move |conn| {
+            async move { Self::post_comment_unatomic(conn, event_id, user_id, form).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn post_comment_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        form: &NewCommentForm,
+    ) -> error::Result<CommentId> {
+        comment::check_body(&form.body)?;
+        Self::find_event_model(conn, event_id).await?;
+        if let Some(parent_id) = form.parent_id {
+            let parent_model = Self::find_comment_model(conn, parent_id).await?;
+            if parent_model.event_id != event_id.into_inner() {
+                return Err(error::Error::Api(ApiError::NotFound(format!(
+                    "No comment with ID {parent_id} found on event {event_id}.",
+                ))));
+            }
+            if parent_model.deleted_at.is_some() {
+                return Err(error::Error::Api(ApiError::InvalidArgument({
+                    "Deleted comments can't be replied to.".to_owned()
+                })));
+            }
+        }
+
+        let id = CommentId::new(Uuid::now_v7());
+        diesel::insert_into(schema::event_comments::table)
+            .values(self::NewComment {
+                id: id.into_inner(),
+                event_id: event_id.into_inner(),
+                author_id: user_id.into_inner(),
+                parent_id: form.parent_id.map(CommentId::into_inner),
+                body: &form.body,
+            })
+            .execute(conn)
+            .await?;
+        Ok(id)
+    }
+}
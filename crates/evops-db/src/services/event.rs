@@ -1,7 +1,36 @@
+pub use self::attendance::{AttendanceStatus, EventAttendance};
+pub use self::check_in::{CheckInReport, CheckInTokenHash};
+pub use self::facets::TagFacet;
+pub use self::filter::{EventFilter, EventPhase, TimeRange};
+pub use self::geo_filter::GeoRadius;
+pub use self::location::EventLocation;
+pub use self::occurrences::{EventOccurrence, OccurrenceOverride};
+pub use self::recurrence::{Recurrence, RecurrenceRule};
+pub use self::schedule::{EventSchedule, EventTimezone};
+pub use self::search::{EventSearchHit, HighlightOptions};
+pub(crate) use self::sort::EventKey;
+pub use self::sort::EventSort;
+pub use self::tag_filter::{TagFilter, TagMatch};
+
+mod attendance;
+mod bookmarks;
+mod check_in;
 mod create;
 mod delete;
+mod facets;
+mod filter;
 mod find;
+mod geo_filter;
 mod list;
+mod location;
+mod occurrences;
+mod reactions;
+mod recurrence;
 mod reorder_images;
 mod reserve_image;
+mod schedule;
+mod search;
+mod search_filter;
+mod sort;
+mod tag_filter;
 mod update;
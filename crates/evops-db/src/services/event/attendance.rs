@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+
+use diesel::dsl;
+use diesel::upsert::excluded;
+use diesel::{ExpressionMethods as _, OptionalExtension as _, QueryDsl as _, QueryResult};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::{error, schema};
+
+/// A user's answer to an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttendanceStatus {
+    Going,
+    Interested,
+    Declined,
+    /// Wants to go, but the event is full. Waitlisted users are let in in the
+    /// order they answered as seats free up.
+    Waitlisted,
+}
+
+/// How many users answered the event, by answer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventAttendance {
+    pub going: u64,
+    pub interested: u64,
+    pub waitlisted: u64,
+    /// `None` if any number of users can go.
+    pub capacity: Option<u32>,
+}
+
+impl AttendanceStatus {
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::Going => "going",
+            Self::Interested => "interested",
+            Self::Declined => "declined",
+            Self::Waitlisted => "waitlisted",
+        }
+    }
+
+    /// `event_attendees_status_check` allows no other values.
+    fn from_db(status: &str) -> Self {
+        match status {
+            "going" => Self::Going,
+            "interested" => Self::Interested,
+            "waitlisted" => Self::Waitlisted,
+            _ => Self::Declined,
+        }
+    }
+}
+
+impl crate::Database {
+    /// Records the user's answer to the event. A user going to a full event
+    /// is waitlisted instead, which the returned status tells. Whenever a
+    /// user stops going, the first waitlisted user takes the seat.
+    #[instrument(skip_all, fields(%event_id, %user_id, ?status))]
+    pub async fn rsvp(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        status: AttendanceStatus,
+    ) -> ApiResult<AttendanceStatus> {
+        self.retrying_transaction("rsvp", move |conn| {
+            async move { Self::rsvp_unatomic(conn, event_id, user_id, status).await }.scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn rsvp_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        status: AttendanceStatus,
+    ) -> error::Result<AttendanceStatus> {
+        if status == AttendanceStatus::Waitlisted {
+            return Err(error::Error::Api(ApiError::InvalidArgument({
+                "Users are only waitlisted when they go to a full event.".to_owned()
+            })));
+        }
+        let (_, capacity) = Self::lock_event_for_attendance(conn, event_id).await?;
+        let previous = Self::attendance_status(conn, event_id, user_id).await?;
+        let status = match (status, previous) {
+            // Going again mustn't lose a waitlisted user their place.
+            (
+                AttendanceStatus::Going,
+                Some(previous @ (AttendanceStatus::Going | AttendanceStatus::Waitlisted)),
+            ) => previous,
+            (AttendanceStatus::Going, _) => {
+                if Self::free_seats(conn, event_id, capacity).await? > 0 {
+                    AttendanceStatus::Going
+                } else {
+                    AttendanceStatus::Waitlisted
+                }
+            }
+            (status, _) => status,
+        };
+        if previous == Some(status) {
+            return Ok(status);
+        }
+
+        diesel::insert_into(schema::event_attendees::table)
+            .values((
+                schema::event_attendees::event_id.eq(event_id.into_inner()),
+                schema::event_attendees::user_id.eq(user_id.into_inner()),
+                schema::event_attendees::status.eq(status.as_str()),
+                schema::event_attendees::responded_at.eq(dsl::now),
+            ))
+            .on_conflict((
+                schema::event_attendees::event_id,
+                schema::event_attendees::user_id,
+            ))
+            .do_update()
+            .set((
+                schema::event_attendees::status.eq(excluded(schema::event_attendees::status)),
+                schema::event_attendees::responded_at
+                    .eq(excluded(schema::event_attendees::responded_at)),
+            ))
+            .execute(conn)
+            .await?;
+        if previous == Some(AttendanceStatus::Going) {
+            Self::promote_waitlisted(conn, event_id, capacity).await?;
+        }
+        Ok(status)
+    }
+
+    /// Forgets the user's answer to the event, as if they never gave one.
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn cancel_rsvp(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        self.retrying_transaction("cancel_rsvp", move |conn| {
+            async move { Self::cancel_rsvp_unatomic(conn, event_id, user_id).await }.scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn cancel_rsvp_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> error::Result<()> {
+        let (_, capacity) = Self::lock_event_for_attendance(conn, event_id).await?;
+        let previous: Option<String> = {
+            diesel::delete({
+                schema::event_attendees::table.find((event_id.into_inner(), user_id.into_inner()))
+            })
+            .returning(schema::event_attendees::status)
+            .get_result(conn)
+            .await
+            .optional()?
+        };
+        if previous.as_deref().map(AttendanceStatus::from_db) == Some(AttendanceStatus::Going) {
+            Self::promote_waitlisted(conn, event_id, capacity).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns `None` if the user hasn't answered.
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn find_rsvp(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<Option<AttendanceStatus>> {
+        let mut conn = self.conn().await?;
+        Self::find_rsvp_inner(&mut conn, event_id, user_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_rsvp_inner(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> error::Result<Option<AttendanceStatus>> {
+        Self::find_event_model(conn, event_id).await?;
+        Ok(Self::attendance_status(conn, event_id, user_id).await?)
+    }
+
+    /// Limits how many users can go to the event, `None` lifting the limit.
+    /// Raising it lets waitlisted users in. Lowering it below the number of
+    /// users going turns nobody away, but keeps new ones waiting until enough
+    /// of them have left.
+    #[instrument(skip_all, fields(%event_id, %user_id, ?capacity))]
+    pub async fn set_event_capacity(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        capacity: Option<u32>,
+    ) -> ApiResult<()> {
+        self.retrying_transaction("set_event_capacity", move |conn| {
+            async move {
+                Self::set_event_capacity_unatomic(conn, event_id, user_id, capacity).await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn set_event_capacity_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        capacity: Option<u32>,
+    ) -> error::Result<()> {
+        let capacity = match capacity.map(i32::try_from) {
+            None => None,
+            Some(Ok(capacity)) if capacity > 0 => Some(capacity),
+            Some(_) => {
+                return Err(error::Error::Api(ApiError::InvalidArgument(format!(
+                    "The event capacity must be between 1 and {}.",
+                    i32::MAX,
+                ))));
+            }
+        };
+        let (author_id, _) = Self::lock_event_for_attendance(conn, event_id).await?;
+        if user_id.into_inner() != author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't modify this event.".to_owned()
+            })));
+        }
+
+        diesel::update(schema::events::table.find(event_id.into_inner()))
+            .set(schema::events::capacity.eq(capacity))
+            .execute(conn)
+            .await?;
+        Self::promote_waitlisted(conn, event_id, capacity).await?;
+        Ok(())
+    }
+
+    /// The attendance of the events, e.g. of those on a listing page, by event
+    /// ID. Events that don't exist are left out.
+    #[instrument(skip_all, fields(event_count = event_ids.len()))]
+    pub async fn find_event_attendance(
+        &self,
+        event_ids: &[evops_models::EventId],
+    ) -> ApiResult<HashMap<Uuid, EventAttendance>> {
+        let mut conn = self.conn().await?;
+        Self::find_event_attendance_inner(&mut conn, event_ids)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_event_attendance_inner(
+        conn: &mut AsyncPgConnection,
+        event_ids: &[evops_models::EventId],
+    ) -> error::Result<HashMap<Uuid, EventAttendance>> {
+        let event_ids_raw: Vec<_> = {
+            event_ids
+                .iter()
+                .copied()
+                .map(evops_models::EventId::into_inner)
+                .collect()
+        };
+        let capacities: Vec<(Uuid, Option<i32>)> = {
+            schema::events::table
+                .filter(schema::events::id.eq_any(event_ids_raw))
+                .select((schema::events::id, schema::events::capacity))
+                .load(conn)
+                .await?
+        };
+        Ok(Self::attendance_of_events(conn, &capacities).await?)
+    }
+
+    /// The attendance of each of the events, given with their capacities.
+    pub(crate) async fn attendance_of_events(
+        conn: &mut AsyncPgConnection,
+        events: &[(Uuid, Option<i32>)],
+    ) -> QueryResult<HashMap<Uuid, EventAttendance>> {
+        let event_ids: Vec<_> = events.iter().map(|&(event_id, _)| event_id).collect();
+        let counts: Vec<(Uuid, String, i64)> = {
+            schema::event_attendees::table
+                .filter(schema::event_attendees::event_id.eq_any(event_ids))
+                .group_by((
+                    schema::event_attendees::event_id,
+                    schema::event_attendees::status,
+                ))
+                .select((
+                    schema::event_attendees::event_id,
+                    schema::event_attendees::status,
+                    dsl::count_star(),
+                ))
+                .load(conn)
+                .await?
+        };
+
+        let mut attendance: HashMap<_, _> = {
+            events
+                .iter()
+                .map(|&(event_id, capacity)| {
+                    let attendance = EventAttendance {
+                        capacity: capacity.and_then(|capacity| u32::try_from(capacity).ok()),
+                        ..EventAttendance::default()
+                    };
+                    (event_id, attendance)
+                })
+                .collect()
+        };
+        for (event_id, status, count) in counts {
+            let Some(attendance) = attendance.get_mut(&event_id) else {
+                continue;
+            };
+            let count = count.unsigned_abs();
+            match AttendanceStatus::from_db(&status) {
+                AttendanceStatus::Going => attendance.going = count,
+                AttendanceStatus::Interested => attendance.interested = count,
+                AttendanceStatus::Waitlisted => attendance.waitlisted = count,
+                AttendanceStatus::Declined => {}
+            }
+        }
+        Ok(attendance)
+    }
+
+    /// Locks the event against concurrent answers, which could otherwise
+    /// overfill it. Returns its author and capacity.
+    pub(crate) async fn lock_event_for_attendance(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+    ) -> error::Result<(Uuid, Option<i32>)> {
+        let event = {
+            schema::events::table
+                .find(event_id.into_inner())
+                .select((schema::events::author_id, schema::events::capacity))
+                .for_update()
+                .get_result(conn)
+                .await
+                .optional()?
+        };
+        event.ok_or_else(|| {
+            error::Error::Api(ApiError::NotFound(format!(
+                "No event with ID {event_id} found."
+            )))
+        })
+    }
+
+    async fn attendance_status(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> QueryResult<Option<AttendanceStatus>> {
+        let status: Option<String> = {
+            schema::event_attendees::table
+                .find((event_id.into_inner(), user_id.into_inner()))
+                .select(schema::event_attendees::status)
+                .get_result(conn)
+                .await
+                .optional()?
+        };
+        Ok(status.as_deref().map(AttendanceStatus::from_db))
+    }
+
+    async fn free_seats(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        capacity: Option<i32>,
+    ) -> QueryResult<i64> {
+        let Some(capacity) = capacity else {
+            return Ok(i64::MAX);
+        };
+        let going: i64 = {
+            schema::event_attendees::table
+                .filter(schema::event_attendees::event_id.eq(event_id.into_inner()))
+                .filter(schema::event_attendees::status.eq(AttendanceStatus::Going.as_str()))
+                .count()
+                .get_result(conn)
+                .await?
+        };
+        Ok(i64::from(capacity) - going)
+    }
+
+    /// Lets as many waitlisted users in as there are free seats, longest
+    /// waiting first.
+    async fn promote_waitlisted(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        capacity: Option<i32>,
+    ) -> QueryResult<()> {
+        let free_seats = Self::free_seats(conn, event_id, capacity).await?;
+        if free_seats <= 0 {
+            return Ok(());
+        }
+        let user_ids: Vec<Uuid> = {
+            schema::event_attendees::table
+                .filter(schema::event_attendees::event_id.eq(event_id.into_inner()))
+                .filter(schema::event_attendees::status.eq(AttendanceStatus::Waitlisted.as_str()))
+                .order((
+                    schema::event_attendees::responded_at.asc(),
+                    schema::event_attendees::user_id.asc(),
+                ))
+                .select(schema::event_attendees::user_id)
+                .limit(free_seats)
+                .load(conn)
+                .await?
+        };
+        diesel::update({
+            schema::event_attendees::table
+                .filter(schema::event_attendees::event_id.eq(event_id.into_inner()))
+                .filter(schema::event_attendees::user_id.eq_any(user_ids))
+        })
+        .set(schema::event_attendees::status.eq(AttendanceStatus::Going.as_str()))
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+}
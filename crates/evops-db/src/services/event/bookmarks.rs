@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use diesel::{BoolExpressionMethods as _, ExpressionMethods as _, QueryDsl as _};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::ApiResult;
+
+use crate::pagination::{self, Cursor, CursorValue, Page, TotalCount};
+use crate::{error, schema};
+
+impl crate::Database {
+    /// Bookmarks the event for the user, or drops the bookmark if there is
+    /// one. Returns whether the event is bookmarked now.
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn toggle_bookmark(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<bool> {
+        self.retrying_transaction("toggle_bookmark", move |conn| {
+            async move { Self::toggle_bookmark_unatomic(conn, event_id, user_id).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn toggle_bookmark_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> error::Result<bool> {
+        let deleted_count = {
+            diesel::delete({
+                schema::event_bookmarks::table.find((user_id.into_inner(), event_id.into_inner()))
+            })
+            .execute(conn)
+            .await?
+        };
+        if deleted_count > 0 {
+            return Ok(false);
+        }
+
+        // A concurrent toggle may have bookmarked the event in the meantime,
+        // which leaves it bookmarked just the same.
+        diesel::insert_into(schema::event_bookmarks::table)
+            .values((
+                schema::event_bookmarks::user_id.eq(user_id.into_inner()),
+                schema::event_bookmarks::event_id.eq(event_id.into_inner()),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?;
+        Ok(true)
+    }
+
+    /// Lists the events the user bookmarked, most recently bookmarked first.
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn list_bookmarked_events(
+        &self,
+        user_id: evops_models::UserId,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::Event>> {
+        let mut conn = self.conn().await?;
+        Self::list_bookmarked_events_inner(&mut conn, user_id, cursor, limit, count)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn list_bookmarked_events_inner(
+        conn: &mut AsyncPgConnection,
+        user_id: evops_models::UserId,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> error::Result<Page<evops_models::Event>> {
+        Self::find_user_model(conn, user_id).await?;
+        let limit = limit.map(i64::from);
+        let bookmarks = || {
+            schema::event_bookmarks::table
+                .filter(schema::event_bookmarks::user_id.eq(user_id.into_inner()))
+                .into_boxed()
+        };
+        let mut keys: Vec<(DateTime<Utc>, Uuid)> = {
+            let mut query = {
+                bookmarks()
+                    .select((
+                        schema::event_bookmarks::created_at,
+                        schema::event_bookmarks::event_id,
+                    ))
+                    .order((
+                        schema::event_bookmarks::created_at.desc(),
+                        schema::event_bookmarks::event_id.desc(),
+                    ))
+            };
+            if let Some(cursor) = cursor {
+                let &[
+                    CursorValue::Timestamp(last_created_at),
+                    CursorValue::Uuid(last_id),
+                ] = cursor.key(Cursor::BOOKMARKS)?
+                else {
+                    return Err(error::Error::Api(Cursor::invalid()));
+                };
+                query = query.filter({
+                    schema::event_bookmarks::created_at.lt(last_created_at).or({
+                        schema::event_bookmarks::created_at
+                            .eq(last_created_at)
+                            .and(schema::event_bookmarks::event_id.lt(last_id))
+                    })
+                });
+            }
+            if let Some(limit) = pagination::fetch_limit(limit) {
+                query = query.limit(limit);
+            }
+            query.load(conn).await?
+        };
+        let (has_more, next_cursor) = {
+            pagination::split_page(&mut keys, limit, |&(created_at, event_id)| {
+                let key = vec![
+                    CursorValue::Timestamp(created_at),
+                    CursorValue::Uuid(event_id),
+                ];
+                Cursor::new(Cursor::BOOKMARKS, key)
+            })
+        };
+        let total = {
+            let query = bookmarks().select(schema::event_bookmarks::event_id);
+            pagination::total(conn, query, count).await?
+        };
+
+        let event_ids = keys.into_iter().map(|(_, event_id)| event_id).collect();
+        let events = Self::list_events_private(conn, event_ids).await?;
+        Ok(Page {
+            items: events,
+            has_more,
+            next_cursor,
+            total,
+        })
+    }
+}
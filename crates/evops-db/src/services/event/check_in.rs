@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+use diesel::dsl;
+use diesel::{ExpressionMethods as _, OptionalExtension as _, QueryDsl as _};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::services::event::attendance::AttendanceStatus;
+use crate::{error, schema};
+
+/// How many of the users going to an event have shown up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CheckInReport {
+    /// Users going to the event.
+    pub registered: u64,
+    /// Users going to the event who have checked in.
+    pub checked_in: u64,
+}
+
+/// The BLAKE3 hash of a check-in token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckInTokenHash(Vec<u8>);
+
+impl CheckInTokenHash {
+    #[must_use]
+    pub const fn new(hash: Vec<u8>) -> Self {
+        Self(hash)
+    }
+}
+
+impl AsRef<[u8]> for CheckInTokenHash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl crate::Database {
+    /// Gives the attendee, who has to be going to the event, a check-in token
+    /// and replaces the one they had. The attendee and the author of the event
+    /// can do this. Just the hash of the token is passed in and stored, the
+    /// token itself is up to the caller to hand out.
+    #[instrument(skip_all, fields(%event_id, %user_id, %attendee_id))]
+    pub async fn issue_check_in_token(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        attendee_id: evops_models::UserId,
+        token_hash: &CheckInTokenHash,
+    ) -> ApiResult<()> {
+        self.retrying_transaction("issue_check_in_token", move |conn| {
+            async move {
+                Self::issue_check_in_token_unatomic(
+                    conn,
+                    event_id,
+                    user_id,
+                    attendee_id,
+                    token_hash,
+                )
+                .await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn issue_check_in_token_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        attendee_id: evops_models::UserId,
+        token_hash: &CheckInTokenHash,
+    ) -> error::Result<()> {
+        let (author_id, _) = Self::lock_event_for_attendance(conn, event_id).await?;
+        if user_id != attendee_id && user_id.into_inner() != author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't issue check-in tokens for other attendees of this event.".to_owned()
+            })));
+        }
+        let attendee: Option<(String, Option<DateTime<Utc>>)> = {
+            schema::event_attendees::table
+                .find((event_id.into_inner(), attendee_id.into_inner()))
+                .select((
+                    schema::event_attendees::status,
+                    schema::event_attendees::checked_in_at,
+                ))
+                .get_result(conn)
+                .await
+                .optional()?
+        };
+        match attendee {
+            Some((_, Some(checked_in_at))) => {
+                return Err(error::Error::Api(ApiError::AlreadyExists(format!(
+                    "User {attendee_id} already checked in at {checked_in_at}.",
+                ))));
+            }
+            Some((status, None)) if status == AttendanceStatus::Going.as_str() => {}
+            _ => {
+                return Err(error::Error::Api(ApiError::InvalidArgument(format!(
+                    "User {attendee_id} isn't going to event {event_id}.",
+                ))));
+            }
+        }
+
+        diesel::update({
+            schema::event_attendees::table.find((event_id.into_inner(), attendee_id.into_inner()))
+        })
+        .set(schema::event_attendees::check_in_token_blake3.eq(token_hash.as_ref()))
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Checks in the holder of the token, which only the author of the event
+    /// can do and only once per token. Returns the user checked in.
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn redeem_check_in_token(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        token_hash: &CheckInTokenHash,
+    ) -> ApiResult<evops_models::UserId> {
+        self.retrying_transaction("redeem_check_in_token", move |conn| {
+            async move {
+                Self::redeem_check_in_token_unatomic(conn, event_id, user_id, token_hash).await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn redeem_check_in_token_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        token_hash: &CheckInTokenHash,
+    ) -> error::Result<evops_models::UserId> {
+        let (author_id, _) = Self::lock_event_for_attendance(conn, event_id).await?;
+        if user_id.into_inner() != author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't check in attendees of this event.".to_owned()
+            })));
+        }
+        let attendee: Option<(Uuid, String, Option<DateTime<Utc>>)> = {
+            schema::event_attendees::table
+                .filter(schema::event_attendees::event_id.eq(event_id.into_inner()))
+                .filter(schema::event_attendees::check_in_token_blake3.eq(token_hash.as_ref()))
+                .select((
+                    schema::event_attendees::user_id,
+                    schema::event_attendees::status,
+                    schema::event_attendees::checked_in_at,
+                ))
+                .get_result(conn)
+                .await
+                .optional()?
+        };
+        let attendee_id = match attendee {
+            None => {
+                return Err(error::Error::Api(ApiError::InvalidArgument({
+                    "Invalid check-in token.".to_owned()
+                })));
+            }
+            Some((attendee_id, _, Some(checked_in_at))) => {
+                return Err(error::Error::Api(ApiError::AlreadyExists(format!(
+                    "User {attendee_id} already checked in at {checked_in_at}.",
+                ))));
+            }
+            // Tokens outlive the answer they were issued for, so that going
+            // again after declining needs no new one.
+            Some((attendee_id, status, None)) if status != AttendanceStatus::Going.as_str() => {
+                return Err(error::Error::Api(ApiError::InvalidArgument(format!(
+                    "User {attendee_id} isn't going to event {event_id}.",
+                ))));
+            }
+            Some((attendee_id, _, None)) => attendee_id,
+        };
+
+        diesel::update(schema::event_attendees::table.find((event_id.into_inner(), attendee_id)))
+            .set(schema::event_attendees::checked_in_at.eq(dsl::now))
+            .execute(conn)
+            .await?;
+        Ok(evops_models::UserId::new(attendee_id))
+    }
+
+    /// Only the author of the event can see it.
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn check_in_report(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<CheckInReport> {
+        let mut conn = self.conn().await?;
+        Self::check_in_report_inner(&mut conn, event_id, user_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn check_in_report_inner(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> error::Result<CheckInReport> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        if user_id.into_inner() != event_model.author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't see who checked in to this event.".to_owned()
+            })));
+        }
+
+        let (registered, checked_in): (i64, i64) = {
+            schema::event_attendees::table
+                .filter(schema::event_attendees::event_id.eq(event_id.into_inner()))
+                .filter(schema::event_attendees::status.eq(AttendanceStatus::Going.as_str()))
+                .select((
+                    dsl::count_star(),
+                    dsl::count(schema::event_attendees::checked_in_at),
+                ))
+                .get_result(conn)
+                .await?
+        };
+        Ok(CheckInReport {
+            registered: registered.unsigned_abs(),
+            checked_in: checked_in.unsigned_abs(),
+        })
+    }
+}
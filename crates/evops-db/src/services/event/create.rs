@@ -1,13 +1,15 @@
 use chrono::{DateTime, Utc};
 use diesel::Insertable;
 use diesel_async::scoped_futures::ScopedFutureExt as _;
-use diesel_async::{AsyncConnection as _, AsyncPgConnection, RunQueryDsl as _};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
 use itertools::Itertools as _;
+use tracing::instrument;
 use uuid::Uuid;
 
-use evops_models::{ApiError, ApiResult};
+use evops_models::ApiResult;
 
-use crate::schema;
+use crate::services::event::schedule::EventSchedule;
+use crate::{error, schema};
 
 #[derive(Insertable)]
 #[diesel(table_name = schema::events)]
@@ -19,6 +21,9 @@ struct NewEvent<'a> {
     author_id: Uuid,
     created_at: &'a DateTime<Utc>,
     modified_at: &'a DateTime<Utc>,
+    starts_at: &'a DateTime<Utc>,
+    ends_at: &'a DateTime<Utc>,
+    timezone: &'a str,
 }
 
 #[derive(Insertable)]
@@ -31,24 +36,28 @@ struct NewEventToTag {
 }
 
 impl crate::Database {
+    #[instrument(skip_all, fields(%author_id))]
     pub async fn create_event(
-        &mut self,
+        &self,
         form: evops_models::NewEventForm,
+        schedule: EventSchedule,
         author_id: evops_models::UserId,
     ) -> ApiResult<evops_models::EventId> {
-        self.conn
-            .transaction(|conn| {
-                async { unsafe { Self::create_event_unatomic(conn, form, author_id).await } }
-                    .scope_boxed()
-            })
-            .await
+        let (form, schedule) = (&form, &schedule);
+        self.retrying_transaction("create_event", move |conn| {
+            async move { Self::create_event_unatomic(conn, form, schedule, author_id).await }
+                .scope_boxed()
+        })
+        .await
     }
 
-    async unsafe fn create_event_unatomic(
+    pub(crate) async fn create_event_unatomic(
         conn: &mut AsyncPgConnection,
-        form: evops_models::NewEventForm,
+        form: &evops_models::NewEventForm,
+        schedule: &EventSchedule,
         author_id: evops_models::UserId,
-    ) -> ApiResult<evops_models::EventId> {
+    ) -> error::Result<evops_models::EventId> {
+        Self::check_event_timezone(conn, &schedule.timezone).await?;
         let event_id = evops_models::EventId::new(Uuid::now_v7());
 
         let now = Utc::now();
@@ -60,18 +69,17 @@ impl crate::Database {
                 author_id: author_id.into_inner(),
                 created_at: &now,
                 modified_at: &now,
+                starts_at: &schedule.starts_at,
+                ends_at: &schedule.ends_at,
+                timezone: schedule.timezone.as_ref(),
             })
             .execute(conn)
-            .await
-            .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(e.to_string()),
-                _ => e.into(),
-            })?;
+            .await?;
 
         diesel::insert_into(schema::events_to_tags::table)
             .values({
                 form.tag_ids
-                    .into_inner()
+                    .as_ref()
                     .iter()
                     .map(|tag_id| self::NewEventToTag {
                         event_id: event_id.into_inner(),
@@ -80,11 +88,7 @@ impl crate::Database {
                     .collect_vec()
             })
             .execute(conn)
-            .await
-            .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(e.to_string()),
-                _ => e.into(),
-            })?;
+            .await?;
 
         Ok(event_id)
     }
@@ -1,69 +1,65 @@
 use diesel::ExpressionMethods as _;
 use diesel::QueryDsl as _;
-use diesel_async::AsyncConnection as _;
 use diesel_async::AsyncPgConnection;
 use diesel_async::RunQueryDsl as _;
 use diesel_async::scoped_futures::ScopedFutureExt as _;
+use tracing::instrument;
 
 use evops_models::{ApiError, ApiResult};
 
-use crate::schema;
+use crate::{error, schema};
 
 impl crate::Database {
+    #[instrument(skip_all, fields(%event_id, %user_id))]
     pub async fn delete_event(
-        &mut self,
+        &self,
         event_id: evops_models::EventId,
         user_id: evops_models::UserId,
     ) -> ApiResult<evops_models::EventImageIds> {
-        let event = self.find_event(event_id).await?;
-        if user_id != event.author.id {
-            return Err(ApiError::Forbidden({
+        self.retrying_transaction("delete_event", move |conn| {
+            async move { Self::delete_event_unatomic(conn, event_id, user_id).await }.scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn delete_event_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> error::Result<evops_models::EventImageIds> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        if user_id.into_inner() != event_model.author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
                 "You can't delete this event.".to_owned()
-            }));
+            })));
         }
+        let image_ids = Self::image_ids_of_event_model_sorted(conn, &event_model).await?;
 
-        self.conn
-            .transaction(|conn| {
-                async {
-                    unsafe { Self::delete_events_to_tags(conn, event_id) }.await?;
-                    unsafe { Self::delete_event_table(conn, event_id) }.await?;
-                    ApiResult::Ok(())
-                }
-                .scope_boxed()
-            })
-            .await?;
-
-        Ok(event.image_ids)
+        Self::delete_events_to_tags(conn, event_id).await?;
+        Self::delete_event_table(conn, event_id).await?;
+        Ok(image_ids)
     }
 
-    async unsafe fn delete_events_to_tags(
+    async fn delete_events_to_tags(
         conn: &mut AsyncPgConnection,
         id: evops_models::EventId,
-    ) -> ApiResult<()> {
+    ) -> error::Result<()> {
         diesel::delete(
             schema::events_to_tags::table
                 .filter(schema::events_to_tags::event_id.eq(id.into_inner())),
         )
         .execute(conn)
-        .await
-        .map_err(|e| match e {
-            diesel::result::Error::NotFound => ApiError::NotFound(e.to_string()),
-            _ => e.into(),
-        })?;
+        .await?;
         Ok(())
     }
 
-    async unsafe fn delete_event_table(
+    async fn delete_event_table(
         conn: &mut AsyncPgConnection,
         id: evops_models::EventId,
-    ) -> ApiResult<()> {
+    ) -> error::Result<()> {
         diesel::delete(schema::events::table.find(id.into_inner()))
             .execute(conn)
-            .await
-            .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(e.to_string()),
-                _ => e.into(),
-            })?;
+            .await?;
         Ok(())
     }
 }
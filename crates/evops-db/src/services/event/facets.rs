@@ -0,0 +1,78 @@
+use diesel::dsl::count_star;
+use diesel::{ExpressionMethods as _, QueryDsl as _};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::ApiResult;
+
+use crate::error;
+use crate::schema;
+use crate::services::event::filter::EventFilter;
+
+/// How many events matching a filter have a certain tag.
+#[derive(Debug, Clone)]
+pub struct TagFacet {
+    pub tag_id: evops_models::TagId,
+    pub tag_name: evops_models::TagName,
+    pub event_count: u64,
+}
+
+impl crate::Database {
+    /// Returns the tags most common among the events [`Self::list_events`]
+    /// would return for `filter`, most common first.
+    #[instrument(skip_all)]
+    pub async fn tag_facets(
+        &self,
+        filter: &EventFilter,
+        limit: Option<evops_models::PgLimit>,
+    ) -> ApiResult<Vec<TagFacet>> {
+        let mut conn = self.conn().await?;
+        Self::tag_facets_inner(&mut conn, filter, limit)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn tag_facets_inner(
+        conn: &mut AsyncPgConnection,
+        filter: &EventFilter,
+        limit: Option<evops_models::PgLimit>,
+    ) -> error::Result<Vec<TagFacet>> {
+        filter.check()?;
+        let mut event_ids = {
+            schema::events::table
+                .select(schema::events::id)
+                .into_boxed()
+        };
+        for condition in filter.conditions() {
+            event_ids = event_ids.filter(condition);
+        }
+        let mut query = {
+            schema::events_to_tags::table
+                .inner_join(schema::tags::table)
+                .filter(schema::events_to_tags::event_id.eq_any(event_ids))
+                .group_by((schema::tags::id, schema::tags::name))
+                .select((schema::tags::id, schema::tags::name, count_star()))
+                .order_by(count_star().desc())
+                .then_order_by(schema::tags::name.asc())
+                .into_boxed()
+        };
+        if let Some(limit) = limit {
+            query = query.limit(limit.into());
+        }
+
+        let facets = {
+            query
+                .load::<(Uuid, String, i64)>(conn)
+                .await?
+                .into_iter()
+                .map(|(tag_id, tag_name, event_count)| TagFacet {
+                    tag_id: evops_models::TagId::new(tag_id),
+                    tag_name: unsafe { evops_models::TagName::new_unchecked(tag_name) },
+                    event_count: event_count.unsigned_abs(),
+                })
+                .collect()
+        };
+        Ok(facets)
+    }
+}
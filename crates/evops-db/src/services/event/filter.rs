@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use diesel::dsl;
+use diesel::expression::BoxableExpression;
+use diesel::pg::Pg;
+use diesel::sql_types::Bool;
+use diesel::{BoolExpressionMethods as _, ExpressionMethods as _};
+
+use evops_models::ApiResult;
+
+use crate::schema;
+use crate::services::event::geo_filter::GeoRadius;
+use crate::services::event::search_filter::EventSearch;
+use crate::services::event::tag_filter::TagFilter;
+
+pub(crate) type EventCondition =
+    Box<dyn BoxableExpression<schema::events::table, Pg, SqlType = Bool>>;
+
+/// A span of time, open on the sides that are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    /// Inclusive.
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive.
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    pub(crate) fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| since <= time) && self.until.is_none_or(|until| time < until)
+    }
+}
+
+/// Where an event's schedule is relative to the present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventPhase {
+    /// Not started yet.
+    Upcoming,
+    /// Started but not ended yet.
+    HappeningNow,
+    /// Ended already.
+    Past,
+}
+
+impl EventPhase {
+    pub(crate) fn contains(
+        self,
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        match self {
+            Self::Upcoming => now < starts_at,
+            Self::HappeningNow => starts_at <= now && now < ends_at,
+            Self::Past => ends_at <= now,
+        }
+    }
+
+    fn condition(self) -> EventCondition {
+        match self {
+            Self::Upcoming => Box::new(schema::events::starts_at.gt(dsl::now)),
+            Self::HappeningNow => Box::new({
+                (schema::events::starts_at.le(dsl::now)).and(schema::events::ends_at.gt(dsl::now))
+            }),
+            Self::Past => Box::new(schema::events::ends_at.le(dsl::now)),
+        }
+    }
+}
+
+/// Which events a listing returns. The default lets every event through.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Only events by one of these users. An empty list filters nothing.
+    pub author_ids: Vec<evops_models::UserId>,
+    pub created: TimeRange,
+    pub modified: TimeRange,
+    pub phase: Option<EventPhase>,
+    /// Only events at a venue within the radius.
+    pub near: Option<GeoRadius>,
+    pub tags: TagFilter,
+    pub search: Option<String>,
+}
+
+impl EventFilter {
+    pub(crate) fn search(&self) -> EventSearch {
+        EventSearch::new(self.search.clone())
+    }
+
+    /// Rejects filters that can't be turned into conditions.
+    pub(crate) fn check(&self) -> ApiResult<()> {
+        if let Some(near) = self.near {
+            near.check()?;
+        }
+        Ok(())
+    }
+
+    /// Everything an event has to satisfy to be listed.
+    pub(crate) fn conditions(&self) -> Vec<EventCondition> {
+        let mut conditions: Vec<EventCondition> = Vec::new();
+        conditions.extend(self.search().matches());
+        conditions.extend(self.tags.conditions());
+        if !self.author_ids.is_empty() {
+            let author_ids: Vec<_> = {
+                self.author_ids
+                    .iter()
+                    .copied()
+                    .map(evops_models::UserId::into_inner)
+                    .collect()
+            };
+            conditions.push(Box::new(schema::events::author_id.eq_any(author_ids)));
+        }
+        if let Some(since) = self.created.since {
+            conditions.push(Box::new(schema::events::created_at.ge(since)));
+        }
+        if let Some(until) = self.created.until {
+            conditions.push(Box::new(schema::events::created_at.lt(until)));
+        }
+        if let Some(since) = self.modified.since {
+            conditions.push(Box::new(schema::events::modified_at.ge(since)));
+        }
+        if let Some(until) = self.modified.until {
+            conditions.push(Box::new(schema::events::modified_at.lt(until)));
+        }
+        if let Some(phase) = self.phase {
+            conditions.push(phase.condition());
+        }
+        if let Some(near) = self.near {
+            conditions.push(near.condition());
+        }
+        conditions
+    }
+}
@@ -2,26 +2,36 @@ use diesel::{BelongingToDsl as _, QueryResult};
 use diesel::{QueryDsl as _, SelectableHelper as _};
 use diesel_async::AsyncPgConnection;
 use diesel_async::RunQueryDsl as _;
+use tracing::instrument;
 
 use evops_models::{ApiError, ApiResult};
 
+use crate::error;
 use crate::models;
 use crate::schema;
 
 impl crate::Database {
-    pub async fn find_event(
-        &mut self,
+    #[instrument(skip_all, fields(event_id = %id))]
+    pub async fn find_event(&self, id: evops_models::EventId) -> ApiResult<evops_models::Event> {
+        let mut conn = self.conn().await?;
+        Self::find_event_inner(&mut conn, id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_event_inner(
+        conn: &mut AsyncPgConnection,
         id: evops_models::EventId,
-    ) -> ApiResult<evops_models::Event> {
-        let event_model = Self::find_event_model(&mut self.conn, id).await?;
+    ) -> error::Result<evops_models::Event> {
+        let event_model = Self::find_event_model(conn, id).await?;
         let event = evops_models::Event {
             id,
             author: {
-                self.find_user(evops_models::UserId::new(event_model.author_id))
+                Self::find_user_inner(conn, evops_models::UserId::new(event_model.author_id))
                     .await?
             },
-            image_ids: Self::image_ids_of_event_model_sorted(&mut self.conn, &event_model).await?,
-            tags: Self::tags_of_event_model(&mut self.conn, &event_model).await?,
+            image_ids: Self::image_ids_of_event_model_sorted(conn, &event_model).await?,
+            tags: Self::tags_of_event_model(conn, &event_model).await?,
             title: unsafe { evops_models::EventTitle::new_unchecked(event_model.title) },
             description: unsafe {
                 evops_models::EventDescription::new_unchecked(event_model.description)
@@ -31,10 +41,11 @@ impl crate::Database {
         };
         Ok(event)
     }
+
     pub(crate) async fn find_event_model(
         conn: &mut AsyncPgConnection,
         id: evops_models::EventId,
-    ) -> ApiResult<models::Event> {
+    ) -> error::Result<models::Event> {
         schema::events::table
             .find(id.into_inner())
             .select(models::Event::as_select())
@@ -42,7 +53,7 @@ impl crate::Database {
             .await
             .map_err(|e| match e {
                 diesel::result::Error::NotFound => {
-                    ApiError::NotFound(format!("No event with ID {id} found."))
+                    ApiError::NotFound(format!("No event with ID {id} found.")).into()
                 }
                 _ => e.into(),
             })
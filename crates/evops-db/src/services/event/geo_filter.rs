@@ -0,0 +1,99 @@
+use diesel::dsl::{AsExprOf, sql};
+use diesel::expression::{SqlLiteral, UncheckedBind};
+use diesel::sql_types::{Bool, Double, Nullable};
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::services::event::filter::EventCondition;
+use crate::services::venue::GeoPoint;
+
+/// The mean radius of the Earth, as in the `haversine_km` SQL function.
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+type NullableDouble = AsExprOf<Option<f64>, Nullable<Double>>;
+
+pub(crate) type Distance = SqlLiteral<
+    Nullable<Double>,
+    UncheckedBind<
+        SqlLiteral<Nullable<Double>, UncheckedBind<SqlLiteral<Nullable<Double>>, NullableDouble>>,
+        NullableDouble,
+    >,
+>;
+
+/// Events at venues within `radius_km` of `center`, along the surface of the
+/// Earth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoRadius {
+    pub center: GeoPoint,
+    pub radius_km: f64,
+}
+
+impl GeoRadius {
+    pub(crate) fn check(self) -> ApiResult<()> {
+        self.center.check()?;
+        if !(self.radius_km.is_finite() && self.radius_km >= 0.0) {
+            return Err(ApiError::InvalidArgument({
+                "The search radius must be a non-negative number of kilometers.".to_owned()
+            }));
+        }
+        Ok(())
+    }
+
+    /// A bounding box lets Postgres narrow the venues down by index before
+    /// computing any distance.
+    pub(crate) fn condition(self) -> EventCondition {
+        let GeoPoint {
+            latitude,
+            longitude,
+        } = self.center;
+        let angle = self.radius_km / EARTH_RADIUS_KM;
+        let latitude_delta = angle.to_degrees();
+        let min_latitude = latitude - latitude_delta;
+        let max_latitude = latitude + latitude_delta;
+        // How far the longitude of the circle reaches at the latitude of its
+        // center, which is where it reaches the farthest. Near the poles and
+        // the antimeridian the box would wrap around, so there the longitude
+        // isn't narrowed.
+        let longitude_ratio = angle.sin() / latitude.to_radians().cos();
+        let (mut min_longitude, mut max_longitude) = (-180.0, 180.0);
+        if -90.0 < min_latitude && max_latitude < 90.0 && longitude_ratio < 1.0 {
+            let longitude_delta = longitude_ratio.asin().to_degrees();
+            if -180.0 <= longitude - longitude_delta && longitude + longitude_delta <= 180.0 {
+                min_longitude = longitude - longitude_delta;
+                max_longitude = longitude + longitude_delta;
+            }
+        }
+
+        let condition = {
+            sql::<Bool>(
+                "events.venue_id IN (SELECT venues.id FROM venues \
+                 WHERE venues.latitude BETWEEN ",
+            )
+            .bind::<Double, _>(min_latitude)
+            .sql(" AND ")
+            .bind::<Double, _>(max_latitude)
+            .sql(" AND venues.longitude BETWEEN ")
+            .bind::<Double, _>(min_longitude)
+            .sql(" AND ")
+            .bind::<Double, _>(max_longitude)
+            .sql(" AND haversine_km(venues.latitude, venues.longitude, ")
+            .bind::<Double, _>(latitude)
+            .sql(", ")
+            .bind::<Double, _>(longitude)
+            .sql(") <= ")
+            .bind::<Double, _>(self.radius_km)
+            .sql(")")
+        };
+        Box::new(condition)
+    }
+}
+
+/// The distance in kilometers from `center` to the venue of an event, or
+/// `NULL` for events without a venue and when there is no `center`.
+pub(crate) fn distance(center: Option<GeoPoint>) -> Distance {
+    sql::<Nullable<Double>>("(SELECT haversine_km(venues.latitude, venues.longitude, ")
+        .bind::<Nullable<Double>, _>(center.map(|center| center.latitude))
+        .sql(", ")
+        .bind::<Nullable<Double>, _>(center.map(|center| center.longitude))
+        .sql(") FROM venues WHERE venues.id = events.venue_id)")
+}
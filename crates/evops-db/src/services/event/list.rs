@@ -1,83 +1,240 @@
 use std::collections::HashMap;
 
+use chrono::{DateTime, Utc};
+use diesel::dsl::sql;
+use diesel::sql_types::{self, Bool, Timestamptz};
 use diesel::{
-    BoolExpressionMethods as _, ExpressionMethods as _, JoinOnDsl as _,
-    PgSortExpressionMethods as _, PgTextExpressionMethods as _, QueryDsl as _, QueryResult,
-    SelectableHelper as _,
+    BoolExpressionMethods as _, ExpressionMethods as _, NullableExpressionMethods as _,
+    QueryDsl as _, SelectableHelper as _,
 };
 use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
 use itertools::Itertools as _;
+use tracing::instrument;
 use uuid::Uuid;
 
 use evops_models::ApiResult;
 
+use crate::error;
 use crate::models;
+use crate::pagination::{self, Cursor, Page, Total, TotalCount};
 use crate::schema;
+use crate::services::event::filter::{EventCondition, EventFilter};
+use crate::services::event::geo_filter;
+use crate::services::event::sort::{EventKey, EventSort};
 
 impl crate::Database {
+    #[instrument(skip_all, fields(tag_count = filter.tags.tags.len(), ?sort))]
     pub async fn list_events(
-        &mut self,
-        last_id: Option<evops_models::EventId>,
+        &self,
+        cursor: Option<Cursor>,
         limit: Option<evops_models::PgLimit>,
-        tags: Vec<evops_models::TagId>,
-        search: Option<String>,
-    ) -> ApiResult<Vec<evops_models::Event>> {
-        let event_ids =
-            Self::list_event_ids_raw(&mut self.conn, last_id, limit, tags, search).await?;
-        Self::list_events_private(&mut self.conn, event_ids).await
+        filter: &EventFilter,
+        sort: EventSort,
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::Event>> {
+        let mut conn = self.conn().await?;
+        Self::list_events_inner(&mut conn, cursor, limit, filter, sort, count)
+            .await
+            .map_err(Into::into)
     }
 
-    pub(crate) async fn list_event_ids_raw(
+    pub(crate) async fn list_events_inner(
         conn: &mut AsyncPgConnection,
-        last_id: Option<evops_models::EventId>,
+        cursor: Option<Cursor>,
         limit: Option<evops_models::PgLimit>,
-        tags: Vec<evops_models::TagId>,
-        search: Option<String>,
-    ) -> QueryResult<Vec<Uuid>> {
-        let tags: Vec<_> = tags
-            .into_iter()
-            .map(evops_models::TagId::into_inner)
-            .collect();
+        filter: &EventFilter,
+        sort: EventSort,
+        count: TotalCount,
+    ) -> error::Result<Page<evops_models::Event>> {
+        let limit = limit.map(i64::from);
+        let mut keys = Self::list_event_ids_raw(conn, cursor.as_ref(), limit, filter, sort).await?;
+        let (has_more, next_cursor) =
+            pagination::split_page(&mut keys, limit, |key| key.cursor(sort));
+        let event_ids = keys.into_iter().map(|key| key.id).collect();
+        let events = Self::list_events_private(conn, event_ids).await?;
+        let total = Self::count_events(conn, filter, count).await?;
+        Ok(Page {
+            items: events,
+            has_more,
+            next_cursor,
+            total,
+        })
+    }
+
+    /// Counts the events `filter` lets through, as asked for by `count`.
+    pub(crate) async fn count_events(
+        conn: &mut AsyncPgConnection,
+        filter: &EventFilter,
+        count: TotalCount,
+    ) -> error::Result<Option<Total>> {
         let mut query = schema::events::table
-            .left_join(
-                schema::events_to_tags::table.on(schema::events::id
-                    .eq(schema::events_to_tags::event_id)
-                    .and(schema::events_to_tags::tag_id.eq_any(&tags))),
-            )
-            .group_by(schema::events::id)
             .select(schema::events::id)
-            .order_by(
-                diesel::dsl::count(schema::events_to_tags::tag_id)
-                    .desc()
-                    .nulls_last(),
-            )
-            .then_order_by(schema::events::id.desc())
             .into_boxed();
-        tracing::info!(
-            "Query: {}",
-            diesel::debug_query::<diesel::pg::Pg, _>(&query).to_string()
-        );
-        if let Some(search_term) = search {
-            query = query.filter(
-                schema::events::title
-                    .ilike(format!("%{search_term}%"))
-                    .or(schema::events::description.ilike(format!("%{search_term}%"))),
-            );
+        for condition in filter.conditions() {
+            query = query.filter(condition);
+        }
+        Ok(pagination::total(conn, query, count).await?)
+    }
+
+    /// Returns the sort keys of the events on the page after `cursor`, plus
+    /// one more if there is one, see [`pagination::split_page`].
+    pub(crate) async fn list_event_ids_raw(
+        conn: &mut AsyncPgConnection,
+        cursor: Option<&Cursor>,
+        limit: Option<i64>,
+        filter: &EventFilter,
+        sort: EventSort,
+    ) -> error::Result<Vec<EventKey>> {
+        filter.check()?;
+        sort.check(filter)?;
+        let search = filter.search();
+        let rank = search.fulltext_rank();
+        let similarity = search.trigram_rank();
+        let tag_matches = filter.tags.match_count();
+        let distance = geo_filter::distance(filter.near.map(|near| near.center));
+
+        let mut query = {
+            schema::events::table
+                .select((
+                    schema::events::id,
+                    rank.clone(),
+                    similarity.clone(),
+                    tag_matches.clone(),
+                    schema::events::created_at,
+                    schema::events::modified_at,
+                    schema::events::starts_at,
+                    distance.clone(),
+                ))
+                .into_boxed()
+        };
+        query = match sort {
+            EventSort::Relevance => query
+                .order_by(rank.clone().desc())
+                .then_order_by(similarity.clone().desc())
+                .then_order_by(tag_matches.clone().desc())
+                .then_order_by(schema::events::id.desc()),
+            EventSort::TagMatches => query
+                .order_by(tag_matches.clone().desc())
+                .then_order_by(schema::events::id.desc()),
+            EventSort::Newest => {
+                query.order_by((schema::events::created_at.desc(), schema::events::id.desc()))
+            }
+            EventSort::RecentlyModified => query.order_by((
+                schema::events::modified_at.desc(),
+                schema::events::id.desc(),
+            )),
+            EventSort::Oldest => {
+                query.order_by((schema::events::created_at.asc(), schema::events::id.asc()))
+            }
+            EventSort::EarliestStart => {
+                query.order_by((schema::events::starts_at.asc(), schema::events::id.asc()))
+            }
+            EventSort::LatestStart => {
+                query.order_by((schema::events::starts_at.desc(), schema::events::id.desc()))
+            }
+            EventSort::Nearest => query
+                .order_by(distance.clone().asc())
+                .then_order_by(schema::events::id.asc()),
+        };
+        for condition in filter.conditions() {
+            query = query.filter(condition);
         }
-        if let Some(last_id) = last_id {
-            query = query.filter(schema::events::id.gt(last_id.into_inner()));
+        if let Some(cursor) = cursor {
+            let last = EventKey::from_cursor(cursor, sort)?;
+            // An event comes after the cursor's one when it sorts lower on
+            // some key and equal on all keys before that one.
+            let after_id = schema::events::id.lt(last.id);
+            let after_tag_matches = {
+                (tag_matches.clone().lt(last.tag_matches))
+                    .or(tag_matches.eq(last.tag_matches).and(after_id))
+            };
+            let after: EventCondition = match sort {
+                EventSort::Relevance => {
+                    let after_similarity = {
+                        (similarity.clone().lt(last.similarity))
+                            .or(similarity.eq(last.similarity).and(after_tag_matches))
+                    };
+                    Box::new(
+                        (rank.clone().lt(last.rank)).or(rank.eq(last.rank).and(after_similarity)),
+                    )
+                }
+                EventSort::TagMatches => Box::new(after_tag_matches),
+                EventSort::Newest => {
+                    self::after_timestamp("created_at", "<", last.created_at, last.id)
+                }
+                EventSort::RecentlyModified => {
+                    self::after_timestamp("modified_at", "<", last.modified_at, last.id)
+                }
+                EventSort::Oldest => {
+                    self::after_timestamp("created_at", ">", last.created_at, last.id)
+                }
+                EventSort::EarliestStart => {
+                    self::after_timestamp("starts_at", ">", last.starts_at, last.id)
+                }
+                EventSort::LatestStart => {
+                    self::after_timestamp("starts_at", "<", last.starts_at, last.id)
+                }
+                // The filter leaves only events with a venue, so the distance
+                // is never `NULL`.
+                EventSort::Nearest => {
+                    let distance = distance.assume_not_null();
+                    Box::new(
+                        (distance.clone().gt(last.distance)).or(distance
+                            .eq(last.distance)
+                            .and(schema::events::id.gt(last.id))),
+                    )
+                }
+            };
+            query = query.filter(after);
         }
-        if let Some(limit) = limit {
-            query = query.limit(limit.into());
+        if let Some(limit) = pagination::fetch_limit(limit) {
+            query = query.limit(limit);
         }
-        let event_ids_raw = query.load(conn).await?;
-        Ok(event_ids_raw)
+
+        let keys = {
+            query
+                .load::<(
+                    Uuid,
+                    f32,
+                    f32,
+                    i64,
+                    DateTime<Utc>,
+                    DateTime<Utc>,
+                    DateTime<Utc>,
+                    Option<f64>,
+                )>(conn)
+                .await?
+                .into_iter()
+                .map(
+                    |(
+                        id,
+                        rank,
+                        similarity,
+                        tag_matches,
+                        created_at,
+                        modified_at,
+                        starts_at,
+                        distance,
+                    )| EventKey {
+                        id,
+                        rank,
+                        similarity,
+                        tag_matches,
+                        created_at,
+                        modified_at,
+                        starts_at,
+                        distance: distance.unwrap_or_default(),
+                    },
+                )
+                .collect()
+        };
+        Ok(keys)
     }
 
     async fn get_events_with_authors(
         conn: &mut AsyncPgConnection,
         event_ids_raw: &Vec<Uuid>,
-    ) -> ApiResult<Vec<(models::Event, models::User)>> {
+    ) -> error::Result<Vec<(models::Event, models::User)>> {
         let result_raw: Vec<(models::Event, models::User)> = schema::events::table
             .inner_join(schema::users::table)
             .filter(schema::events::id.eq_any(event_ids_raw))
@@ -85,7 +242,9 @@ impl crate::Database {
             .load(conn)
             .await
             .map_err(|e| match e {
-                diesel::result::Error::NotFound => evops_models::ApiError::NotFound(e.to_string()),
+                diesel::result::Error::NotFound => {
+                    error::Error::Api(evops_models::ApiError::NotFound(e.to_string()))
+                }
                 _ => e.into(),
             })?;
         Ok(result_raw)
@@ -94,7 +253,7 @@ impl crate::Database {
     async fn get_tags(
         conn: &mut AsyncPgConnection,
         event_ids_raw: &Vec<Uuid>,
-    ) -> ApiResult<HashMap<Uuid, HashMap<models::Tag, Option<Vec<models::TagAlias>>>>> {
+    ) -> error::Result<HashMap<Uuid, HashMap<models::Tag, Option<Vec<models::TagAlias>>>>> {
         let result: HashMap<Uuid, HashMap<models::Tag, Option<Vec<models::TagAlias>>>> = {
             let event_tags: Vec<(Uuid, models::Tag)> = schema::events_to_tags::table
                 .filter(schema::events_to_tags::event_id.eq_any(event_ids_raw))
@@ -130,7 +289,7 @@ impl crate::Database {
     async fn get_images(
         conn: &mut AsyncPgConnection,
         event_ids_raw: &Vec<Uuid>,
-    ) -> ApiResult<HashMap<uuid::Uuid, Vec<models::EventImage>>> {
+    ) -> error::Result<HashMap<uuid::Uuid, Vec<models::EventImage>>> {
         let result = schema::event_images::table
             .filter(schema::event_images::event_id.eq_any(event_ids_raw))
             .select(models::EventImage::as_select())
@@ -141,10 +300,10 @@ impl crate::Database {
         Ok(result)
     }
 
-    async fn list_events_private(
+    pub(crate) async fn list_events_private(
         conn: &mut AsyncPgConnection,
         event_ids_raw: Vec<Uuid>,
-    ) -> ApiResult<Vec<evops_models::Event>> {
+    ) -> error::Result<Vec<evops_models::Event>> {
         if event_ids_raw.is_empty() {
             return Ok(Vec::default());
         }
@@ -232,3 +391,22 @@ impl crate::Database {
         Ok(result)
     }
 }
+
+/// Compares `(events.<column>, events.id)` with `(timestamp, id)` as a row,
+/// which unlike the equivalent `OR` lets Postgres scan the matching index
+/// from the cursor on.
+fn after_timestamp(
+    column: &str,
+    operator: &str,
+    timestamp: DateTime<Utc>,
+    id: Uuid,
+) -> EventCondition {
+    let condition = {
+        sql::<Bool>(&format!("(events.{column}, events.id) {operator} ("))
+            .bind::<Timestamptz, _>(timestamp)
+            .sql(", ")
+            .bind::<sql_types::Uuid, _>(id)
+            .sql(")")
+    };
+    Box::new(condition)
+}
@@ -0,0 +1,100 @@
+use chrono::Utc;
+use diesel::{ExpressionMethods as _, QueryDsl as _};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use url::Url;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::services::venue::VenueId;
+use crate::{error, schema};
+
+/// Where an event takes place. An event may have a venue, an online link,
+/// both or neither.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventLocation {
+    pub venue_id: Option<VenueId>,
+    /// An `http` or `https` link to join the event online.
+    pub online_url: Option<Url>,
+}
+
+impl crate::Database {
+    /// Replaces the venue and online link of the event, which also marks it as
+    /// modified.
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn set_event_location(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        location: EventLocation,
+    ) -> ApiResult<()> {
+        let location = &location;
+        self.retrying_transaction("set_event_location", move |conn| {
+            async move {
+                Self::set_event_location_unatomic(conn, event_id, user_id, location).await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn set_event_location_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        location: &EventLocation,
+    ) -> error::Result<()> {
+        if let Some(online_url) = &location.online_url
+            && !matches!(online_url.scheme(), "http" | "https")
+        {
+            return Err(error::Error::Api(ApiError::InvalidArgument({
+                "The online link must be an http or https URL.".to_owned()
+            })));
+        }
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        if user_id.into_inner() != event_model.author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't modify this event.".to_owned()
+            })));
+        }
+
+        diesel::update(schema::events::table.find(event_id.into_inner()))
+            .set((
+                schema::events::venue_id.eq(location.venue_id.map(VenueId::into_inner)),
+                schema::events::online_url.eq(location.online_url.as_ref().map(Url::as_str)),
+                schema::events::modified_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn find_event_location(
+        &self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<EventLocation> {
+        let mut conn = self.conn().await?;
+        Self::find_event_location_inner(&mut conn, event_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_event_location_inner(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+    ) -> error::Result<EventLocation> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        // Only URLs that parsed are ever stored.
+        let online_url = {
+            event_model
+                .online_url
+                .and_then(|online_url| Url::parse(&online_url).ok())
+        };
+        Ok(EventLocation {
+            venue_id: event_model.venue_id.map(VenueId::new),
+            online_url,
+        })
+    }
+}
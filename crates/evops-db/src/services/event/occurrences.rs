@@ -0,0 +1,600 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use diesel::dsl::sql;
+use diesel::sql_types::{Array, Text, Timestamp, Timestamptz};
+use diesel::upsert::excluded;
+use diesel::{BoolExpressionMethods as _, ExpressionMethods as _, QueryDsl as _, QueryResult};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use itertools::Itertools as _;
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::services::event::filter::{EventCondition, EventFilter, TimeRange};
+use crate::services::event::recurrence::{Recurrence, RecurrenceRule};
+use crate::services::event::schedule::{EventSchedule, EventTimezone};
+use crate::{error, schema};
+
+/// How wide a window [`crate::Database::list_occurrences`] takes, enough for a
+/// year-long calendar.
+const MAX_WINDOW_DAYS: i64 = 366;
+/// Daily series don't reach it within the widest window. It guards the
+/// listing against rules that would.
+const MAX_OCCURRENCES_PER_SERIES: usize = 400;
+
+/// One occurrence of an event, as listed by
+/// [`crate::Database::list_occurrences`].
+#[derive(Debug, Clone)]
+pub struct EventOccurrence {
+    /// Where the rule of the series puts the occurrence. It identifies the
+    /// occurrence even after an override has moved it. For events that don't
+    /// recur, their start.
+    pub occurs_at: DateTime<Utc>,
+    /// The event with the title and description of the occurrence.
+    pub event: evops_models::Event,
+    pub schedule: EventSchedule,
+}
+
+/// Changes to a single occurrence of a recurring event. The fields that are
+/// `None` follow the series.
+#[derive(Debug, Clone, Default)]
+pub struct OccurrenceOverride {
+    pub title: Option<evops_models::EventTitle>,
+    pub description: Option<evops_models::EventDescription>,
+    pub starts_at: Option<DateTime<Utc>>,
+    /// Defaults to the start of the occurrence plus the duration of the
+    /// series.
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+impl OccurrenceOverride {
+    const fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.starts_at.is_none()
+            && self.ends_at.is_none()
+    }
+}
+
+/// An event row with what it takes to expand its occurrences.
+struct Series {
+    id: Uuid,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    /// `starts_at` as a wall-clock time in `timezone`.
+    local_start: NaiveDateTime,
+    timezone: String,
+    rule: Option<RecurrenceRule>,
+}
+
+impl crate::Database {
+    /// Replaces the rule and exception dates of the event, which also marks it
+    /// as modified. `None` makes the event a one-off again and drops its
+    /// occurrence overrides.
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn set_event_recurrence(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        recurrence: Option<Recurrence>,
+    ) -> ApiResult<()> {
+        let recurrence = recurrence.as_ref();
+        self.retrying_transaction("set_event_recurrence", move |conn| {
+            async move {
+                Self::set_event_recurrence_unatomic(conn, event_id, user_id, recurrence).await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn set_event_recurrence_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        recurrence: Option<&Recurrence>,
+    ) -> error::Result<()> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        if user_id.into_inner() != event_model.author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't modify this event.".to_owned()
+            })));
+        }
+
+        diesel::update(schema::events::table.find(event_id.into_inner()))
+            .set((
+                schema::events::recurrence_rule
+                    .eq(recurrence.map(|recurrence| recurrence.rule.to_string())),
+                schema::events::modified_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .await?;
+        Self::refresh_last_occurs_at(conn, event_id).await?;
+        diesel::delete({
+            schema::event_exception_dates::table
+                .filter(schema::event_exception_dates::event_id.eq(event_id.into_inner()))
+        })
+        .execute(conn)
+        .await?;
+
+        let Some(recurrence) = recurrence else {
+            diesel::delete({
+                schema::event_occurrence_overrides::table.filter({
+                    schema::event_occurrence_overrides::event_id.eq(event_id.into_inner())
+                })
+            })
+            .execute(conn)
+            .await?;
+            return Ok(());
+        };
+        let records: Vec<_> = {
+            recurrence
+                .exception_dates
+                .iter()
+                .sorted_unstable()
+                .dedup()
+                .map(|&occurs_at| {
+                    (
+                        schema::event_exception_dates::event_id.eq(event_id.into_inner()),
+                        schema::event_exception_dates::occurs_at.eq(occurs_at),
+                    )
+                })
+                .collect()
+        };
+        diesel::insert_into(schema::event_exception_dates::table)
+            .values(&records)
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Recomputes when the last occurrence of the event starts at the latest
+    /// from its saved rule and schedule.
+    pub(crate) async fn refresh_last_occurs_at(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+    ) -> error::Result<()> {
+        let (local_start, rule) = {
+            schema::events::table
+                .find(event_id.into_inner())
+                .select((
+                    sql::<Timestamp>("events.starts_at AT TIME ZONE events.timezone"),
+                    schema::events::recurrence_rule,
+                ))
+                .get_result::<(NaiveDateTime, Option<String>)>(conn)
+                .await?
+        };
+        let rule = rule
+            .as_deref()
+            .map(str::parse::<RecurrenceRule>)
+            .transpose()?;
+        let last_occurs_at = rule.and_then(|rule| rule.last_start_bound(local_start));
+        diesel::update(schema::events::table.find(event_id.into_inner()))
+            .set(schema::events::last_occurs_at.eq(last_occurs_at))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn find_event_recurrence(
+        &self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<Option<Recurrence>> {
+        let mut conn = self.conn().await?;
+        Self::find_event_recurrence_inner(&mut conn, event_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_event_recurrence_inner(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+    ) -> error::Result<Option<Recurrence>> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        let Some(rule) = event_model.recurrence_rule else {
+            return Ok(None);
+        };
+        let exception_dates = {
+            schema::event_exception_dates::table
+                .filter(schema::event_exception_dates::event_id.eq(event_id.into_inner()))
+                .select(schema::event_exception_dates::occurs_at)
+                .order(schema::event_exception_dates::occurs_at.asc())
+                .load(conn)
+                .await?
+        };
+        Ok(Some(Recurrence {
+            rule: rule.parse()?,
+            exception_dates,
+        }))
+    }
+
+    /// Changes the occurrence of a recurring event that the rule puts at
+    /// `occurs_at`, leaving the rest of the series alone. An empty `form`
+    /// makes the occurrence follow the series again.
+    #[instrument(skip_all, fields(%event_id, %user_id, %occurs_at))]
+    pub async fn override_occurrence(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        occurs_at: DateTime<Utc>,
+        form: OccurrenceOverride,
+    ) -> ApiResult<()> {
+        let form = &form;
+        self.retrying_transaction("override_occurrence", move |conn| {
+            async move {
+                Self::override_occurrence_unatomic(conn, event_id, user_id, occurs_at, form).await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn override_occurrence_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        occurs_at: DateTime<Utc>,
+        form: &OccurrenceOverride,
+    ) -> error::Result<()> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        if user_id.into_inner() != event_model.author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't modify this event.".to_owned()
+            })));
+        }
+        if event_model.recurrence_rule.is_none() {
+            return Err(error::Error::Api(ApiError::InvalidArgument(format!(
+                "Event {event_id} doesn't recur.",
+            ))));
+        }
+        let (since, until) = (occurs_at, occurs_at + TimeDelta::microseconds(1));
+        let series = {
+            let condition = schema::events::id.eq(event_id.into_inner());
+            Self::load_series(conn, vec![Box::new(condition)], since, until).await?
+        };
+        if Self::occurrence_starts(conn, &series, since, until)
+            .await?
+            .is_empty()
+        {
+            return Err(error::Error::Api(ApiError::NotFound(format!(
+                "Event {event_id} has no occurrence at {occurs_at}.",
+            ))));
+        }
+        let starts_at = form.starts_at.unwrap_or(occurs_at);
+        let duration = event_model.ends_at - event_model.starts_at;
+        if form.ends_at.unwrap_or(starts_at + duration) < starts_at {
+            return Err(error::Error::Api(ApiError::InvalidArgument({
+                "An event can't end before it starts.".to_owned()
+            })));
+        }
+
+        let key = {
+            schema::event_occurrence_overrides::table
+                .filter(schema::event_occurrence_overrides::event_id.eq(event_id.into_inner()))
+                .filter(schema::event_occurrence_overrides::occurs_at.eq(occurs_at))
+        };
+        if form.is_empty() {
+            diesel::delete(key).execute(conn).await?;
+            return Ok(());
+        }
+        diesel::insert_into(schema::event_occurrence_overrides::table)
+            .values((
+                schema::event_occurrence_overrides::event_id.eq(event_id.into_inner()),
+                schema::event_occurrence_overrides::occurs_at.eq(occurs_at),
+                schema::event_occurrence_overrides::title
+                    .eq(form.title.as_ref().map(|it| it.as_ref())),
+                schema::event_occurrence_overrides::description
+                    .eq(form.description.as_ref().map(|it| it.as_ref())),
+                schema::event_occurrence_overrides::starts_at.eq(form.starts_at),
+                schema::event_occurrence_overrides::ends_at.eq(form.ends_at),
+            ))
+            .on_conflict((
+                schema::event_occurrence_overrides::event_id,
+                schema::event_occurrence_overrides::occurs_at,
+            ))
+            .do_update()
+            .set((
+                schema::event_occurrence_overrides::title
+                    .eq(excluded(schema::event_occurrence_overrides::title)),
+                schema::event_occurrence_overrides::description
+                    .eq(excluded(schema::event_occurrence_overrides::description)),
+                schema::event_occurrence_overrides::starts_at
+                    .eq(excluded(schema::event_occurrence_overrides::starts_at)),
+                schema::event_occurrence_overrides::ends_at
+                    .eq(excluded(schema::event_occurrence_overrides::ends_at)),
+            ))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Lists the occurrences of the events `filter` lets through that the
+    /// rules put in `window`, ordered by start. Events that don't recur occur
+    /// once, at their start.
+    ///
+    /// The window has to be bounded on both sides and at most a year wide. An
+    /// override that moves an occurrence doesn't move it in or out of the
+    /// window.
+    #[instrument(skip_all)]
+    pub async fn list_occurrences(
+        &self,
+        window: &TimeRange,
+        filter: &EventFilter,
+    ) -> ApiResult<Vec<EventOccurrence>> {
+        let mut conn = self.conn().await?;
+        Self::list_occurrences_inner(&mut conn, window, filter)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn list_occurrences_inner(
+        conn: &mut AsyncPgConnection,
+        window: &TimeRange,
+        filter: &EventFilter,
+    ) -> error::Result<Vec<EventOccurrence>> {
+        let (Some(since), Some(until)) = (window.since, window.until) else {
+            return Err(error::Error::Api(ApiError::InvalidArgument({
+                "Occurrences can only be listed in a bounded window.".to_owned()
+            })));
+        };
+        if until <= since {
+            return Err(error::Error::Api(ApiError::InvalidArgument({
+                "The window must end after it starts.".to_owned()
+            })));
+        }
+        if until - since > TimeDelta::days(self::MAX_WINDOW_DAYS) {
+            return Err(error::Error::Api(ApiError::InvalidArgument(format!(
+                "The window can't be wider than {} days.",
+                self::MAX_WINDOW_DAYS,
+            ))));
+        }
+
+        filter.check()?;
+        let series = Self::load_series(conn, filter.conditions(), since, until).await?;
+        let starts = Self::occurrence_starts(conn, &series, since, until).await?;
+        let recurring_ids: Vec<_> = {
+            series
+                .iter()
+                .filter(|series| series.rule.is_some())
+                .map(|series| series.id)
+                .collect()
+        };
+        let exception_dates: HashSet<(Uuid, DateTime<Utc>)> = {
+            schema::event_exception_dates::table
+                .filter(schema::event_exception_dates::event_id.eq_any(&recurring_ids))
+                .filter(schema::event_exception_dates::occurs_at.ge(since))
+                .filter(schema::event_exception_dates::occurs_at.lt(until))
+                .select((
+                    schema::event_exception_dates::event_id,
+                    schema::event_exception_dates::occurs_at,
+                ))
+                .load::<(Uuid, DateTime<Utc>)>(conn)
+                .await?
+                .into_iter()
+                .collect()
+        };
+        let overrides: HashMap<_, _> = {
+            schema::event_occurrence_overrides::table
+                .filter(schema::event_occurrence_overrides::event_id.eq_any(&recurring_ids))
+                .filter(schema::event_occurrence_overrides::occurs_at.ge(since))
+                .filter(schema::event_occurrence_overrides::occurs_at.lt(until))
+                .select((
+                    schema::event_occurrence_overrides::event_id,
+                    schema::event_occurrence_overrides::occurs_at,
+                    schema::event_occurrence_overrides::title,
+                    schema::event_occurrence_overrides::description,
+                    schema::event_occurrence_overrides::starts_at,
+                    schema::event_occurrence_overrides::ends_at,
+                ))
+                .load::<(
+                    Uuid,
+                    DateTime<Utc>,
+                    Option<String>,
+                    Option<String>,
+                    Option<DateTime<Utc>>,
+                    Option<DateTime<Utc>>,
+                )>(conn)
+                .await?
+                .into_iter()
+                .map(
+                    |(event_id, occurs_at, title, description, starts_at, ends_at)| {
+                        (
+                            (event_id, occurs_at),
+                            (title, description, starts_at, ends_at),
+                        )
+                    },
+                )
+                .collect()
+        };
+        let events: HashMap<_, _> = {
+            let event_ids = starts.iter().map(|&(index, _)| series[index].id).unique();
+            Self::list_events_private(conn, event_ids.collect())
+                .await?
+                .into_iter()
+                .map(|event| (event.id.into_inner(), event))
+                .collect()
+        };
+
+        let mut occurrences = Vec::with_capacity(starts.len());
+        for (index, occurs_at) in starts {
+            let series = &series[index];
+            if exception_dates.contains(&(series.id, occurs_at)) {
+                continue;
+            }
+            // Deleted since the series was loaded.
+            let Some(event) = events.get(&series.id) else {
+                continue;
+            };
+            let mut event = event.clone();
+            let (title, description, starts_at, ends_at) = {
+                overrides
+                    .get(&(series.id, occurs_at))
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            if let Some(title) = title {
+                event.title = unsafe { evops_models::EventTitle::new_unchecked(title) };
+            }
+            if let Some(description) = description {
+                event.description =
+                    unsafe { evops_models::EventDescription::new_unchecked(description) };
+            }
+            let starts_at = starts_at.unwrap_or(occurs_at);
+            let schedule = EventSchedule {
+                starts_at,
+                ends_at: ends_at.unwrap_or(starts_at + (series.ends_at - series.starts_at)),
+                timezone: EventTimezone::new(series.timezone.clone()),
+            };
+            occurrences.push(EventOccurrence {
+                occurs_at,
+                event,
+                schedule,
+            });
+        }
+        occurrences.sort_by_key(|occurrence| {
+            (
+                occurrence.schedule.starts_at,
+                occurrence.event.id.into_inner(),
+            )
+        });
+        Ok(occurrences)
+    }
+
+    /// Loads the events satisfying `conditions` that may occur in
+    /// `[since, until)`.
+    async fn load_series(
+        conn: &mut AsyncPgConnection,
+        conditions: Vec<EventCondition>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> error::Result<Vec<Series>> {
+        let mut query = {
+            schema::events::table
+                .select((
+                    schema::events::id,
+                    schema::events::starts_at,
+                    schema::events::ends_at,
+                    sql::<Timestamp>("events.starts_at AT TIME ZONE events.timezone"),
+                    schema::events::timezone,
+                    schema::events::recurrence_rule,
+                ))
+                .into_boxed()
+        };
+        for condition in conditions {
+            query = query.filter(condition);
+        }
+        let one_off_in_window = {
+            (schema::events::recurrence_rule.is_null())
+                .and(schema::events::starts_at.ge(since))
+                .and(schema::events::starts_at.lt(until))
+        };
+        let recurring_in_window = {
+            (schema::events::recurrence_rule.is_not_null())
+                .and(schema::events::starts_at.lt(until))
+                .and({
+                    (schema::events::last_occurs_at.is_null())
+                        .or(schema::events::last_occurs_at.ge(since))
+                })
+        };
+        query = query.filter(one_off_in_window.or(recurring_in_window));
+
+        query
+            .load::<(
+                Uuid,
+                DateTime<Utc>,
+                DateTime<Utc>,
+                NaiveDateTime,
+                String,
+                Option<String>,
+            )>(conn)
+            .await?
+            .into_iter()
+            .map(|(id, starts_at, ends_at, local_start, timezone, rule)| {
+                Ok(Series {
+                    id,
+                    starts_at,
+                    ends_at,
+                    local_start,
+                    timezone,
+                    rule: rule
+                        .as_deref()
+                        .map(str::parse::<RecurrenceRule>)
+                        .transpose()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the starts of the occurrences of `series` in `[since, until)`,
+    /// each with the index of its series.
+    async fn occurrence_starts(
+        conn: &mut AsyncPgConnection,
+        series: &[Series],
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> error::Result<Vec<(usize, DateTime<Utc>)>> {
+        // Wider than any UTC offset, so that no occurrence in the window is
+        // cut off before its time zone is applied.
+        let slack = TimeDelta::days(1);
+        let local_since = since.naive_utc() - slack;
+        let local_until = until.naive_utc() + slack;
+
+        let mut starts = Vec::new();
+        let mut local_starts = Vec::new();
+        let mut timezones = Vec::new();
+        let mut local_indices = Vec::new();
+        for (index, series) in series.iter().enumerate() {
+            let Some(rule) = &series.rule else {
+                starts.push((index, series.starts_at));
+                continue;
+            };
+            let in_window = {
+                rule.local_starts_since(series.local_start, local_since)
+                    .take_while(|&start| start < local_until)
+                    .take(self::MAX_OCCURRENCES_PER_SERIES)
+            };
+            for local_start in in_window {
+                local_starts.push(local_start);
+                timezones.push(series.timezone.clone());
+                local_indices.push(index);
+            }
+        }
+        let utc_starts = Self::local_to_utc(conn, local_starts, timezones).await?;
+        starts.extend({
+            local_indices
+                .into_iter()
+                .zip(utc_starts)
+                .filter(|&(index, start)| {
+                    let rule = series[index].rule.as_ref();
+                    let last_start = rule.and_then(RecurrenceRule::until_utc);
+                    last_start.is_none_or(|last_start| start <= last_start)
+                })
+        });
+        starts.retain(|&(_, start)| since <= start && start < until);
+        Ok(starts)
+    }
+
+    /// Resolves wall-clock times in the given time zones to instants, leaving
+    /// the times that daylight saving skips or repeats to Postgres.
+    async fn local_to_utc(
+        conn: &mut AsyncPgConnection,
+        local_times: Vec<NaiveDateTime>,
+        timezones: Vec<String>,
+    ) -> QueryResult<Vec<DateTime<Utc>>> {
+        if local_times.is_empty() {
+            return Ok(Vec::new());
+        }
+        let instants = {
+            sql::<Array<Timestamptz>>("ARRAY(SELECT t.local AT TIME ZONE t.timezone FROM unnest(")
+                .bind::<Array<Timestamp>, _>(local_times)
+                .sql(", ")
+                .bind::<Array<Text>, _>(timezones)
+                .sql(") WITH ORDINALITY AS t (local, timezone, n) ORDER BY t.n)")
+        };
+        diesel::select(instants).get_result(conn).await
+    }
+}
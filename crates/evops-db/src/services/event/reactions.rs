@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, HashMap};
+
+use diesel::dsl;
+use diesel::{ExpressionMethods as _, QueryDsl as _, QueryResult};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::{error, schema};
+
+/// The longest reaction, in characters, as `event_reactions_reaction_check`
+/// allows.
+const MAX_REACTION_CHARS: usize = 32;
+
+impl crate::Database {
+    /// Adds the user's reaction to the event, or takes it back if the user
+    /// already reacted so. A reaction is a short token without whitespace,
+    /// such as an emoji or a `:shortcode:`. Returns whether the reaction is
+    /// there now.
+    #[instrument(skip_all, fields(%event_id, %user_id, %reaction))]
+    pub async fn toggle_reaction(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        reaction: &str,
+    ) -> ApiResult<bool> {
+        self.retrying_transaction("toggle_reaction", move |conn| {
+            async move { Self::toggle_reaction_unatomic(conn, event_id, user_id, reaction).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn toggle_reaction_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        reaction: &str,
+    ) -> error::Result<bool> {
+        let char_count = reaction.chars().count();
+        if char_count == 0
+            || char_count > MAX_REACTION_CHARS
+            || reaction.chars().any(char::is_whitespace)
+        {
+            return Err(error::Error::Api(ApiError::InvalidArgument(format!(
+                "A reaction must be 1 to {MAX_REACTION_CHARS} characters without whitespace.",
+            ))));
+        }
+
+        let deleted_count = {
+            diesel::delete({
+                schema::event_reactions::table.find((
+                    event_id.into_inner(),
+                    user_id.into_inner(),
+                    reaction,
+                ))
+            })
+            .execute(conn)
+            .await?
+        };
+        if deleted_count > 0 {
+            return Ok(false);
+        }
+
+        // A concurrent toggle may have added the reaction in the meantime,
+        // which leaves it there just the same.
+        diesel::insert_into(schema::event_reactions::table)
+            .values((
+                schema::event_reactions::event_id.eq(event_id.into_inner()),
+                schema::event_reactions::user_id.eq(user_id.into_inner()),
+                schema::event_reactions::reaction.eq(reaction),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?;
+        Ok(true)
+    }
+
+    /// How many users gave each reaction to each of the events, by event ID.
+    /// Events without reactions are left out.
+    #[instrument(skip_all, fields(event_count = event_ids.len()))]
+    pub async fn find_event_reactions(
+        &self,
+        event_ids: &[evops_models::EventId],
+    ) -> ApiResult<HashMap<Uuid, BTreeMap<String, u64>>> {
+        let mut conn = self.conn().await?;
+        Self::find_event_reactions_inner(&mut conn, event_ids)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_event_reactions_inner(
+        conn: &mut AsyncPgConnection,
+        event_ids: &[evops_models::EventId],
+    ) -> error::Result<HashMap<Uuid, BTreeMap<String, u64>>> {
+        let event_ids_raw: Vec<_> = {
+            event_ids
+                .iter()
+                .copied()
+                .map(evops_models::EventId::into_inner)
+                .collect()
+        };
+        Ok(Self::reactions_of_events(conn, &event_ids_raw).await?)
+    }
+
+    /// How many users gave each reaction to each of the events. Events
+    /// without reactions are left out.
+    async fn reactions_of_events(
+        conn: &mut AsyncPgConnection,
+        event_ids: &[Uuid],
+    ) -> QueryResult<HashMap<Uuid, BTreeMap<String, u64>>> {
+        let counts: Vec<(Uuid, String, i64)> = {
+            schema::event_reactions::table
+                .filter(schema::event_reactions::event_id.eq_any(event_ids))
+                .group_by((
+                    schema::event_reactions::event_id,
+                    schema::event_reactions::reaction,
+                ))
+                .select((
+                    schema::event_reactions::event_id,
+                    schema::event_reactions::reaction,
+                    dsl::count_star(),
+                ))
+                .load(conn)
+                .await?
+        };
+        let mut reactions: HashMap<_, BTreeMap<_, _>> = HashMap::new();
+        for (event_id, reaction, count) in counts {
+            reactions
+                .entry(event_id)
+                .or_default()
+                .insert(reaction, count.unsigned_abs());
+        }
+        Ok(reactions)
+    }
+}
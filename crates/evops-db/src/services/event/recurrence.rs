@@ -0,0 +1,401 @@
+//! iCalendar recurrence rules (RFC 5545, section 3.3.10), limited to the
+//! parts that repeating events need.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike as _, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc,
+    Weekday,
+};
+
+use evops_models::ApiError;
+
+/// How an event repeats after its first occurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub rule: RecurrenceRule,
+    /// Occurrences to skip, by their [`crate::EventOccurrence::occurs_at`].
+    pub exception_dates: Vec<DateTime<Utc>>,
+}
+
+/// A rule such as `FREQ=WEEKLY;BYDAY=TU,TH;COUNT=10`.
+///
+/// The supported parts are `FREQ` (`DAILY`, `WEEKLY`, `MONTHLY` or `YEARLY`),
+/// `INTERVAL`, `COUNT` (up to 10 000), `UNTIL`, `WKST` and, in weekly rules,
+/// `BYDAY` without ordinals. Other parts are rejected rather than ignored, so that a rule never
+/// silently yields the wrong occurrences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<Until>,
+    weekdays: Vec<Weekday>,
+    week_start: Weekday,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Until {
+    /// The last moment an occurrence may start at.
+    Utc(DateTime<Utc>),
+    /// The last day an occurrence may start on, in the event's time zone.
+    Date(NaiveDate),
+}
+
+impl RecurrenceRule {
+    /// Counted rules are expanded from their first occurrence, so the count
+    /// is what bounds the work.
+    const MAX_COUNT: u32 = 10_000;
+
+    /// The starts of the occurrences of an event first starting at `first`,
+    /// as wall-clock times in the event's time zone. The first occurrence is
+    /// always `first`, even if the rule wouldn't produce it.
+    ///
+    /// Only the time zone knows when a wall-clock time is in UTC, so an
+    /// `UNTIL` in UTC isn't applied here, see [`Self::until_utc`].
+    pub(crate) fn local_starts(
+        &self,
+        first: NaiveDateTime,
+    ) -> impl Iterator<Item = NaiveDateTime> + '_ {
+        self.local_starts_from_period(first, 0)
+    }
+
+    /// [`Self::local_starts`] without the starts before `since`. Rules without
+    /// `COUNT` skip straight to the period of `since`. The others are expanded
+    /// from `first`, since the count runs from there, which is why `COUNT` is
+    /// capped.
+    pub(crate) fn local_starts_since(
+        &self,
+        first: NaiveDateTime,
+        since: NaiveDateTime,
+    ) -> impl Iterator<Item = NaiveDateTime> + '_ {
+        let period = if self.count.is_none() {
+            self.period_of(first, since)
+        } else {
+            0
+        };
+        self.local_starts_from_period(first, period)
+            .skip_while(move |&start| start < since)
+    }
+
+    fn local_starts_from_period(
+        &self,
+        first: NaiveDateTime,
+        period: u32,
+    ) -> impl Iterator<Item = NaiveDateTime> + '_ {
+        let count = self.count.map_or(usize::MAX, |count| {
+            usize::try_from(count).unwrap_or(usize::MAX)
+        });
+        let later = {
+            (period..)
+                .map_while(move |period| self.period_starts(first, period))
+                .flatten()
+                .filter(move |&start| first < start)
+        };
+        (period == 0)
+            .then_some(first)
+            .into_iter()
+            .chain(later)
+            .take_while(move |start| match self.until {
+                Some(Until::Date(until)) => start.date() <= until,
+                Some(Until::Utc(_)) | None => true,
+            })
+            .take(count)
+    }
+
+    /// The last moment an occurrence may start at, if the rule names one in
+    /// UTC.
+    pub(crate) const fn until_utc(&self) -> Option<DateTime<Utc>> {
+        match self.until {
+            Some(Until::Utc(until)) => Some(until),
+            Some(Until::Date(_)) | None => None,
+        }
+    }
+
+    /// A moment that no occurrence of an event first starting at `first`
+    /// starts after, or `None` if the rule repeats forever.
+    pub(crate) fn last_start_bound(&self, first: NaiveDateTime) -> Option<DateTime<Utc>> {
+        // No time zone is a day or more away from UTC.
+        let latest_utc = |local: NaiveDateTime| local.and_utc() + TimeDelta::days(1);
+        let last_local_start = match (self.until, self.count) {
+            (Some(Until::Utc(until)), _) => return Some(until.max(latest_utc(first))),
+            (Some(Until::Date(until)), _) => until.succ_opt()?.and_time(NaiveTime::MIN),
+            (None, Some(_)) => self.local_starts(first).last()?,
+            (None, None) => return None,
+        };
+        Some(latest_utc(last_local_start.max(first)))
+    }
+
+    /// The period that `since` falls in, counted from the one of `first`. All
+    /// the starts in earlier periods are before `since`.
+    fn period_of(&self, first: NaiveDateTime, since: NaiveDateTime) -> u32 {
+        let (first, since) = (first.date(), since.date());
+        if since <= first {
+            return 0;
+        }
+        let elapsed = match self.frequency {
+            Frequency::Daily => (since - first).num_days(),
+            Frequency::Weekly => {
+                let days_into_week = Days::new(self.days_into_week(first.weekday()));
+                let week = first.checked_sub_days(days_into_week).unwrap_or(first);
+                (since - week).num_days() / 7
+            }
+            Frequency::Monthly => self::months(since) - self::months(first),
+            Frequency::Yearly => i64::from(since.year() - first.year()),
+        };
+        u32::try_from(elapsed / i64::from(self.interval)).unwrap_or(u32::MAX)
+    }
+
+    /// The candidate starts in the `period`th day, week, month or year after
+    /// the one of `first`, in order. Returns `None` once the dates overflow.
+    fn period_starts(&self, first: NaiveDateTime, period: u32) -> Option<Vec<NaiveDateTime>> {
+        let step = period.checked_mul(self.interval)?;
+        let date = first.date();
+        let dates = match self.frequency {
+            Frequency::Daily => vec![date.checked_add_days(Days::new(step.into()))?],
+            Frequency::Weekly => {
+                let week = {
+                    date.checked_sub_days(Days::new(self.days_into_week(date.weekday())))?
+                        .checked_add_days(Days::new(u64::from(step) * 7))?
+                };
+                let mut offsets: Vec<_> = if self.weekdays.is_empty() {
+                    vec![self.days_into_week(date.weekday())]
+                } else {
+                    self.weekdays
+                        .iter()
+                        .map(|&weekday| self.days_into_week(weekday))
+                        .collect()
+                };
+                offsets.sort_unstable();
+                offsets
+                    .into_iter()
+                    .map(|offset| week.checked_add_days(Days::new(offset)))
+                    .collect::<Option<_>>()?
+            }
+            // Months without the day are skipped, as RFC 5545 requires.
+            Frequency::Monthly => {
+                let month = date.with_day(1)?.checked_add_months(Months::new(step))?;
+                month.with_day(date.day()).into_iter().collect()
+            }
+            Frequency::Yearly => {
+                let months = Months::new(step.checked_mul(12)?);
+                let year = date.with_day(1)?.checked_add_months(months)?;
+                year.with_day(date.day()).into_iter().collect()
+            }
+        };
+        Some(
+            dates
+                .into_iter()
+                .map(|date| date.and_time(first.time()))
+                .collect(),
+        )
+    }
+
+    fn days_into_week(&self, weekday: Weekday) -> u64 {
+        let days = 7 + weekday.num_days_from_monday() - self.week_start.num_days_from_monday();
+        u64::from(days % 7)
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = s.trim().to_ascii_uppercase();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(&rule);
+
+        let mut seen = Vec::new();
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut weekdays = Vec::new();
+        let mut week_start = Weekday::Mon;
+        for part in rule.split(';') {
+            let Some((name, value)) = part.split_once('=') else {
+                return Err(self::invalid(format!("{part} isn't a NAME=VALUE pair")));
+            };
+            if seen.contains(&name) {
+                return Err(self::invalid(format!("{name} is given twice")));
+            }
+            seen.push(name);
+            match name {
+                "FREQ" => frequency = Some(Frequency::from_str(value)?),
+                "INTERVAL" => interval = self::positive(name, value)?,
+                "COUNT" => {
+                    let value = self::positive(name, value)?;
+                    if value > Self::MAX_COUNT {
+                        return Err(self::invalid(format!(
+                            "COUNT must be at most {}",
+                            Self::MAX_COUNT,
+                        )));
+                    }
+                    count = Some(value);
+                }
+                "UNTIL" => until = Some(Until::from_str(value)?),
+                "BYDAY" => {
+                    weekdays = {
+                        value
+                            .split(',')
+                            .map(self::weekday)
+                            .collect::<Result<Vec<_>, _>>()?
+                    };
+                }
+                "WKST" => week_start = self::weekday(value)?,
+                _ => return Err(self::invalid(format!("{name} isn't supported"))),
+            }
+        }
+
+        let Some(frequency) = frequency else {
+            return Err(self::invalid("FREQ is missing"));
+        };
+        if count.is_some() && until.is_some() {
+            return Err(self::invalid("COUNT and UNTIL can't both be given"));
+        }
+        if !weekdays.is_empty() && frequency != Frequency::Weekly {
+            return Err(self::invalid("BYDAY is only supported in weekly rules"));
+        }
+        weekdays.sort_unstable_by_key(Weekday::num_days_from_monday);
+        weekdays.dedup();
+        Ok(Self {
+            frequency,
+            interval,
+            count,
+            until,
+            weekdays,
+            week_start,
+        })
+    }
+}
+
+/// Writes only the parts that differ from their defaults.
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={until}")?;
+        }
+        if !self.weekdays.is_empty() {
+            let weekdays: Vec<_> = self
+                .weekdays
+                .iter()
+                .map(|&it| self::weekday_code(it))
+                .collect();
+            write!(f, ";BYDAY={}", weekdays.join(","))?;
+        }
+        if self.week_start != Weekday::Mon {
+            write!(f, ";WKST={}", self::weekday_code(self.week_start))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Frequency {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DAILY" => Ok(Self::Daily),
+            "WEEKLY" => Ok(Self::Weekly),
+            "MONTHLY" => Ok(Self::Monthly),
+            "YEARLY" => Ok(Self::Yearly),
+            _ => Err(self::invalid(format!("FREQ={s} isn't supported"))),
+        }
+    }
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Daily => "DAILY",
+            Self::Weekly => "WEEKLY",
+            Self::Monthly => "MONTHLY",
+            Self::Yearly => "YEARLY",
+        })
+    }
+}
+
+impl FromStr for Until {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(date_time) = s.strip_suffix('Z') {
+            if let Ok(date_time) = NaiveDateTime::parse_from_str(date_time, "%Y%m%dT%H%M%S") {
+                return Ok(Self::Utc(date_time.and_utc()));
+            }
+        } else if let Ok(date) = NaiveDate::parse_from_str(s, "%Y%m%d") {
+            return Ok(Self::Date(date));
+        }
+        Err(self::invalid("UNTIL must be a date or a date-time in UTC"))
+    }
+}
+
+impl fmt::Display for Until {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utc(until) => write!(f, "{}", until.format("%Y%m%dT%H%M%SZ")),
+            Self::Date(until) => write!(f, "{}", until.format("%Y%m%d")),
+        }
+    }
+}
+
+/// Months since the start of year 0.
+fn months(date: NaiveDate) -> i64 {
+    i64::from(date.year()) * 12 + i64::from(date.month0())
+}
+
+fn positive(name: &str, value: &str) -> Result<u32, ApiError> {
+    match value.parse() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(self::invalid(format!("{name} must be a positive number"))),
+    }
+}
+
+fn weekday(code: &str) -> Result<Weekday, ApiError> {
+    let weekday = match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => {
+            return Err(self::invalid(format!(
+                "{code} isn't a weekday without ordinal"
+            )));
+        }
+    };
+    Ok(weekday)
+}
+
+const fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn invalid(reason: impl fmt::Display) -> ApiError {
+    ApiError::InvalidArgument(format!("Invalid recurrence rule: {reason}."))
+}
@@ -1,15 +1,16 @@
 use chrono::Utc;
 use diesel::QueryDsl as _;
-use diesel::result::{DatabaseErrorKind, OptionalExtension as _};
+use diesel::result::OptionalExtension as _;
 use diesel::{ExpressionMethods, Insertable};
 use diesel_async::scoped_futures::ScopedFutureExt as _;
-use diesel_async::{AsyncConnection as _, AsyncPgConnection, RunQueryDsl as _};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
 use tap::TryConv as _;
+use tracing::instrument;
 use uuid::Uuid;
 
 use evops_models::{ApiError, ApiResult};
 
-use crate::schema;
+use crate::{error, schema};
 
 #[derive(Insertable)]
 #[diesel(table_name = schema::event_images)]
@@ -21,31 +22,33 @@ struct NewEventImage {
 }
 
 impl crate::Database {
+    #[instrument(skip_all, fields(%event_id, %image_id, %user_id))]
     pub async fn reserve_image(
-        &mut self,
+        &self,
         event_id: evops_models::EventId,
         image_id: evops_models::EventImageId,
         user_id: evops_models::UserId,
     ) -> ApiResult<()> {
-        let event_model = Self::find_event_model(&mut self.conn, event_id).await?;
-        if user_id.into_inner() != event_model.author_id {
-            return Err(ApiError::Forbidden({
-                "You can't modify this event.".to_owned()
-            }));
-        }
-        self.conn
-            .transaction(|conn| {
-                async { unsafe { Self::reserve_image_unatomic(conn, event_id, image_id).await } }
-                    .scope_boxed()
-            })
-            .await
+        self.retrying_transaction("reserve_image", move |conn| {
+            async move { Self::reserve_image_unatomic(conn, event_id, image_id, user_id).await }
+                .scope_boxed()
+        })
+        .await
     }
 
-    async unsafe fn reserve_image_unatomic(
+    pub(crate) async fn reserve_image_unatomic(
         conn: &mut AsyncPgConnection,
         event_id: evops_models::EventId,
         image_id: evops_models::EventImageId,
-    ) -> ApiResult<()> {
+        user_id: evops_models::UserId,
+    ) -> error::Result<()> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        if user_id.into_inner() != event_model.author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't modify this event.".to_owned()
+            })));
+        }
+
         let position = {
             let current_last_position: i16 = {
                 schema::event_images::table
@@ -66,10 +69,10 @@ impl crate::Database {
                 .unwrap()
         };
         if position == max_position {
-            return Err(ApiError::AlreadyExists(format!(
+            return Err(error::Error::Api(ApiError::AlreadyExists(format!(
                 "Event {event_id} already has {} images.",
                 evops_models::EventImageIds::ITEMS_MAX,
-            )));
+            ))));
         }
 
         diesel::insert_into(schema::event_images::table)
@@ -79,13 +82,7 @@ impl crate::Database {
                 position,
             })
             .execute(conn)
-            .await
-            .map_err(|e| match e {
-                diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, info) => {
-                    ApiError::AlreadyExists(info.message().to_owned())
-                }
-                _ => e.into(),
-            })?;
+            .await?;
 
         let now = Utc::now();
         diesel::update(schema::events::table)
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::dsl::sql;
+use diesel::sql_types::{Bool, Text};
+use diesel::{ExpressionMethods as _, QueryDsl as _};
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::{error, schema};
+
+/// An IANA time zone name such as `Europe/Moscow`. Names Postgres doesn't
+/// know are rejected when the event is saved, and so are the legacy ones
+/// without a region like `EST`, which only keep a fixed offset.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EventTimezone(String);
+
+impl EventTimezone {
+    #[must_use]
+    pub const fn new(name: String) -> Self {
+        Self(name)
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+
+    /// `UTC` or an `Area/Location` name. The `posix/` and `right/` copies of
+    /// the database are left out, the latter counting leap seconds.
+    pub(crate) fn is_regional(&self) -> bool {
+        let name = self.as_ref();
+        name == "UTC"
+            || (name.contains('/') && !name.starts_with("posix/") && !name.starts_with("right/"))
+    }
+}
+
+impl AsRef<str> for EventTimezone {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// When an event takes place. Recurring events repeat at the same wall-clock
+/// time in `timezone`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventSchedule {
+    pub starts_at: DateTime<Utc>,
+    /// No earlier than `starts_at`.
+    pub ends_at: DateTime<Utc>,
+    pub timezone: EventTimezone,
+}
+
+impl crate::Database {
+    /// Moves the event to a new time, which also marks it as modified.
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn set_event_schedule(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        schedule: EventSchedule,
+    ) -> ApiResult<()> {
+        let schedule = &schedule;
+        self.retrying_transaction("set_event_schedule", move |conn| {
+            async move {
+                Self::set_event_schedule_unatomic(conn, event_id, user_id, schedule).await
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn set_event_schedule_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        schedule: &EventSchedule,
+    ) -> error::Result<()> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        if user_id.into_inner() != event_model.author_id {
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't modify this event.".to_owned()
+            })));
+        }
+        Self::check_event_timezone(conn, &schedule.timezone).await?;
+
+        diesel::update(schema::events::table.find(event_id.into_inner()))
+            .set((
+                schema::events::starts_at.eq(schedule.starts_at),
+                schema::events::ends_at.eq(schedule.ends_at),
+                schema::events::timezone.eq(schedule.timezone.as_ref()),
+                schema::events::modified_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .await?;
+        // The rule repeats the new start, so the series may end elsewhere.
+        Self::refresh_last_occurs_at(conn, event_id).await?;
+        Ok(())
+    }
+
+    /// `AT TIME ZONE` also takes abbreviations and POSIX offsets, so the name
+    /// is looked up among the ones Postgres knows instead.
+    pub(crate) async fn check_event_timezone(
+        conn: &mut AsyncPgConnection,
+        timezone: &EventTimezone,
+    ) -> error::Result<()> {
+        let known: bool = if timezone.is_regional() {
+            diesel::select({
+                sql::<Bool>("EXISTS (SELECT FROM pg_timezone_names WHERE name = ")
+                    .bind::<Text, _>(timezone.as_ref())
+                    .sql(")")
+            })
+            .get_result(conn)
+            .await?
+        } else {
+            false
+        };
+
+        if known {
+            Ok(())
+        } else {
+            Err(error::Error::Api(ApiError::InvalidArgument(format!(
+                "{} isn't an IANA time zone.",
+                timezone.as_ref(),
+            ))))
+        }
+    }
+
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn find_event_schedule(
+        &self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<EventSchedule> {
+        let mut conn = self.conn().await?;
+        Self::find_event_schedule_inner(&mut conn, event_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_event_schedule_inner(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+    ) -> error::Result<EventSchedule> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
+        Ok(EventSchedule {
+            starts_at: event_model.starts_at,
+            ends_at: event_model.ends_at,
+            timezone: EventTimezone::new(event_model.timezone),
+        })
+    }
+
+    /// The schedules of the events, e.g. of those on a listing page, by event
+    /// ID. Events that don't exist are left out.
+    #[instrument(skip_all, fields(event_count = event_ids.len()))]
+    pub async fn find_event_schedules(
+        &self,
+        event_ids: &[evops_models::EventId],
+    ) -> ApiResult<HashMap<Uuid, EventSchedule>> {
+        let mut conn = self.conn().await?;
+        Self::find_event_schedules_inner(&mut conn, event_ids)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_event_schedules_inner(
+        conn: &mut AsyncPgConnection,
+        event_ids: &[evops_models::EventId],
+    ) -> error::Result<HashMap<Uuid, EventSchedule>> {
+        let event_ids_raw: Vec<_> = {
+            event_ids
+                .iter()
+                .copied()
+                .map(evops_models::EventId::into_inner)
+                .collect()
+        };
+        let schedules = {
+            schema::events::table
+                .filter(schema::events::id.eq_any(event_ids_raw))
+                .select((
+                    schema::events::id,
+                    schema::events::starts_at,
+                    schema::events::ends_at,
+                    schema::events::timezone,
+                ))
+                .load::<(Uuid, DateTime<Utc>, DateTime<Utc>, String)>(conn)
+                .await?
+                .into_iter()
+                .map(|(event_id, starts_at, ends_at, timezone)| {
+                    let schedule = EventSchedule {
+                        starts_at,
+                        ends_at,
+                        timezone: EventTimezone::new(timezone),
+                    };
+                    (event_id, schedule)
+                })
+                .collect()
+        };
+        Ok(schedules)
+    }
+}
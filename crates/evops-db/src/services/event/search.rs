@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use diesel::dsl::sql;
+use diesel::sql_types::Text;
+use diesel::{ExpressionMethods as _, QueryDsl as _};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::ApiResult;
+
+use crate::error;
+use crate::pagination::{self, Cursor, Page, TotalCount};
+use crate::schema;
+use crate::services::{EventFilter, EventSort};
+
+/// How matches are marked up in [`EventSearchHit`]s.
+#[derive(Debug, Clone)]
+pub struct HighlightOptions {
+    pub start_marker: String,
+    pub stop_marker: String,
+    /// Maximum number of description fragments to return. Zero returns the
+    /// whole description instead.
+    pub max_fragments: u32,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        Self {
+            start_marker: "<b>".to_owned(),
+            stop_marker: "</b>".to_owned(),
+            max_fragments: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EventSearchHit {
+    pub event: evops_models::Event,
+    /// The whole title with the matching words marked.
+    pub title_highlight: String,
+    /// The fragments of the description around the matching words, with
+    /// those words marked.
+    pub description_snippet: String,
+}
+
+impl crate::Database {
+    /// Like [`Self::list_events`] with [`EventSort::Relevance`], but also
+    /// returns why each event matched. `search` takes the place of
+    /// `filter.search`.
+    #[instrument(skip_all, fields(tag_count = filter.tags.tags.len()))]
+    pub async fn search_events(
+        &self,
+        search: String,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        filter: &EventFilter,
+        highlight: &HighlightOptions,
+        count: TotalCount,
+    ) -> ApiResult<Page<EventSearchHit>> {
+        let mut conn = self.conn().await?;
+        Self::search_events_inner(&mut conn, search, cursor, limit, filter, highlight, count)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn search_events_inner(
+        conn: &mut AsyncPgConnection,
+        search: String,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        filter: &EventFilter,
+        highlight: &HighlightOptions,
+        count: TotalCount,
+    ) -> error::Result<Page<EventSearchHit>> {
+        let limit = limit.map(i64::from);
+        let sort = EventSort::Relevance;
+        let filter = EventFilter {
+            search: Some(search.clone()),
+            ..filter.clone()
+        };
+        let mut keys =
+            Self::list_event_ids_raw(conn, cursor.as_ref(), limit, &filter, sort).await?;
+        let (has_more, next_cursor) =
+            pagination::split_page(&mut keys, limit, |key| key.cursor(sort));
+        let event_ids: Vec<_> = keys.into_iter().map(|key| key.id).collect();
+        let mut highlights = Self::get_highlights(conn, &event_ids, search, highlight).await?;
+        let events = Self::list_events_private(conn, event_ids).await?;
+        let total = Self::count_events(conn, &filter, count).await?;
+
+        let hits = {
+            events
+                .into_iter()
+                .map(|event| {
+                    let (title_highlight, description_snippet) = {
+                        highlights
+                            .remove(&event.id.into_inner())
+                            .unwrap_or_default()
+                    };
+                    EventSearchHit {
+                        event,
+                        title_highlight,
+                        description_snippet,
+                    }
+                })
+                .collect()
+        };
+        Ok(Page {
+            items: hits,
+            has_more,
+            next_cursor,
+            total,
+        })
+    }
+
+    async fn get_highlights(
+        conn: &mut AsyncPgConnection,
+        event_ids_raw: &[Uuid],
+        search: String,
+        highlight: &HighlightOptions,
+    ) -> error::Result<HashMap<Uuid, (String, String)>> {
+        if event_ids_raw.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let markers = format!(
+            "StartSel={}, StopSel={}",
+            self::quote_option(&highlight.start_marker),
+            self::quote_option(&highlight.stop_marker),
+        );
+        let title_options = format!("{markers}, HighlightAll=true");
+        let description_options = match highlight.max_fragments {
+            0 => format!("{markers}, HighlightAll=true"),
+            max_fragments => format!("{markers}, MaxFragments={max_fragments}"),
+        };
+
+        // Same config as `events.search_vector`.
+        let title_highlight = {
+            sql::<Text>("ts_headline('english', events.title, websearch_to_tsquery('english', ")
+                .bind::<Text, _>(search.clone())
+                .sql("), ")
+                .bind::<Text, _>(title_options)
+                .sql(")")
+        };
+        let description_snippet = {
+            sql::<Text>(
+                "ts_headline('english', events.description, websearch_to_tsquery('english', ",
+            )
+            .bind::<Text, _>(search)
+            .sql("), ")
+            .bind::<Text, _>(description_options)
+            .sql(")")
+        };
+        let highlights = {
+            schema::events::table
+                .filter(schema::events::id.eq_any(event_ids_raw))
+                .select((schema::events::id, title_highlight, description_snippet))
+                .load::<(Uuid, String, String)>(conn)
+                .await?
+                .into_iter()
+                .map(|(event_id, title, description)| (event_id, (title, description)))
+                .collect()
+        };
+        Ok(highlights)
+    }
+}
+
+/// Quotes a `ts_headline` option value, so that markers may contain commas
+/// and spaces.
+fn quote_option(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
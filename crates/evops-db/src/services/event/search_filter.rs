@@ -0,0 +1,73 @@
+use diesel::BoolExpressionMethods as _;
+use diesel::dsl::{AsExprOf, sql};
+use diesel::expression::{BoxableExpression, SqlLiteral, UncheckedBind};
+use diesel::pg::Pg;
+use diesel::sql_types::{Bool, Float, Nullable, Text};
+
+pub(crate) type SearchRank =
+    SqlLiteral<Float, UncheckedBind<SqlLiteral<Float>, AsExprOf<Option<String>, Nullable<Text>>>>;
+
+/// The search term filter shared by every event listing.
+///
+/// `websearch_to_tsquery` understands quoted phrases, `or` and `-` for
+/// negation. The config must match the one `events.search_vector` is
+/// generated with.
+pub(crate) struct EventSearch {
+    term: Option<String>,
+}
+
+impl EventSearch {
+    pub(crate) const fn new(term: Option<String>) -> Self {
+        Self { term }
+    }
+
+    /// Returns `None` when there is nothing to search for.
+    pub(crate) fn matches<'a, QS: 'a>(
+        &self,
+    ) -> Option<Box<dyn BoxableExpression<QS, Pg, SqlType = Bool> + 'a>> {
+        let term = self.term.clone()?;
+        let fulltext_match = {
+            sql::<Bool>("events.search_vector @@ websearch_to_tsquery('english', ")
+                .bind::<Text, _>(term.clone())
+                .sql(")")
+        };
+        if !self::is_plain(&term) {
+            return Some(Box::new(fulltext_match));
+        }
+
+        // Misspelled words don't survive stemming, so plain searches also
+        // match by trigram word similarity. Operators are left out, since a
+        // negated word would match that way too.
+        let trigram_match = {
+            sql::<Bool>("events.title %> ")
+                .bind::<Text, _>(term.clone())
+                .sql(" OR events.description %> ")
+                .bind::<Text, _>(term)
+        };
+        Some(Box::new(fulltext_match.or(trigram_match)))
+    }
+
+    /// Zero for every event when there is nothing to search for.
+    pub(crate) fn fulltext_rank(&self) -> SearchRank {
+        sql::<Float>("COALESCE(ts_rank(events.search_vector, websearch_to_tsquery('english', ")
+            .bind::<Nullable<Text>, _>(self.term.clone())
+            .sql(")), 0)")
+    }
+
+    /// Ranks plain searches by trigram similarity, which tells apart the
+    /// events without a full-text match. Zero for every event otherwise.
+    pub(crate) fn trigram_rank(&self) -> SearchRank {
+        let plain_term = self.term.clone().filter(|term| self::is_plain(term));
+        sql::<Float>("COALESCE(word_similarity(")
+            .bind::<Nullable<Text>, _>(plain_term)
+            .sql(", events.title), 0)")
+    }
+}
+
+/// Whether `term` uses none of the `websearch_to_tsquery` operators.
+fn is_plain(term: &str) -> bool {
+    !term.contains('"')
+        && !term
+            .split_whitespace()
+            .any(|word| word.starts_with('-') || word.eq_ignore_ascii_case("or"))
+}
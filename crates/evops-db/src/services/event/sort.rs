@@ -0,0 +1,191 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::pagination::{Cursor, CursorValue};
+
+/// The order of an event listing. Events that tie on everything else are
+/// ordered by ID, which follows the creation order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventSort {
+    /// Best search matches first, then the events with the most matching
+    /// tags, then the newest ones. Without a search term, the same as
+    /// [`Self::TagMatches`].
+    #[default]
+    Relevance,
+    /// Events with the most matching tags first, then the newest ones.
+    TagMatches,
+    /// Newest created first.
+    Newest,
+    /// Most recently modified first.
+    RecentlyModified,
+    /// Oldest created first.
+    Oldest,
+    /// Earliest starting first, which suits upcoming events.
+    EarliestStart,
+    /// Latest starting first, which suits past events.
+    LatestStart,
+    /// Closest to the center of [`crate::EventFilter::near`] first, which
+    /// the filter is required for.
+    Nearest,
+}
+
+impl EventSort {
+    /// Cursors of one order can't be used with another, since the sort keys
+    /// differ.
+    const fn cursor_scope(self) -> &'static str {
+        match self {
+            Self::Relevance => "events:relevance",
+            Self::TagMatches => "events:tag-matches",
+            Self::Newest => "events:newest",
+            Self::RecentlyModified => "events:recently-modified",
+            Self::Oldest => "events:oldest",
+            Self::EarliestStart => "events:earliest-start",
+            Self::LatestStart => "events:latest-start",
+            Self::Nearest => "events:nearest",
+        }
+    }
+
+    pub(crate) fn check(self, filter: &crate::EventFilter) -> ApiResult<()> {
+        if self == Self::Nearest && filter.near.is_none() {
+            return Err(ApiError::InvalidArgument({
+                "Sorting by distance needs a point to measure from.".to_owned()
+            }));
+        }
+        Ok(())
+    }
+}
+
+/// The sort key of an event in [`crate::Database::list_event_ids_raw`].
+///
+/// A key read from a cursor only has the fields its order uses, the others
+/// are left at their defaults.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EventKey {
+    pub(crate) id: Uuid,
+    pub(crate) rank: f32,
+    pub(crate) similarity: f32,
+    pub(crate) tag_matches: i64,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) modified_at: DateTime<Utc>,
+    pub(crate) starts_at: DateTime<Utc>,
+    pub(crate) distance: f64,
+}
+
+impl EventKey {
+    pub(crate) fn from_cursor(cursor: &Cursor, sort: EventSort) -> ApiResult<Self> {
+        let key = match (sort, cursor.key(sort.cursor_scope())?) {
+            (
+                EventSort::Relevance,
+                &[
+                    CursorValue::Float(rank),
+                    CursorValue::Float(similarity),
+                    CursorValue::Int(tag_matches),
+                    CursorValue::Uuid(id),
+                ],
+            ) => Self {
+                id,
+                rank,
+                similarity,
+                tag_matches,
+                ..Self::default()
+            },
+            (EventSort::TagMatches, &[CursorValue::Int(tag_matches), CursorValue::Uuid(id)]) => {
+                Self {
+                    id,
+                    tag_matches,
+                    ..Self::default()
+                }
+            }
+            (
+                EventSort::Newest | EventSort::Oldest,
+                &[CursorValue::Timestamp(created_at), CursorValue::Uuid(id)],
+            ) => Self {
+                id,
+                created_at,
+                ..Self::default()
+            },
+            (
+                EventSort::RecentlyModified,
+                &[CursorValue::Timestamp(modified_at), CursorValue::Uuid(id)],
+            ) => Self {
+                id,
+                modified_at,
+                ..Self::default()
+            },
+            (
+                EventSort::EarliestStart | EventSort::LatestStart,
+                &[CursorValue::Timestamp(starts_at), CursorValue::Uuid(id)],
+            ) => Self {
+                id,
+                starts_at,
+                ..Self::default()
+            },
+            (EventSort::Nearest, &[CursorValue::Double(distance), CursorValue::Uuid(id)]) => Self {
+                id,
+                distance,
+                ..Self::default()
+            },
+            _ => return Err(Cursor::invalid()),
+        };
+        Ok(key)
+    }
+
+    pub(crate) fn cursor(&self, sort: EventSort) -> Cursor {
+        let key = match sort {
+            EventSort::Relevance => vec![
+                CursorValue::Float(self.rank),
+                CursorValue::Float(self.similarity),
+                CursorValue::Int(self.tag_matches),
+                CursorValue::Uuid(self.id),
+            ],
+            EventSort::TagMatches => vec![
+                CursorValue::Int(self.tag_matches),
+                CursorValue::Uuid(self.id),
+            ],
+            EventSort::Newest | EventSort::Oldest => vec![
+                CursorValue::Timestamp(self.created_at),
+                CursorValue::Uuid(self.id),
+            ],
+            EventSort::RecentlyModified => vec![
+                CursorValue::Timestamp(self.modified_at),
+                CursorValue::Uuid(self.id),
+            ],
+            EventSort::EarliestStart | EventSort::LatestStart => vec![
+                CursorValue::Timestamp(self.starts_at),
+                CursorValue::Uuid(self.id),
+            ],
+            EventSort::Nearest => vec![
+                CursorValue::Double(self.distance),
+                CursorValue::Uuid(self.id),
+            ],
+        };
+        Cursor::new(sort.cursor_scope(), key)
+    }
+
+    /// Returns [`Ordering::Less`] if `self` is listed before `other`.
+    pub(crate) fn cmp_in(&self, other: &Self, sort: EventSort) -> Ordering {
+        match sort {
+            EventSort::Relevance => (other.rank.total_cmp(&self.rank))
+                .then(other.similarity.total_cmp(&self.similarity))
+                .then(other.tag_matches.cmp(&self.tag_matches))
+                .then(other.id.cmp(&self.id)),
+            EventSort::TagMatches => {
+                (other.tag_matches, other.id).cmp(&(self.tag_matches, self.id))
+            }
+            EventSort::Newest => (other.created_at, other.id).cmp(&(self.created_at, self.id)),
+            EventSort::RecentlyModified => {
+                (other.modified_at, other.id).cmp(&(self.modified_at, self.id))
+            }
+            EventSort::Oldest => (self.created_at, self.id).cmp(&(other.created_at, other.id)),
+            EventSort::EarliestStart => (self.starts_at, self.id).cmp(&(other.starts_at, other.id)),
+            EventSort::LatestStart => (other.starts_at, other.id).cmp(&(self.starts_at, self.id)),
+            EventSort::Nearest => {
+                (self.distance.total_cmp(&other.distance)).then(self.id.cmp(&other.id))
+            }
+        }
+    }
+}
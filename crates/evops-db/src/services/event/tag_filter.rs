@@ -0,0 +1,94 @@
+use diesel::ExpressionMethods as _;
+use diesel::dsl::{AsExprOf, sql};
+use diesel::expression::{BoxableExpression, SqlLiteral, UncheckedBind};
+use diesel::pg::Pg;
+use diesel::sql_types::{self, Array, BigInt, Bool};
+use uuid::Uuid;
+
+pub(crate) type TagMatchCount = SqlLiteral<
+    BigInt,
+    UncheckedBind<SqlLiteral<BigInt>, AsExprOf<Vec<Uuid>, Array<sql_types::Uuid>>>,
+>;
+
+type Condition<'a, QS> = Box<dyn BoxableExpression<QS, Pg, SqlType = Bool> + 'a>;
+
+/// Which events [`TagFilter::tags`] lets through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TagMatch {
+    /// Every event, so that the tags only rank events.
+    #[default]
+    Boost,
+    /// Events with at least one of the tags.
+    Any,
+    /// Events with all of the tags.
+    All,
+}
+
+/// The tags an event listing is filtered and ranked by.
+#[derive(Debug, Clone, Default)]
+pub struct TagFilter {
+    /// Events with more of these tags rank higher. An empty list filters
+    /// nothing, whatever the `mode`.
+    pub tags: Vec<evops_models::TagId>,
+    pub mode: TagMatch,
+    /// Events with any of these tags are left out.
+    pub exclude_tags: Vec<evops_models::TagId>,
+}
+
+impl TagFilter {
+    /// The number of distinct [`Self::tags`] an event has.
+    pub(crate) fn match_count(&self) -> TagMatchCount {
+        sql::<BigInt>(
+            "(SELECT count(*) FROM events_to_tags \
+             WHERE events_to_tags.event_id = events.id AND events_to_tags.tag_id = ANY(",
+        )
+        .bind::<Array<sql_types::Uuid>, _>(self::distinct_ids(&self.tags))
+        .sql("))")
+    }
+
+    /// How many [`Self::match_count`] an event needs to be listed.
+    pub(crate) fn required_matches(&self) -> i64 {
+        let tag_count = self::distinct_ids(&self.tags).len();
+        if tag_count == 0 {
+            return 0;
+        }
+        match self.mode {
+            TagMatch::Boost => 0,
+            TagMatch::Any => 1,
+            TagMatch::All => tag_count.try_into().unwrap_or(i64::MAX),
+        }
+    }
+
+    pub(crate) fn conditions<'a, QS: 'a>(&self) -> Vec<Condition<'a, QS>> {
+        let mut conditions: Vec<Condition<'a, QS>> = Vec::new();
+        let required_matches = self.required_matches();
+        if required_matches > 0 {
+            conditions.push(Box::new(self.match_count().ge(required_matches)));
+        }
+        if !self.exclude_tags.is_empty() {
+            let excluded = {
+                sql::<Bool>(
+                    "NOT EXISTS (SELECT FROM events_to_tags \
+                     WHERE events_to_tags.event_id = events.id \
+                     AND events_to_tags.tag_id = ANY(",
+                )
+                .bind::<Array<sql_types::Uuid>, _>(self::distinct_ids(&self.exclude_tags))
+                .sql("))")
+            };
+            conditions.push(Box::new(excluded));
+        }
+        conditions
+    }
+}
+
+fn distinct_ids(tags: &[evops_models::TagId]) -> Vec<Uuid> {
+    let mut ids: Vec<_> = {
+        tags.iter()
+            .copied()
+            .map(evops_models::TagId::into_inner)
+            .collect()
+    };
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
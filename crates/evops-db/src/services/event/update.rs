@@ -1,50 +1,60 @@
+use chrono::Utc;
 use diesel::ExpressionMethods as _;
 use diesel::QueryDsl as _;
-use diesel_async::AsyncConnection as _;
 use diesel_async::AsyncPgConnection;
 use diesel_async::RunQueryDsl as _;
 use diesel_async::scoped_futures::ScopedFutureExt as _;
+use tracing::instrument;
 
 use evops_models::{ApiError, ApiResult};
 
-use crate::schema;
+use crate::{error, schema};
 
 impl crate::Database {
+    #[instrument(skip_all, fields(%event_id, %user_id))]
     pub async fn update_event(
-        &mut self,
+        &self,
         event_id: evops_models::EventId,
         user_id: evops_models::UserId,
         form: evops_models::UpdateEventForm,
     ) -> ApiResult<()> {
-        let event_model = Self::find_event_model(&mut self.conn, event_id).await?;
+        let form = &form;
+        self.retrying_transaction("update_event", move |conn| {
+            async move { Self::update_event_unatomic(conn, event_id, user_id, form).await }
+                .scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn update_event_unatomic(
+        conn: &mut AsyncPgConnection,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        form: &evops_models::UpdateEventForm,
+    ) -> error::Result<()> {
+        let event_model = Self::find_event_model(conn, event_id).await?;
         if user_id.into_inner() != event_model.author_id {
-            return Err(ApiError::Forbidden({
+            return Err(error::Error::Api(ApiError::Forbidden({
                 "You can't modify this event.".to_owned()
-            }));
+            })));
+        }
+
+        if form.description.is_some() || form.title.is_some() || form.tag_ids.is_some() {
+            Self::update_basic_fields(conn, event_id, form).await?;
+        }
+        if let Some(tag_ids) = &form.tag_ids {
+            Self::delete_tags_for_event(conn, event_id).await?;
+            Self::create_tags_for_event(conn, event_id, tag_ids).await?;
         }
-        self.conn
-            .transaction(|conn| {
-                async {
-                    if form.description.is_some() || form.title.is_some() {
-                        unsafe { Self::update_basic_fields(conn, event_id, &form) }.await?;
-                    }
-                    if let Some(tag_ids) = form.tag_ids {
-                        unsafe { Self::delete_tags_for_event(conn, event_id) }.await?;
-                        unsafe { Self::create_tags_for_event(conn, event_id, tag_ids) }.await?;
-                    }
-                    ApiResult::Ok(())
-                }
-                .scope_boxed()
-            })
-            .await?;
         Ok(())
     }
 
-    async unsafe fn update_basic_fields(
+    /// Also marks the event as modified, which edits of its tags need too.
+    async fn update_basic_fields(
         conn: &mut AsyncPgConnection,
         id: evops_models::EventId,
         form: &evops_models::UpdateEventForm,
-    ) -> ApiResult<()> {
+    ) -> error::Result<()> {
         diesel::update(schema::events::table.find(id.into_inner()))
             .set({
                 let description_eq = {
@@ -57,43 +67,39 @@ impl crate::Database {
                         .as_ref()
                         .map(|it| schema::events::title.eq(it.as_ref()))
                 };
-                (description_eq, title_eq)
+                (
+                    description_eq,
+                    title_eq,
+                    schema::events::modified_at.eq(Utc::now()),
+                )
             })
             .execute(conn)
-            .await
-            .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(e.to_string()),
-                _ => e.into(),
-            })?;
+            .await?;
         Ok(())
     }
 
-    async unsafe fn delete_tags_for_event(
+    async fn delete_tags_for_event(
         conn: &mut AsyncPgConnection,
         id: evops_models::EventId,
-    ) -> ApiResult<()> {
+    ) -> error::Result<()> {
         diesel::delete({
             schema::events_to_tags::table
                 .filter(schema::events_to_tags::event_id.eq(id.into_inner()))
         })
         .execute(conn)
-        .await
-        .map_err(|e| match e {
-            diesel::result::Error::NotFound => ApiError::NotFound(e.to_string()),
-            _ => e.into(),
-        })?;
+        .await?;
         Ok(())
     }
 
-    async unsafe fn create_tags_for_event(
+    async fn create_tags_for_event(
         conn: &mut AsyncPgConnection,
         id: evops_models::EventId,
-        new_tags: evops_models::EventTagIds,
-    ) -> ApiResult<()> {
+        new_tags: &evops_models::EventTagIds,
+    ) -> error::Result<()> {
         let records: Vec<_> = {
             new_tags
-                .into_inner()
-                .into_iter()
+                .as_ref()
+                .iter()
                 .map(|t| {
                     (
                         schema::events_to_tags::event_id.eq(id.into_inner()),
@@ -105,11 +111,7 @@ impl crate::Database {
         diesel::insert_into(schema::events_to_tags::table)
             .values(&records)
             .execute(conn)
-            .await
-            .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(e.to_string()),
-                _ => e.into(),
-            })?;
+            .await?;
         Ok(())
     }
 }
@@ -1,13 +1,13 @@
 use diesel::Insertable;
-use diesel::result::DatabaseErrorKind;
 use diesel_async::scoped_futures::ScopedFutureExt as _;
-use diesel_async::{AsyncConnection as _, AsyncPgConnection, RunQueryDsl as _};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
 use itertools::Itertools as _;
+use tracing::instrument;
 use uuid::Uuid;
 
-use evops_models::{ApiError, ApiResult};
+use evops_models::ApiResult;
 
-use crate::schema;
+use crate::{error, schema};
 
 #[derive(Insertable)]
 #[diesel(table_name = schema::tags)]
@@ -27,24 +27,24 @@ struct NewTagAlias<'a> {
 }
 
 impl crate::Database {
+    #[instrument(skip_all, fields(%owner_id))]
     pub async fn create_tag(
-        &mut self,
+        &self,
         form: evops_models::NewTagForm,
         owner_id: evops_models::UserId,
     ) -> ApiResult<evops_models::TagId> {
-        self.conn
-            .transaction(|conn| {
-                async { unsafe { Self::create_tag_unatomic(conn, form, owner_id).await } }
-                    .scope_boxed()
-            })
-            .await
+        let form = &form;
+        self.retrying_transaction("create_tag", move |conn| {
+            async move { Self::create_tag_unatomic(conn, form, owner_id).await }.scope_boxed()
+        })
+        .await
     }
 
-    async unsafe fn create_tag_unatomic(
+    pub(crate) async fn create_tag_unatomic(
         conn: &mut AsyncPgConnection,
-        form: evops_models::NewTagForm,
+        form: &evops_models::NewTagForm,
         owner_id: evops_models::UserId,
-    ) -> ApiResult<evops_models::TagId> {
+    ) -> error::Result<evops_models::TagId> {
         let id = evops_models::TagId::new(Uuid::now_v7());
 
         diesel::insert_into(schema::tags::table)
@@ -54,13 +54,7 @@ impl crate::Database {
                 owner_id: Some(owner_id.into_inner()),
             })
             .execute(conn)
-            .await
-            .map_err(|e| match e {
-                diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, info) => {
-                    ApiError::AlreadyExists(info.message().to_owned())
-                }
-                _ => e.into(),
-            })?;
+            .await?;
 
         diesel::insert_into(schema::tag_aliases::table)
             .values({
@@ -1,33 +1,44 @@
 use diesel::{ExpressionMethods as _, QueryDsl as _};
-use diesel_async::{
-    AsyncConnection as _, AsyncPgConnection, RunQueryDsl as _, scoped_futures::ScopedFutureExt as _,
-};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _, scoped_futures::ScopedFutureExt as _};
+use tracing::instrument;
 
 use evops_models::{ApiError, ApiResult};
 
-use crate::schema;
+use crate::{error, schema};
 
 impl crate::Database {
+    #[instrument(skip_all, fields(tag_id = %id, %user_id))]
     pub async fn delete_tag(
-        &mut self,
+        &self,
         id: evops_models::TagId,
         user_id: evops_models::UserId,
     ) -> ApiResult<()> {
-        let tag_model = Self::find_tag_model(&mut self.conn, id).await?;
+        self.retrying_transaction("delete_tag", move |conn| {
+            async move { Self::delete_tag_unatomic(conn, id, user_id).await }.scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn delete_tag_unatomic(
+        conn: &mut AsyncPgConnection,
+        id: evops_models::TagId,
+        user_id: evops_models::UserId,
+    ) -> error::Result<()> {
+        let tag_model = Self::find_tag_model(conn, id).await?;
         if Some(user_id.into_inner()) != tag_model.owner_id {
-            return Err(ApiError::Forbidden("You can't delete this tag.".to_owned()));
+            return Err(error::Error::Api(ApiError::Forbidden({
+                "You can't delete this tag.".to_owned()
+            })));
         }
-        self.conn
-            .transaction(|conn| {
-                async { unsafe { Self::delete_tag_unatomic(conn, id) }.await }.scope_boxed()
-            })
-            .await
+        Self::delete_tag_records(conn, id).await
     }
 
-    async unsafe fn delete_tag_unatomic(
+    /// Deletes the tag along with its aliases and event assignments, without
+    /// looking at who owns it.
+    pub(crate) async fn delete_tag_records(
         conn: &mut AsyncPgConnection,
         id: evops_models::TagId,
-    ) -> ApiResult<()> {
+    ) -> error::Result<()> {
         diesel::delete({
             schema::tag_aliases::table.filter(schema::tag_aliases::tag_id.eq(id.into_inner()))
         })
@@ -42,11 +53,7 @@ impl crate::Database {
 
         diesel::delete(schema::tags::table.find(id.into_inner()))
             .execute(conn)
-            .await
-            .map_err(|e| match e {
-                diesel::result::Error::NotFound => ApiError::NotFound(e.to_string()),
-                _ => e.into(),
-            })?;
+            .await?;
 
         Ok(())
     }
@@ -2,15 +2,28 @@ use diesel::{BelongingToDsl as _, GroupedBy as _, QueryResult};
 use diesel::{ExpressionMethods as _, QueryDsl as _, SelectableHelper as _};
 use diesel_async::AsyncPgConnection;
 use diesel_async::RunQueryDsl as _;
+use tracing::instrument;
 
 use evops_models::{ApiError, ApiResult};
 
+use crate::error;
 use crate::models;
 use crate::schema;
 
 impl crate::Database {
-    pub async fn find_tag(&mut self, id: evops_models::TagId) -> ApiResult<evops_models::Tag> {
-        let tag_model = Self::find_tag_model(&mut self.conn, id).await?;
+    #[instrument(skip_all, fields(tag_id = %id))]
+    pub async fn find_tag(&self, id: evops_models::TagId) -> ApiResult<evops_models::Tag> {
+        let mut conn = self.conn().await?;
+        Self::find_tag_inner(&mut conn, id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_tag_inner(
+        conn: &mut AsyncPgConnection,
+        id: evops_models::TagId,
+    ) -> error::Result<evops_models::Tag> {
+        let tag_model = Self::find_tag_model(conn, id).await?;
 
         let tag = evops_models::Tag {
             id,
@@ -20,7 +33,7 @@ impl crate::Database {
                     schema::tag_aliases::table
                         .filter(schema::tag_aliases::tag_id.eq(id.into_inner()))
                         .select(models::TagAlias::as_select())
-                        .load(&mut self.conn)
+                        .load(conn)
                         .await?
                         .into_iter()
                         .map(|tag_alias_model| unsafe {
@@ -37,7 +50,7 @@ impl crate::Database {
     pub(crate) async fn find_tag_model(
         conn: &mut AsyncPgConnection,
         id: evops_models::TagId,
-    ) -> ApiResult<models::Tag> {
+    ) -> error::Result<models::Tag> {
         schema::tags::table
             .find(id.into_inner())
             .select(models::Tag::as_select())
@@ -45,7 +58,7 @@ impl crate::Database {
             .await
             .map_err(|e| match e {
                 diesel::result::Error::NotFound => {
-                    ApiError::NotFound(format!("No tag with ID {id} found."))
+                    ApiError::NotFound(format!("No tag with ID {id} found.")).into()
                 }
                 _ => e.into(),
             })
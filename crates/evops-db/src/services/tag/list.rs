@@ -2,44 +2,71 @@ use diesel::{
     BelongingToDsl as _, ExpressionMethods as _, GroupedBy as _, QueryDsl as _,
     SelectableHelper as _,
 };
-use diesel_async::RunQueryDsl as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
 use uuid::Uuid;
 
 use evops_models::ApiResult;
 
+use crate::error;
 use crate::models;
+use crate::pagination::{self, Cursor, CursorValue, Page, TotalCount};
 use crate::schema;
 
 impl crate::Database {
+    #[instrument(skip_all)]
     pub async fn list_tags(
-        &mut self,
-        last_id: Option<evops_models::TagId>,
+        &self,
+        cursor: Option<Cursor>,
         limit: Option<evops_models::PgLimit>,
-    ) -> ApiResult<Vec<evops_models::Tag>> {
-        let tag_ids: Vec<Uuid> = {
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::Tag>> {
+        let mut conn = self.conn().await?;
+        Self::list_tags_inner(&mut conn, cursor, limit, count)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn list_tags_inner(
+        conn: &mut AsyncPgConnection,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> error::Result<Page<evops_models::Tag>> {
+        let limit = limit.map(i64::from);
+        let mut tag_ids: Vec<Uuid> = {
             let mut query = schema::tags::table.select(schema::tags::id).into_boxed();
-            if let Some(last_id) = last_id {
-                query = query.filter(schema::tags::id.gt(last_id.into_inner()));
+            if let Some(cursor) = cursor {
+                let &[CursorValue::Uuid(last_id)] = cursor.key(Cursor::TAGS)? else {
+                    return Err(error::Error::Api(Cursor::invalid()));
+                };
+                query = query.filter(schema::tags::id.gt(last_id));
             }
             query = query.order(schema::tags::id.asc());
-            if let Some(limit) = limit {
-                query = query.limit(limit.into());
+            if let Some(limit) = pagination::fetch_limit(limit) {
+                query = query.limit(limit);
             }
-            query.load(&mut self.conn).await?
+            query.load(conn).await?
         };
+        let (has_more, next_cursor) = pagination::split_page(&mut tag_ids, limit, |&id| {
+            Cursor::new(Cursor::TAGS, vec![CursorValue::Uuid(id)])
+        });
+        let total =
+            pagination::total(conn, schema::tags::table.select(schema::tags::id), count).await?;
 
         let tag_models: Vec<models::Tag> = {
             schema::tags::table
                 .filter(schema::tags::id.eq_any(&tag_ids))
                 .select(models::Tag::as_select())
-                .load(&mut self.conn)
+                .order(schema::tags::id.asc())
+                .load(conn)
                 .await?
         };
 
         let tag_alias_models: Vec<models::TagAlias> = {
             models::TagAlias::belonging_to(&tag_models)
                 .select(models::TagAlias::as_select())
-                .load(&mut self.conn)
+                .load(conn)
                 .await?
         };
 
@@ -70,6 +97,11 @@ impl crate::Database {
                 })
                 .collect()
         };
-        Ok(tags)
+        Ok(Page {
+            items: tags,
+            has_more,
+            next_cursor,
+            total,
+        })
     }
 }
@@ -0,0 +1,68 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+mod create;
+mod find;
+
+/// A place on Earth, in degrees.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl GeoPoint {
+    /// Mirrors the checks on `venues`.
+    pub(crate) fn check(self) -> ApiResult<()> {
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err(ApiError::InvalidArgument({
+                "The latitude must be between -90 and 90 degrees.".to_owned()
+            }));
+        }
+        if !(-180.0..=180.0).contains(&self.longitude) {
+            return Err(ApiError::InvalidArgument({
+                "The longitude must be between -180 and 180 degrees.".to_owned()
+            }));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VenueId(Uuid);
+
+impl VenueId {
+    #[must_use]
+    pub const fn new(id: Uuid) -> Self {
+        Self(id)
+    }
+
+    #[must_use]
+    pub const fn into_inner(self) -> Uuid {
+        self.0
+    }
+}
+
+impl fmt::Display for VenueId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Venue {
+    pub id: VenueId,
+    pub name: String,
+    pub address: String,
+    pub location: GeoPoint,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewVenueForm {
+    pub name: String,
+    pub address: String,
+    pub location: GeoPoint,
+}
@@ -0,0 +1,53 @@
+use diesel::Insertable;
+use diesel_async::scoped_futures::ScopedFutureExt as _;
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+use uuid::Uuid;
+
+use evops_models::ApiResult;
+
+use crate::services::venue::{NewVenueForm, VenueId};
+use crate::{error, schema};
+
+#[derive(Insertable)]
+#[diesel(table_name = schema::venues)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+struct NewVenue<'a> {
+    id: Uuid,
+    name: &'a str,
+    address: &'a str,
+    latitude: f64,
+    longitude: f64,
+}
+
+impl crate::Database {
+    #[instrument(skip_all)]
+    pub async fn create_venue(&self, form: NewVenueForm) -> ApiResult<VenueId> {
+        let form = &form;
+        self.retrying_transaction("create_venue", move |conn| {
+            async move { Self::create_venue_unatomic(conn, form).await }.scope_boxed()
+        })
+        .await
+    }
+
+    pub(crate) async fn create_venue_unatomic(
+        conn: &mut AsyncPgConnection,
+        form: &NewVenueForm,
+    ) -> error::Result<VenueId> {
+        form.location.check()?;
+        let id = VenueId::new(Uuid::now_v7());
+
+        diesel::insert_into(schema::venues::table)
+            .values(self::NewVenue {
+                id: id.into_inner(),
+                name: &form.name,
+                address: &form.address,
+                latitude: form.location.latitude,
+                longitude: form.location.longitude,
+            })
+            .execute(conn)
+            .await?;
+
+        Ok(id)
+    }
+}
@@ -0,0 +1,49 @@
+use diesel::{QueryDsl as _, SelectableHelper as _};
+use diesel_async::{AsyncPgConnection, RunQueryDsl as _};
+use tracing::instrument;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::error;
+use crate::models;
+use crate::schema;
+use crate::services::venue::{GeoPoint, Venue, VenueId};
+
+impl crate::Database {
+    #[instrument(skip_all, fields(venue_id = %id))]
+    pub async fn find_venue(&self, id: VenueId) -> ApiResult<Venue> {
+        let mut conn = self.conn().await?;
+        Self::find_venue_inner(&mut conn, id)
+            .await
+            .map_err(Into::into)
+    }
+
+    pub(crate) async fn find_venue_inner(
+        conn: &mut AsyncPgConnection,
+        id: VenueId,
+    ) -> error::Result<Venue> {
+        let venue_model: models::Venue = {
+            schema::venues::table
+                .find(id.into_inner())
+                .select(models::Venue::as_select())
+                .get_result(conn)
+                .await
+                .map_err(|e| match e {
+                    diesel::result::Error::NotFound => {
+                        let message = format!("No venue with ID {id} found.");
+                        error::Error::Api(ApiError::NotFound(message))
+                    }
+                    _ => e.into(),
+                })?
+        };
+        Ok(Venue {
+            id: VenueId::new(venue_model.id),
+            name: venue_model.name,
+            address: venue_model.address,
+            location: GeoPoint {
+                latitude: venue_model.latitude,
+                longitude: venue_model.longitude,
+            },
+        })
+    }
+}
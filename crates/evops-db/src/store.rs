@@ -0,0 +1,197 @@
+//! Backend-agnostic view of the services.
+//!
+//! [`crate::Database`] implements these traits on top of Postgres and
+//! [`MemoryStore`] implements them in process memory, so code written against
+//! the traits can be unit-tested without a database.
+//!
+//! The traits cover users, refresh tokens, tags and the events themselves.
+//! Everything else is only kept by [`crate::Database`]:
+//! - RSVPs and capacities, see [`crate::Database::find_event_attendance`];
+//! - check-ins, see [`crate::Database::issue_check_in_token`];
+//! - comments, see [`crate::Database::list_comments`];
+//! - bookmarks, see [`crate::Database::list_bookmarked_events`];
+//! - reactions, see [`crate::Database::find_event_reactions`];
+//! - venues and event locations, see [`crate::Database::find_venue`];
+//! - recurrences and occurrences, see [`crate::Database::list_occurrences`];
+//! - full-text search, see [`crate::Database::search_events`];
+//! - tag facets, see [`crate::Database::tag_facets`];
+//! - image reordering, see [`crate::Database::reorder_images`];
+//! - the admin services, see [`crate::Database::integrity_report`].
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use evops_models::ApiResult;
+
+use crate::pagination::{Cursor, Page, TotalCount};
+use crate::services::{EventFilter, EventSchedule, EventSort};
+
+pub use self::memory::MemoryStore;
+
+mod memory;
+mod postgres;
+
+pub trait UserStore: Send + Sync {
+    fn find_user(
+        &self,
+        id: evops_models::UserId,
+    ) -> impl Future<Output = ApiResult<evops_models::User>> + Send;
+
+    fn list_users(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> impl Future<Output = ApiResult<Page<evops_models::User>>> + Send;
+
+    fn create_user(
+        &self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+    ) -> impl Future<Output = ApiResult<()>> + Send;
+}
+
+pub trait AuthStore: Send + Sync {
+    fn sign_up(
+        &self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+        refresh_token_hash: &evops_models::JsonWebTokenHash,
+    ) -> impl Future<Output = ApiResult<()>> + Send;
+
+    fn get_password_hash(
+        &self,
+        login: &evops_models::UserLogin,
+    ) -> impl Future<Output = ApiResult<(evops_models::UserId, evops_models::UserPasswordHash)>> + Send;
+
+    fn insert_refresh_token(
+        &self,
+        token_hash: &evops_models::JsonWebTokenHash,
+        user_id: evops_models::UserId,
+    ) -> impl Future<Output = ApiResult<()>> + Send;
+
+    fn check_refresh_token(
+        &self,
+        token_hash: &evops_models::JsonWebTokenHash,
+    ) -> impl Future<Output = ApiResult<()>> + Send;
+
+    fn list_refresh_token_ids(
+        &self,
+        user_id: evops_models::UserId,
+    ) -> impl Future<Output = ApiResult<Vec<Uuid>>> + Send;
+
+    fn revoke_refresh_token(
+        &self,
+        user_id: evops_models::UserId,
+        token_id: Uuid,
+    ) -> impl Future<Output = ApiResult<()>> + Send;
+
+    fn revoke_all_refresh_tokens(
+        &self,
+        user_id: evops_models::UserId,
+    ) -> impl Future<Output = ApiResult<usize>> + Send;
+}
+
+pub trait EventStore: Send + Sync {
+    fn create_event(
+        &self,
+        form: evops_models::NewEventForm,
+        schedule: EventSchedule,
+        author_id: evops_models::UserId,
+    ) -> impl Future<Output = ApiResult<evops_models::EventId>> + Send;
+
+    fn find_event(
+        &self,
+        id: evops_models::EventId,
+    ) -> impl Future<Output = ApiResult<evops_models::Event>> + Send;
+
+    /// Lists the events `filter` lets through, in `sort` order.
+    ///
+    /// [`MemoryStore`] differs from [`crate::Database`] in two ways:
+    /// - It matches [`EventFilter::search`] as a case-insensitive substring of
+    ///   the title or the description instead of running a full-text search,
+    ///   so all matches rank the same and [`EventSort::Relevance`] orders
+    ///   them like [`EventSort::TagMatches`].
+    /// - Venues aren't part of the store, so it rejects [`EventFilter::near`]
+    ///   as an invalid argument.
+    fn list_events(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        filter: &EventFilter,
+        sort: EventSort,
+        count: TotalCount,
+    ) -> impl Future<Output = ApiResult<Page<evops_models::Event>>> + Send;
+
+    fn update_event(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        form: evops_models::UpdateEventForm,
+    ) -> impl Future<Output = ApiResult<()>> + Send;
+
+    fn set_event_schedule(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        schedule: EventSchedule,
+    ) -> impl Future<Output = ApiResult<()>> + Send;
+
+    fn find_event_schedule(
+        &self,
+        event_id: evops_models::EventId,
+    ) -> impl Future<Output = ApiResult<EventSchedule>> + Send;
+
+    /// The schedules of the events by event ID, leaving out the events that
+    /// don't exist.
+    fn find_event_schedules(
+        &self,
+        event_ids: &[evops_models::EventId],
+    ) -> impl Future<Output = ApiResult<HashMap<Uuid, EventSchedule>>> + Send;
+
+    /// Deletes the event and returns the IDs of its images, which are left for
+    /// the caller to remove from the image storage.
+    fn delete_event(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> impl Future<Output = ApiResult<evops_models::EventImageIds>> + Send;
+
+    fn reserve_image(
+        &self,
+        event_id: evops_models::EventId,
+        image_id: evops_models::EventImageId,
+        user_id: evops_models::UserId,
+    ) -> impl Future<Output = ApiResult<()>> + Send;
+}
+
+pub trait TagStore: Send + Sync {
+    fn create_tag(
+        &self,
+        form: evops_models::NewTagForm,
+        owner_id: evops_models::UserId,
+    ) -> impl Future<Output = ApiResult<evops_models::TagId>> + Send;
+
+    fn find_tag(
+        &self,
+        id: evops_models::TagId,
+    ) -> impl Future<Output = ApiResult<evops_models::Tag>> + Send;
+
+    fn list_tags(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> impl Future<Output = ApiResult<Page<evops_models::Tag>>> + Send;
+
+    fn delete_tag(
+        &self,
+        id: evops_models::TagId,
+        user_id: evops_models::UserId,
+    ) -> impl Future<Output = ApiResult<()>> + Send;
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::pagination::{Total, TotalCount};
+use crate::services::{EventSchedule, EventTimezone};
+
+mod auth;
+mod event;
+mod tag;
+
+/// In-process implementation of the store traits.
+///
+/// It enforces the same constraints as the database schema and reports the
+/// same [`ApiError`] variants as [`crate::Database`]. Clones share the data.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    users: BTreeMap<Uuid, UserRow>,
+    refresh_tokens: BTreeMap<Uuid, RefreshTokenRow>,
+    tags: BTreeMap<Uuid, TagRow>,
+    events: BTreeMap<Uuid, EventRow>,
+}
+
+struct UserRow {
+    login: String,
+    password_hash: String,
+    display_name: String,
+}
+
+struct RefreshTokenRow {
+    user_id: Uuid,
+    token_hash: Vec<u8>,
+}
+
+struct TagRow {
+    name: String,
+    owner_id: Option<Uuid>,
+    aliases: Vec<String>,
+}
+
+struct EventRow {
+    title: String,
+    description: String,
+    author_id: Uuid,
+    created_at: DateTime<Utc>,
+    modified_at: DateTime<Utc>,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    timezone: String,
+    tag_ids: Vec<Uuid>,
+    /// Sorted by position.
+    image_ids: Vec<Uuid>,
+}
+
+impl MemoryStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // Every operation validates before it mutates, so a panicking holder
+        // can't leave the state half-updated.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl State {
+    fn user_row(&self, id: evops_models::UserId) -> ApiResult<&UserRow> {
+        self.users
+            .get(&id.into_inner())
+            .ok_or_else(|| ApiError::NotFound(format!("No user with ID {id} found.")))
+    }
+
+    fn tag_row(&self, id: evops_models::TagId) -> ApiResult<&TagRow> {
+        self.tags
+            .get(&id.into_inner())
+            .ok_or_else(|| ApiError::NotFound(format!("No tag with ID {id} found.")))
+    }
+
+    fn event_row(&self, id: evops_models::EventId) -> ApiResult<&EventRow> {
+        self.events
+            .get(&id.into_inner())
+            .ok_or_else(|| ApiError::NotFound(format!("No event with ID {id} found.")))
+    }
+
+    fn event_schedule(&self, id: evops_models::EventId) -> ApiResult<EventSchedule> {
+        let row = self.event_row(id)?;
+        let schedule = EventSchedule {
+            starts_at: row.starts_at,
+            ends_at: row.ends_at,
+            timezone: EventTimezone::new(row.timezone.clone()),
+        };
+        Ok(schedule)
+    }
+
+    fn user(&self, id: evops_models::UserId) -> ApiResult<evops_models::User> {
+        let row = self.user_row(id)?;
+        let user = evops_models::User {
+            id,
+            login: unsafe { evops_models::UserLogin::new_unchecked(row.login.clone()) },
+            display_name: unsafe {
+                evops_models::UserDisplayName::new_unchecked(row.display_name.clone())
+            },
+        };
+        Ok(user)
+    }
+
+    fn tag(&self, id: evops_models::TagId) -> ApiResult<evops_models::Tag> {
+        let row = self.tag_row(id)?;
+        let tag = evops_models::Tag {
+            id,
+            name: unsafe { evops_models::TagName::new_unchecked(row.name.clone()) },
+            aliases: {
+                let aliases_raw = {
+                    row.aliases
+                        .iter()
+                        .map(|alias| unsafe {
+                            evops_models::TagAlias::new_unchecked(alias.clone())
+                        })
+                        .collect()
+                };
+                unsafe { evops_models::TagAliases::new_unchecked(aliases_raw) }
+            },
+        };
+        Ok(tag)
+    }
+
+    fn event(&self, id: evops_models::EventId) -> ApiResult<evops_models::Event> {
+        let row = self.event_row(id)?;
+        let event = evops_models::Event {
+            id,
+            author: self.user(evops_models::UserId::new(row.author_id))?,
+            image_ids: {
+                let image_ids_raw = {
+                    row.image_ids
+                        .iter()
+                        .copied()
+                        .map(evops_models::EventImageId::new)
+                        .collect()
+                };
+                unsafe { evops_models::EventImageIds::new_unchecked(image_ids_raw) }
+            },
+            tags: {
+                let tags_raw = {
+                    row.tag_ids
+                        .iter()
+                        .map(|&tag_id| self.tag(evops_models::TagId::new(tag_id)))
+                        .collect::<ApiResult<_>>()?
+                };
+                unsafe { evops_models::EventTags::new_unchecked(tags_raw) }
+            },
+            title: unsafe { evops_models::EventTitle::new_unchecked(row.title.clone()) },
+            description: unsafe {
+                evops_models::EventDescription::new_unchecked(row.description.clone())
+            },
+            created_at: row.created_at,
+            modified_at: row.modified_at,
+        };
+        Ok(event)
+    }
+}
+
+fn limit_to_usize(limit: Option<i64>) -> usize {
+    limit.map_or(usize::MAX, |limit| {
+        usize::try_from(limit).unwrap_or_default()
+    })
+}
+
+/// Counting is cheap here, so even estimates are exact.
+fn total(item_count: usize, count: TotalCount) -> Option<Total> {
+    let item_count = item_count.try_into().unwrap_or(u64::MAX);
+    match count {
+        TotalCount::Skip => None,
+        TotalCount::Exact => Some(Total::Exact(item_count)),
+        TotalCount::Estimated => Some(Total::Estimated(item_count)),
+    }
+}
@@ -0,0 +1,253 @@
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::pagination::{self, Cursor, CursorValue, Page, TotalCount};
+use crate::store::memory::{self, MemoryStore, RefreshTokenRow, State, UserRow};
+use crate::store::{AuthStore, UserStore};
+
+impl UserStore for MemoryStore {
+    async fn find_user(&self, id: evops_models::UserId) -> ApiResult<evops_models::User> {
+        self.state().user(id)
+    }
+
+    async fn list_users(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::User>> {
+        let state = self.state();
+        let limit = limit.map(i64::from);
+        let last_id = match &cursor {
+            Some(cursor) => {
+                let &[CursorValue::Uuid(last_id)] = cursor.key(Cursor::USERS)? else {
+                    return Err(Cursor::invalid());
+                };
+                Some(last_id)
+            }
+            None => None,
+        };
+
+        let mut user_ids: Vec<Uuid> = {
+            state
+                .users
+                .keys()
+                .copied()
+                .filter(|&id| last_id.is_none_or(|last_id| id > last_id))
+                .take(memory::limit_to_usize(pagination::fetch_limit(limit)))
+                .collect()
+        };
+        let (has_more, next_cursor) = pagination::split_page(&mut user_ids, limit, |&id| {
+            Cursor::new(Cursor::USERS, vec![CursorValue::Uuid(id)])
+        });
+        let users = {
+            user_ids
+                .into_iter()
+                .map(|id| state.user(evops_models::UserId::new(id)))
+                .collect::<ApiResult<_>>()?
+        };
+        Ok(Page {
+            items: users,
+            has_more,
+            next_cursor,
+            total: memory::total(state.users.len(), count),
+        })
+    }
+
+    async fn create_user(
+        &self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+    ) -> ApiResult<()> {
+        let mut state = self.state();
+        state.check_user_insertable(user_id, login)?;
+        state.insert_user(user_id, login, password_hash, display_name);
+        Ok(())
+    }
+}
+
+impl AuthStore for MemoryStore {
+    async fn sign_up(
+        &self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+        refresh_token_hash: &evops_models::JsonWebTokenHash,
+    ) -> ApiResult<()> {
+        let mut state = self.state();
+        state.check_user_insertable(user_id, login)?;
+        state.check_refresh_token_insertable(refresh_token_hash)?;
+        state.insert_user(user_id, login, password_hash, display_name);
+        state.insert_refresh_token(refresh_token_hash, user_id);
+        Ok(())
+    }
+
+    async fn get_password_hash(
+        &self,
+        login: &evops_models::UserLogin,
+    ) -> ApiResult<(evops_models::UserId, evops_models::UserPasswordHash)> {
+        let state = self.state();
+        state
+            .users
+            .iter()
+            .find(|(_, row)| row.login.to_lowercase() == login.as_ref().to_lowercase())
+            .map(|(&id, row)| {
+                (
+                    evops_models::UserId::new(id),
+                    evops_models::UserPasswordHash::new(row.password_hash.clone()),
+                )
+            })
+            .ok_or_else(|| ApiError::Forbidden("Wrong credentials.".to_string()))
+    }
+
+    async fn insert_refresh_token(
+        &self,
+        token_hash: &evops_models::JsonWebTokenHash,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        let mut state = self.state();
+        state.user_row(user_id)?;
+        state.check_refresh_token_insertable(token_hash)?;
+        state.insert_refresh_token(token_hash, user_id);
+        Ok(())
+    }
+
+    async fn check_refresh_token(
+        &self,
+        token_hash: &evops_models::JsonWebTokenHash,
+    ) -> ApiResult<()> {
+        let exists = {
+            self.state()
+                .refresh_tokens
+                .values()
+                .any(|row| row.token_hash == token_hash.as_ref())
+        };
+        if exists {
+            Ok(())
+        } else {
+            Err(ApiError::Auth("Invalid refresh JWT token.".to_owned()))
+        }
+    }
+
+    async fn list_refresh_token_ids(&self, user_id: evops_models::UserId) -> ApiResult<Vec<Uuid>> {
+        let state = self.state();
+        state.user_row(user_id)?;
+
+        let token_ids = {
+            state
+                .refresh_tokens
+                .iter()
+                .filter(|(_, row)| row.user_id == user_id.into_inner())
+                .map(|(&id, _)| id)
+                .collect()
+        };
+        Ok(token_ids)
+    }
+
+    async fn revoke_refresh_token(
+        &self,
+        user_id: evops_models::UserId,
+        token_id: Uuid,
+    ) -> ApiResult<()> {
+        let mut state = self.state();
+        match state.refresh_tokens.get(&token_id) {
+            Some(row) if row.user_id == user_id.into_inner() => {
+                state.refresh_tokens.remove(&token_id);
+                Ok(())
+            }
+            _ => Err(ApiError::NotFound(format!(
+                "No refresh token with ID {token_id} found for user {user_id}.",
+            ))),
+        }
+    }
+
+    async fn revoke_all_refresh_tokens(&self, user_id: evops_models::UserId) -> ApiResult<usize> {
+        let mut state = self.state();
+        state.user_row(user_id)?;
+
+        let count_before = state.refresh_tokens.len();
+        state
+            .refresh_tokens
+            .retain(|_, row| row.user_id != user_id.into_inner());
+        Ok(count_before - state.refresh_tokens.len())
+    }
+}
+
+impl State {
+    /// Mirrors the primary key and the case-insensitive unique login of
+    /// `users`.
+    fn check_user_insertable(
+        &self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+    ) -> ApiResult<()> {
+        if self.users.contains_key(&user_id.into_inner()) {
+            return Err(ApiError::AlreadyExists(format!(
+                "User with ID {user_id} already exists.",
+            )));
+        }
+        let login_lowercase = login.as_ref().to_lowercase();
+        if self
+            .users
+            .values()
+            .any(|row| row.login.to_lowercase() == login_lowercase)
+        {
+            return Err(ApiError::AlreadyExists(format!(
+                "User with login {} already exists.",
+                login.as_ref(),
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_refresh_token_insertable(
+        &self,
+        token_hash: &evops_models::JsonWebTokenHash,
+    ) -> ApiResult<()> {
+        if self
+            .refresh_tokens
+            .values()
+            .any(|row| row.token_hash == token_hash.as_ref())
+        {
+            return Err(ApiError::AlreadyExists(
+                "Refresh token already exists.".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn insert_user(
+        &mut self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+    ) {
+        self.users.insert(
+            user_id.into_inner(),
+            UserRow {
+                login: login.as_ref().to_owned(),
+                password_hash: password_hash.as_ref().to_owned(),
+                display_name: display_name.as_ref().to_owned(),
+            },
+        );
+    }
+
+    fn insert_refresh_token(
+        &mut self,
+        token_hash: &evops_models::JsonWebTokenHash,
+        user_id: evops_models::UserId,
+    ) {
+        self.refresh_tokens.insert(
+            Uuid::now_v7(),
+            RefreshTokenRow {
+                user_id: user_id.into_inner(),
+                token_hash: token_hash.as_ref().to_owned(),
+            },
+        );
+    }
+}
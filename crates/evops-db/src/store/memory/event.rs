@@ -0,0 +1,365 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{SubsecRound as _, Utc};
+use tap::TryConv as _;
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::pagination::{self, Cursor, Page, TotalCount};
+use crate::services::{EventFilter, EventKey, EventSchedule, EventSort};
+use crate::store::EventStore;
+use crate::store::memory::{self, EventRow, MemoryStore, State};
+
+impl EventStore for MemoryStore {
+    async fn create_event(
+        &self,
+        form: evops_models::NewEventForm,
+        schedule: EventSchedule,
+        author_id: evops_models::UserId,
+    ) -> ApiResult<evops_models::EventId> {
+        let mut state = self.state();
+        state.user_row(author_id)?;
+        let event_id = evops_models::EventId::new(Uuid::now_v7());
+        state.check_event_tags(event_id, form.tag_ids.as_ref())?;
+        self::check_schedule(&schedule)?;
+
+        let now = Utc::now();
+        state.events.insert(
+            event_id.into_inner(),
+            EventRow {
+                title: form.title.as_ref().to_owned(),
+                description: form.description.as_ref().to_owned(),
+                author_id: author_id.into_inner(),
+                created_at: now,
+                modified_at: now,
+                starts_at: schedule.starts_at,
+                ends_at: schedule.ends_at,
+                timezone: schedule.timezone.into_inner(),
+                tag_ids: {
+                    form.tag_ids
+                        .as_ref()
+                        .iter()
+                        .map(evops_models::TagId::into_inner)
+                        .collect()
+                },
+                image_ids: Vec::new(),
+            },
+        );
+        Ok(event_id)
+    }
+
+    async fn find_event(&self, id: evops_models::EventId) -> ApiResult<evops_models::Event> {
+        self.state().event(id)
+    }
+
+    async fn list_events(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        filter: &EventFilter,
+        sort: EventSort,
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::Event>> {
+        filter.check()?;
+        sort.check(filter)?;
+        if filter.near.is_some() {
+            return Err(ApiError::InvalidArgument({
+                "Events can't be filtered by distance without venues.".to_owned()
+            }));
+        }
+        let state = self.state();
+        let limit = limit.map(i64::from);
+        let last = {
+            cursor
+                .as_ref()
+                .map(|cursor| EventKey::from_cursor(cursor, sort))
+                .transpose()?
+        };
+        let author_ids: HashSet<_> = {
+            filter
+                .author_ids
+                .iter()
+                .copied()
+                .map(evops_models::UserId::into_inner)
+                .collect()
+        };
+        let tags: HashSet<_> = {
+            filter
+                .tags
+                .tags
+                .iter()
+                .copied()
+                .map(evops_models::TagId::into_inner)
+                .collect()
+        };
+        let exclude_tags: HashSet<_> = {
+            filter
+                .tags
+                .exclude_tags
+                .iter()
+                .copied()
+                .map(evops_models::TagId::into_inner)
+                .collect()
+        };
+        let required_matches = filter.tags.required_matches();
+        let now = Utc::now();
+        // Substring matching stands in for full-text search, so the search
+        // ranks of every event are zero.
+        let search = filter.search.as_ref().map(|search| search.to_lowercase());
+
+        let mut keys: Vec<EventKey> = {
+            state
+                .events
+                .iter()
+                .filter(|(_, row)| {
+                    search.as_ref().is_none_or(|search| {
+                        row.title.to_lowercase().contains(search)
+                            || row.description.to_lowercase().contains(search)
+                    })
+                })
+                .filter(|(_, row)| {
+                    !row.tag_ids
+                        .iter()
+                        .any(|tag_id| exclude_tags.contains(tag_id))
+                })
+                .filter(|(_, row)| author_ids.is_empty() || author_ids.contains(&row.author_id))
+                .filter(|(_, row)| {
+                    filter.created.contains(row.created_at)
+                        && filter.modified.contains(row.modified_at)
+                })
+                .filter(|(_, row)| {
+                    filter
+                        .phase
+                        .is_none_or(|phase| phase.contains(row.starts_at, row.ends_at, now))
+                })
+                .map(|(&id, row)| {
+                    let tag_matches = {
+                        row.tag_ids
+                            .iter()
+                            .filter(|tag_id| tags.contains(tag_id))
+                            .count()
+                    };
+                    // Postgres keeps microseconds, and so do cursors.
+                    EventKey {
+                        id,
+                        rank: 0.0,
+                        similarity: 0.0,
+                        tag_matches: tag_matches.try_into().unwrap_or(i64::MAX),
+                        created_at: row.created_at.trunc_subsecs(6),
+                        modified_at: row.modified_at.trunc_subsecs(6),
+                        starts_at: row.starts_at.trunc_subsecs(6),
+                        distance: 0.0,
+                    }
+                })
+                .filter(|key| key.tag_matches >= required_matches)
+                .collect()
+        };
+        let total = memory::total(keys.len(), count);
+        keys.retain(|key| {
+            last.as_ref()
+                .is_none_or(|last| key.cmp_in(last, sort).is_gt())
+        });
+        keys.sort_unstable_by(|a, b| a.cmp_in(b, sort));
+
+        let (has_more, next_cursor) =
+            pagination::split_page(&mut keys, limit, |key| key.cursor(sort));
+        let events = {
+            keys.iter()
+                .map(|key| state.event(evops_models::EventId::new(key.id)))
+                .collect::<ApiResult<_>>()?
+        };
+        Ok(Page {
+            items: events,
+            has_more,
+            next_cursor,
+            total,
+        })
+    }
+
+    async fn update_event(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        form: evops_models::UpdateEventForm,
+    ) -> ApiResult<()> {
+        let mut state = self.state();
+        if user_id.into_inner() != state.event_row(event_id)?.author_id {
+            return Err(ApiError::Forbidden({
+                "You can't modify this event.".to_owned()
+            }));
+        }
+        if let Some(tag_ids) = &form.tag_ids {
+            state.check_event_tags(event_id, tag_ids.as_ref())?;
+        }
+
+        let Some(row) = state.events.get_mut(&event_id.into_inner()) else {
+            unreachable!();
+        };
+        if form.title.is_some() || form.description.is_some() || form.tag_ids.is_some() {
+            row.modified_at = Utc::now();
+        }
+        if let Some(title) = form.title {
+            row.title = title.as_ref().to_owned();
+        }
+        if let Some(description) = form.description {
+            row.description = description.as_ref().to_owned();
+        }
+        if let Some(tag_ids) = form.tag_ids {
+            row.tag_ids = tag_ids
+                .as_ref()
+                .iter()
+                .map(evops_models::TagId::into_inner)
+                .collect();
+        }
+        Ok(())
+    }
+
+    async fn set_event_schedule(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        schedule: EventSchedule,
+    ) -> ApiResult<()> {
+        let mut state = self.state();
+        if user_id.into_inner() != state.event_row(event_id)?.author_id {
+            return Err(ApiError::Forbidden({
+                "You can't modify this event.".to_owned()
+            }));
+        }
+        self::check_schedule(&schedule)?;
+
+        let Some(row) = state.events.get_mut(&event_id.into_inner()) else {
+            unreachable!();
+        };
+        row.starts_at = schedule.starts_at;
+        row.ends_at = schedule.ends_at;
+        row.timezone = schedule.timezone.into_inner();
+        row.modified_at = Utc::now();
+        Ok(())
+    }
+
+    async fn find_event_schedule(
+        &self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<EventSchedule> {
+        self.state().event_schedule(event_id)
+    }
+
+    async fn find_event_schedules(
+        &self,
+        event_ids: &[evops_models::EventId],
+    ) -> ApiResult<HashMap<Uuid, EventSchedule>> {
+        let state = self.state();
+        let schedules = {
+            event_ids
+                .iter()
+                .filter_map(|&event_id| {
+                    let schedule = state.event_schedule(event_id).ok()?;
+                    Some((event_id.into_inner(), schedule))
+                })
+                .collect()
+        };
+        Ok(schedules)
+    }
+
+    async fn delete_event(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<evops_models::EventImageIds> {
+        let mut state = self.state();
+        let event = state.event(event_id)?;
+        if user_id != event.author.id {
+            return Err(ApiError::Forbidden({
+                "You can't delete this event.".to_owned()
+            }));
+        }
+
+        state.events.remove(&event_id.into_inner());
+        Ok(event.image_ids)
+    }
+
+    async fn reserve_image(
+        &self,
+        event_id: evops_models::EventId,
+        image_id: evops_models::EventImageId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        let mut state = self.state();
+        let row = state.event_row(event_id)?;
+        if user_id.into_inner() != row.author_id {
+            return Err(ApiError::Forbidden({
+                "You can't modify this event.".to_owned()
+            }));
+        }
+        let position = row.image_ids.len() + 1;
+        if Some(position)
+            == evops_models::EventImageIds::ITEMS_MAX
+                .try_conv::<usize>()
+                .ok()
+        {
+            return Err(ApiError::AlreadyExists(format!(
+                "Event {event_id} already has {} images.",
+                evops_models::EventImageIds::ITEMS_MAX,
+            )));
+        }
+        if state
+            .events
+            .values()
+            .any(|row| row.image_ids.contains(&image_id.into_inner()))
+        {
+            return Err(ApiError::AlreadyExists(format!(
+                "Image with ID {} already exists.",
+                image_id.into_inner(),
+            )));
+        }
+
+        let Some(row) = state.events.get_mut(&event_id.into_inner()) else {
+            unreachable!();
+        };
+        row.image_ids.push(image_id.into_inner());
+        row.modified_at = Utc::now();
+        Ok(())
+    }
+}
+
+impl State {
+    /// Mirrors the foreign key to `tags` and the primary key of
+    /// `events_to_tags`.
+    fn check_event_tags(
+        &self,
+        event_id: evops_models::EventId,
+        tag_ids: &[evops_models::TagId],
+    ) -> ApiResult<()> {
+        let mut seen = HashSet::new();
+        for &tag_id in tag_ids {
+            self.tag_row(tag_id)?;
+            if !seen.insert(tag_id.into_inner()) {
+                return Err(ApiError::AlreadyExists(format!(
+                    "Event {event_id} already has tag {tag_id}.",
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Mirrors `events_ends_at_check`. Time zones are taken as they are, there is
+/// no IANA database to check them against here.
+/// Without the time zone database at hand, only names that can't be IANA ones
+/// are rejected.
+fn check_schedule(schedule: &EventSchedule) -> ApiResult<()> {
+    if schedule.ends_at < schedule.starts_at {
+        return Err(ApiError::InvalidArgument({
+            "An event can't end before it starts.".to_owned()
+        }));
+    }
+    if !schedule.timezone.is_regional() {
+        return Err(ApiError::InvalidArgument(format!(
+            "{} isn't an IANA time zone.",
+            schedule.timezone.as_ref(),
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::pagination::{self, Cursor, CursorValue, Page, TotalCount};
+use crate::store::TagStore;
+use crate::store::memory::{self, MemoryStore, TagRow};
+
+impl TagStore for MemoryStore {
+    async fn create_tag(
+        &self,
+        form: evops_models::NewTagForm,
+        owner_id: evops_models::UserId,
+    ) -> ApiResult<evops_models::TagId> {
+        let mut state = self.state();
+        state.user_row(owner_id)?;
+        if state
+            .tags
+            .values()
+            .any(|row| row.name == form.name.as_ref())
+        {
+            return Err(ApiError::AlreadyExists(format!(
+                "Tag with name {} already exists.",
+                form.name.as_ref(),
+            )));
+        }
+        let aliases: Vec<String> = {
+            form.aliases
+                .as_ref()
+                .iter()
+                .map(|alias| alias.as_ref().to_owned())
+                .collect()
+        };
+        let id = evops_models::TagId::new(Uuid::now_v7());
+        let mut seen = HashSet::new();
+        if let Some(alias) = aliases.iter().find(|alias| !seen.insert(*alias)) {
+            return Err(ApiError::AlreadyExists(format!(
+                "Tag {id} already has alias {alias}.",
+            )));
+        }
+
+        state.tags.insert(
+            id.into_inner(),
+            TagRow {
+                name: form.name.as_ref().to_owned(),
+                owner_id: Some(owner_id.into_inner()),
+                aliases,
+            },
+        );
+        Ok(id)
+    }
+
+    async fn find_tag(&self, id: evops_models::TagId) -> ApiResult<evops_models::Tag> {
+        self.state().tag(id)
+    }
+
+    async fn list_tags(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::Tag>> {
+        let state = self.state();
+        let limit = limit.map(i64::from);
+        let last_id = match &cursor {
+            Some(cursor) => {
+                let &[CursorValue::Uuid(last_id)] = cursor.key(Cursor::TAGS)? else {
+                    return Err(Cursor::invalid());
+                };
+                Some(last_id)
+            }
+            None => None,
+        };
+
+        let mut tag_ids: Vec<Uuid> = {
+            state
+                .tags
+                .keys()
+                .copied()
+                .filter(|&id| last_id.is_none_or(|last_id| id > last_id))
+                .take(memory::limit_to_usize(pagination::fetch_limit(limit)))
+                .collect()
+        };
+        let (has_more, next_cursor) = pagination::split_page(&mut tag_ids, limit, |&id| {
+            Cursor::new(Cursor::TAGS, vec![CursorValue::Uuid(id)])
+        });
+        let tags = {
+            tag_ids
+                .into_iter()
+                .map(|id| state.tag(evops_models::TagId::new(id)))
+                .collect::<ApiResult<_>>()?
+        };
+        Ok(Page {
+            items: tags,
+            has_more,
+            next_cursor,
+            total: memory::total(state.tags.len(), count),
+        })
+    }
+
+    async fn delete_tag(
+        &self,
+        id: evops_models::TagId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        let mut state = self.state();
+        if Some(user_id.into_inner()) != state.tag_row(id)?.owner_id {
+            return Err(ApiError::Forbidden("You can't delete this tag.".to_owned()));
+        }
+
+        for event in state.events.values_mut() {
+            event.tag_ids.retain(|&tag_id| tag_id != id.into_inner());
+        }
+        state.tags.remove(&id.into_inner());
+        Ok(())
+    }
+}
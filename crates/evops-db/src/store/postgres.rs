@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use evops_models::ApiResult;
+
+use crate::Database;
+use crate::pagination::{Cursor, Page, TotalCount};
+use crate::services::{EventFilter, EventSchedule, EventSort};
+use crate::store::{AuthStore, EventStore, TagStore, UserStore};
+
+impl UserStore for Database {
+    async fn find_user(&self, id: evops_models::UserId) -> ApiResult<evops_models::User> {
+        Self::find_user(self, id).await
+    }
+
+    async fn list_users(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::User>> {
+        Self::list_users(self, cursor, limit, count).await
+    }
+
+    async fn create_user(
+        &self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+    ) -> ApiResult<()> {
+        Self::create_user(self, user_id, login, password_hash, display_name).await
+    }
+}
+
+impl AuthStore for Database {
+    async fn sign_up(
+        &self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+        refresh_token_hash: &evops_models::JsonWebTokenHash,
+    ) -> ApiResult<()> {
+        Self::sign_up(
+            self,
+            user_id,
+            login,
+            password_hash,
+            display_name,
+            refresh_token_hash,
+        )
+        .await
+    }
+
+    async fn get_password_hash(
+        &self,
+        login: &evops_models::UserLogin,
+    ) -> ApiResult<(evops_models::UserId, evops_models::UserPasswordHash)> {
+        Self::get_password_hash(self, login).await
+    }
+
+    async fn insert_refresh_token(
+        &self,
+        token_hash: &evops_models::JsonWebTokenHash,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        Self::insert_refresh_token(self, token_hash, user_id).await
+    }
+
+    async fn check_refresh_token(
+        &self,
+        token_hash: &evops_models::JsonWebTokenHash,
+    ) -> ApiResult<()> {
+        Self::check_refresh_token(self, token_hash).await
+    }
+
+    async fn list_refresh_token_ids(&self, user_id: evops_models::UserId) -> ApiResult<Vec<Uuid>> {
+        Self::list_refresh_token_ids(self, user_id).await
+    }
+
+    async fn revoke_refresh_token(
+        &self,
+        user_id: evops_models::UserId,
+        token_id: Uuid,
+    ) -> ApiResult<()> {
+        Self::revoke_refresh_token(self, user_id, token_id).await
+    }
+
+    async fn revoke_all_refresh_tokens(&self, user_id: evops_models::UserId) -> ApiResult<usize> {
+        Self::revoke_all_refresh_tokens(self, user_id).await
+    }
+}
+
+impl EventStore for Database {
+    async fn create_event(
+        &self,
+        form: evops_models::NewEventForm,
+        schedule: EventSchedule,
+        author_id: evops_models::UserId,
+    ) -> ApiResult<evops_models::EventId> {
+        Self::create_event(self, form, schedule, author_id).await
+    }
+
+    async fn find_event(&self, id: evops_models::EventId) -> ApiResult<evops_models::Event> {
+        Self::find_event(self, id).await
+    }
+
+    async fn list_events(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        filter: &EventFilter,
+        sort: EventSort,
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::Event>> {
+        Self::list_events(self, cursor, limit, filter, sort, count).await
+    }
+
+    async fn update_event(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        form: evops_models::UpdateEventForm,
+    ) -> ApiResult<()> {
+        Self::update_event(self, event_id, user_id, form).await
+    }
+
+    async fn set_event_schedule(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        schedule: EventSchedule,
+    ) -> ApiResult<()> {
+        Self::set_event_schedule(self, event_id, user_id, schedule).await
+    }
+
+    async fn find_event_schedule(
+        &self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<EventSchedule> {
+        Self::find_event_schedule(self, event_id).await
+    }
+
+    async fn find_event_schedules(
+        &self,
+        event_ids: &[evops_models::EventId],
+    ) -> ApiResult<HashMap<Uuid, EventSchedule>> {
+        Self::find_event_schedules(self, event_ids).await
+    }
+
+    async fn delete_event(
+        &self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<evops_models::EventImageIds> {
+        Self::delete_event(self, event_id, user_id).await
+    }
+
+    async fn reserve_image(
+        &self,
+        event_id: evops_models::EventId,
+        image_id: evops_models::EventImageId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        Self::reserve_image(self, event_id, image_id, user_id).await
+    }
+}
+
+impl TagStore for Database {
+    async fn create_tag(
+        &self,
+        form: evops_models::NewTagForm,
+        owner_id: evops_models::UserId,
+    ) -> ApiResult<evops_models::TagId> {
+        Self::create_tag(self, form, owner_id).await
+    }
+
+    async fn find_tag(&self, id: evops_models::TagId) -> ApiResult<evops_models::Tag> {
+        Self::find_tag(self, id).await
+    }
+
+    async fn list_tags(
+        &self,
+        cursor: Option<Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: TotalCount,
+    ) -> ApiResult<Page<evops_models::Tag>> {
+        Self::list_tags(self, cursor, limit, count).await
+    }
+
+    async fn delete_tag(
+        &self,
+        id: evops_models::TagId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> {
+        Self::delete_tag(self, id, user_id).await
+    }
+}
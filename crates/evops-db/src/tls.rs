@@ -0,0 +1,53 @@
+use std::fs;
+
+use diesel::ConnectionResult;
+use diesel::result::ConnectionError;
+use diesel_async::AsyncPgConnection;
+use diesel_async::pooled_connection::ManagerConfig;
+use eyre::Context as _;
+use native_tls::{Certificate, TlsConnector};
+use postgres_native_tls::MakeTlsConnector;
+
+use crate::config::{TlsConfig, TlsMode};
+
+pub(crate) fn manager_config(tls: &TlsConfig) -> eyre::Result<ManagerConfig<AsyncPgConnection>> {
+    let mut manager_config = ManagerConfig::default();
+    if tls.mode == TlsMode::Disable {
+        return Ok(manager_config);
+    }
+
+    let connector = self::build_connector(tls)?;
+    manager_config.custom_setup = Box::new(move |url| {
+        let url = url.to_owned();
+        let connector = connector.clone();
+        Box::pin(async move { self::establish(&url, connector).await })
+    });
+    Ok(manager_config)
+}
+
+fn build_connector(tls: &TlsConfig) -> eyre::Result<TlsConnector> {
+    let mut builder = TlsConnector::builder();
+    if let Some(path) = &tls.root_cert {
+        let pem = fs::read(path).wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        builder.add_root_certificate({
+            Certificate::from_pem(&pem).wrap_err("failed to parse the TLS root certificate")?
+        });
+    }
+    if tls.mode == TlsMode::Require {
+        builder
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true);
+    }
+    builder
+        .build()
+        .wrap_err("failed to build the TLS connector")
+}
+
+async fn establish(url: &str, connector: TlsConnector) -> ConnectionResult<AsyncPgConnection> {
+    let (client, connection) = {
+        tokio_postgres::connect(url, MakeTlsConnector::new(connector))
+            .await
+            .map_err(|e| ConnectionError::BadConnection(e.to_string()))?
+    };
+    AsyncPgConnection::try_from_client_and_connection(client, connection).await
+}
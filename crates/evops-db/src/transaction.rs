@@ -0,0 +1,555 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use diesel_async::pooled_connection::bb8::PooledConnection;
+use diesel_async::scoped_futures::ScopedBoxFuture;
+use diesel_async::{AnsiTransactionManager, AsyncPgConnection, TransactionManager as _};
+use tracing::{instrument, warn};
+use uuid::Uuid;
+
+use evops_models::{ApiError, ApiResult};
+
+use crate::error;
+
+/// An open transaction offering the service methods of [`crate::Database`],
+/// so that several of them can be committed or rolled back together.
+pub struct Transaction {
+    conn: PooledConnection<'static, AsyncPgConnection>,
+    /// Whether a statement failed in a way that retrying the whole
+    /// transaction might fix.
+    transient_failure: bool,
+}
+
+impl crate::Database {
+    /// Runs `callback` in a transaction that is committed if it returns `Ok`
+    /// and rolled back otherwise.
+    ///
+    /// The callback is retried from scratch after serialization failures,
+    /// deadlocks and connections lost before the commit, so it should not
+    /// have side effects outside the transaction.
+    #[instrument(skip_all)]
+    pub async fn transaction<'a, R, F>(&self, callback: F) -> ApiResult<R>
+    where
+        F: for<'r> FnOnce(&'r mut Transaction) -> ScopedBoxFuture<'a, 'r, ApiResult<R>>
+            + Clone
+            + Send
+            + 'a,
+        R: Send + 'a,
+    {
+        let mut attempt = 1;
+        loop {
+            let mut tx = Transaction {
+                conn: self.owned_conn().await?,
+                transient_failure: false,
+            };
+            match tx.run(callback.clone()).await {
+                Err(e) if tx.transient_failure && attempt < self.config.retry.max_attempts => {
+                    let backoff = self.config.retry.backoff(attempt);
+                    metrics::counter!(
+                        crate::instrumentation::TRANSACTION_RETRIES_TOTAL,
+                        "operation" => "transaction",
+                    )
+                    .increment(1);
+                    warn!(
+                        attempt,
+                        ?backoff,
+                        error = %e,
+                        "retrying transaction after a transient failure",
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Transaction {
+    async fn run<'a, R, F>(&mut self, callback: F) -> ApiResult<R>
+    where
+        F: for<'r> FnOnce(&'r mut Self) -> ScopedBoxFuture<'a, 'r, ApiResult<R>> + Send + 'a,
+        R: Send + 'a,
+    {
+        let begun = AnsiTransactionManager::begin_transaction(&mut *self.conn).await;
+        self.track(begun)?;
+        match callback(self).await {
+            Ok(value) => {
+                // Running the callback again after a commit that went
+                // through would apply its writes twice, so a connection lost
+                // while committing isn't retried.
+                AnsiTransactionManager::commit_transaction(&mut *self.conn)
+                    .await
+                    .map_err(|e| {
+                        let e = error::Error::from(e);
+                        self.transient_failure |= e.is_transient();
+                        ApiError::from(e)
+                    })?;
+                Ok(value)
+            }
+            Err(e) => {
+                // A connection left inside a transaction is discarded by the
+                // pool, so a failed rollback only needs to be reported.
+                if let Err(rollback_error) =
+                    AnsiTransactionManager::rollback_transaction(&mut *self.conn).await
+                {
+                    warn!(error = %rollback_error, "failed to roll back a transaction");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Converts the error of a statement run in this transaction, remembering
+    /// whether it is worth retrying the transaction.
+    fn track<T, E>(&mut self, result: Result<T, E>) -> ApiResult<T>
+    where
+        E: Into<error::Error>,
+    {
+        result.map_err(|e| {
+            let e: error::Error = e.into();
+            self.transient_failure |= e.is_transient() || e.is_connection_lost();
+            e.into()
+        })
+    }
+}
+
+/// Defines [`Transaction`] methods that call the connection-level function of
+/// [`crate::Database`] named after the `=` with the transaction's connection
+/// and the given arguments.
+macro_rules! transaction_methods {
+    ($(
+        $(#[$attr:meta])*
+        pub async fn $name:ident(&mut self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty
+            = $inner:ident($($call:expr),* $(,)?);
+    )*) => {
+        impl Transaction {
+            $(
+                $(#[$attr])*
+                pub async fn $name(&mut self $(, $arg: $ty)*) -> $ret {
+                    let result = crate::Database::$inner(&mut self.conn $(, $call)*).await;
+                    self.track(result)
+                }
+            )*
+        }
+    };
+}
+
+transaction_methods! {
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn sign_up(
+        &mut self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+        refresh_token_hash: &evops_models::JsonWebTokenHash,
+    ) -> ApiResult<()> = sign_up_unatomic(
+        user_id,
+        login,
+        password_hash,
+        display_name,
+        refresh_token_hash,
+    );
+
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn create_user(
+        &mut self,
+        user_id: evops_models::UserId,
+        login: &evops_models::UserLogin,
+        password_hash: &evops_models::UserPasswordHash,
+        display_name: &evops_models::UserDisplayName,
+    ) -> ApiResult<()> = insert_user(user_id, login, password_hash, display_name);
+
+    #[instrument(skip_all, fields(user_id = %id))]
+    pub async fn find_user(&mut self, id: evops_models::UserId) -> ApiResult<evops_models::User>
+        = find_user_inner(id);
+
+    #[instrument(skip_all)]
+    pub async fn list_users(
+        &mut self,
+        cursor: Option<crate::Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: crate::TotalCount,
+    ) -> ApiResult<crate::Page<evops_models::User>> = list_users_inner(cursor, limit, count);
+
+    #[instrument(skip_all)]
+    pub async fn get_password_hash(
+        &mut self,
+        login: &evops_models::UserLogin,
+    ) -> ApiResult<(evops_models::UserId, evops_models::UserPasswordHash)>
+        = get_password_hash_inner(login);
+
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn insert_refresh_token(
+        &mut self,
+        token_hash: &evops_models::JsonWebTokenHash,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> = insert_refresh_token_inner(token_hash, user_id);
+
+    #[instrument(skip_all)]
+    pub async fn check_refresh_token(
+        &mut self,
+        token_hash: &evops_models::JsonWebTokenHash,
+    ) -> ApiResult<()> = check_refresh_token_inner(token_hash);
+
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn list_refresh_token_ids(
+        &mut self,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<Vec<Uuid>> = list_refresh_token_ids_inner(user_id);
+
+    #[instrument(skip_all, fields(%user_id, %token_id))]
+    pub async fn revoke_refresh_token(
+        &mut self,
+        user_id: evops_models::UserId,
+        token_id: Uuid,
+    ) -> ApiResult<()> = revoke_refresh_token_unatomic(user_id, token_id);
+
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn revoke_all_refresh_tokens(
+        &mut self,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<usize> = revoke_all_refresh_tokens_unatomic(user_id);
+
+    #[instrument(skip_all, fields(%author_id))]
+    pub async fn create_event(
+        &mut self,
+        form: evops_models::NewEventForm,
+        schedule: crate::EventSchedule,
+        author_id: evops_models::UserId,
+    ) -> ApiResult<evops_models::EventId> = create_event_unatomic(&form, &schedule, author_id);
+
+    #[instrument(skip_all, fields(event_id = %id))]
+    pub async fn find_event(&mut self, id: evops_models::EventId) -> ApiResult<evops_models::Event>
+        = find_event_inner(id);
+
+    #[instrument(skip_all, fields(tag_count = filter.tags.tags.len(), ?sort))]
+    pub async fn list_events(
+        &mut self,
+        cursor: Option<crate::Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        filter: &crate::EventFilter,
+        sort: crate::EventSort,
+        count: crate::TotalCount,
+    ) -> ApiResult<crate::Page<evops_models::Event>>
+        = list_events_inner(cursor, limit, filter, sort, count);
+
+    #[instrument(skip_all, fields(tag_count = filter.tags.tags.len()))]
+    pub async fn search_events(
+        &mut self,
+        search: String,
+        cursor: Option<crate::Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        filter: &crate::EventFilter,
+        highlight: &crate::HighlightOptions,
+        count: crate::TotalCount,
+    ) -> ApiResult<crate::Page<crate::EventSearchHit>>
+        = search_events_inner(search, cursor, limit, filter, highlight, count);
+
+    #[instrument(skip_all)]
+    pub async fn tag_facets(
+        &mut self,
+        filter: &crate::EventFilter,
+        limit: Option<evops_models::PgLimit>,
+    ) -> ApiResult<Vec<crate::TagFacet>> = tag_facets_inner(filter, limit);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn update_event(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        form: evops_models::UpdateEventForm,
+    ) -> ApiResult<()> = update_event_unatomic(event_id, user_id, &form);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn set_event_schedule(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        schedule: crate::EventSchedule,
+    ) -> ApiResult<()> = set_event_schedule_unatomic(event_id, user_id, &schedule);
+
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn find_event_schedule(
+        &mut self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<crate::EventSchedule> = find_event_schedule_inner(event_id);
+
+    #[instrument(skip_all, fields(event_count = event_ids.len()))]
+    pub async fn find_event_schedules(
+        &mut self,
+        event_ids: &[evops_models::EventId],
+    ) -> ApiResult<HashMap<Uuid, crate::EventSchedule>> = find_event_schedules_inner(event_ids);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn delete_event(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<evops_models::EventImageIds> = delete_event_unatomic(event_id, user_id);
+
+    #[instrument(skip_all, fields(%event_id, %image_id, %user_id))]
+    pub async fn reserve_image(
+        &mut self,
+        event_id: evops_models::EventId,
+        image_id: evops_models::EventImageId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> = reserve_image_unatomic(event_id, image_id, user_id);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn set_event_recurrence(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        recurrence: Option<crate::Recurrence>,
+    ) -> ApiResult<()> = set_event_recurrence_unatomic(event_id, user_id, recurrence.as_ref());
+
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn find_event_recurrence(
+        &mut self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<Option<crate::Recurrence>> = find_event_recurrence_inner(event_id);
+
+    #[instrument(skip_all, fields(%event_id, %user_id, %occurs_at))]
+    pub async fn override_occurrence(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        occurs_at: DateTime<Utc>,
+        form: crate::OccurrenceOverride,
+    ) -> ApiResult<()> = override_occurrence_unatomic(event_id, user_id, occurs_at, &form);
+
+    #[instrument(skip_all)]
+    pub async fn list_occurrences(
+        &mut self,
+        window: &crate::TimeRange,
+        filter: &crate::EventFilter,
+    ) -> ApiResult<Vec<crate::EventOccurrence>> = list_occurrences_inner(window, filter);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn set_event_location(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        location: crate::EventLocation,
+    ) -> ApiResult<()> = set_event_location_unatomic(event_id, user_id, &location);
+
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn find_event_location(
+        &mut self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<crate::EventLocation> = find_event_location_inner(event_id);
+
+    #[instrument(skip_all, fields(%event_id, %user_id, ?status))]
+    pub async fn rsvp(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        status: crate::AttendanceStatus,
+    ) -> ApiResult<crate::AttendanceStatus> = rsvp_unatomic(event_id, user_id, status);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn cancel_rsvp(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> = cancel_rsvp_unatomic(event_id, user_id);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn find_rsvp(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<Option<crate::AttendanceStatus>> = find_rsvp_inner(event_id, user_id);
+
+    #[instrument(skip_all, fields(event_count = event_ids.len()))]
+    pub async fn find_event_attendance(
+        &mut self,
+        event_ids: &[evops_models::EventId],
+    ) -> ApiResult<HashMap<Uuid, crate::EventAttendance>>
+        = find_event_attendance_inner(event_ids);
+
+    #[instrument(skip_all, fields(%event_id, %user_id, ?capacity))]
+    pub async fn set_event_capacity(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        capacity: Option<u32>,
+    ) -> ApiResult<()> = set_event_capacity_unatomic(event_id, user_id, capacity);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn toggle_bookmark(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<bool> = toggle_bookmark_unatomic(event_id, user_id);
+
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn list_bookmarked_events(
+        &mut self,
+        user_id: evops_models::UserId,
+        cursor: Option<crate::Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: crate::TotalCount,
+    ) -> ApiResult<crate::Page<evops_models::Event>>
+        = list_bookmarked_events_inner(user_id, cursor, limit, count);
+
+    #[instrument(skip_all, fields(%event_id, %user_id, %reaction))]
+    pub async fn toggle_reaction(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        reaction: &str,
+    ) -> ApiResult<bool> = toggle_reaction_unatomic(event_id, user_id, reaction);
+
+    #[instrument(skip_all, fields(event_count = event_ids.len()))]
+    pub async fn find_event_reactions(
+        &mut self,
+        event_ids: &[evops_models::EventId],
+    ) -> ApiResult<HashMap<Uuid, BTreeMap<String, u64>>> = find_event_reactions_inner(event_ids);
+
+    #[instrument(skip_all, fields(%event_id, %user_id, %attendee_id))]
+    pub async fn issue_check_in_token(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        attendee_id: evops_models::UserId,
+        token_hash: &crate::CheckInTokenHash,
+    ) -> ApiResult<()> = issue_check_in_token_unatomic(event_id, user_id, attendee_id, token_hash);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn redeem_check_in_token(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        token_hash: &crate::CheckInTokenHash,
+    ) -> ApiResult<evops_models::UserId>
+        = redeem_check_in_token_unatomic(event_id, user_id, token_hash);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn check_in_report(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<crate::CheckInReport> = check_in_report_inner(event_id, user_id);
+
+    #[instrument(skip_all, fields(%owner_id))]
+    pub async fn create_tag(
+        &mut self,
+        form: evops_models::NewTagForm,
+        owner_id: evops_models::UserId,
+    ) -> ApiResult<evops_models::TagId> = create_tag_unatomic(&form, owner_id);
+
+    #[instrument(skip_all, fields(tag_id = %id))]
+    pub async fn find_tag(&mut self, id: evops_models::TagId) -> ApiResult<evops_models::Tag>
+        = find_tag_inner(id);
+
+    #[instrument(skip_all)]
+    pub async fn list_tags(
+        &mut self,
+        cursor: Option<crate::Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: crate::TotalCount,
+    ) -> ApiResult<crate::Page<evops_models::Tag>> = list_tags_inner(cursor, limit, count);
+
+    #[instrument(skip_all, fields(tag_id = %id, %user_id))]
+    pub async fn delete_tag(
+        &mut self,
+        id: evops_models::TagId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> = delete_tag_unatomic(id, user_id);
+
+    #[instrument(skip_all, fields(%event_id, %user_id))]
+    pub async fn post_comment(
+        &mut self,
+        event_id: evops_models::EventId,
+        user_id: evops_models::UserId,
+        form: crate::NewCommentForm,
+    ) -> ApiResult<crate::CommentId> = post_comment_unatomic(event_id, user_id, &form);
+
+    #[instrument(skip_all, fields(comment_id = %id))]
+    pub async fn find_comment(&mut self, id: crate::CommentId) -> ApiResult<crate::EventComment>
+        = find_comment_inner(id);
+
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn list_comments(
+        &mut self,
+        event_id: evops_models::EventId,
+        cursor: Option<crate::Cursor>,
+        limit: Option<evops_models::PgLimit>,
+        count: crate::TotalCount,
+    ) -> ApiResult<crate::Page<crate::EventComment>>
+        = list_comments_inner(event_id, cursor, limit, count);
+
+    #[instrument(skip_all, fields(%comment_id, %user_id))]
+    pub async fn edit_comment(
+        &mut self,
+        comment_id: crate::CommentId,
+        user_id: evops_models::UserId,
+        body: String,
+    ) -> ApiResult<()> = edit_comment_unatomic(comment_id, user_id, &body);
+
+    #[instrument(skip_all, fields(%comment_id, %user_id))]
+    pub async fn delete_comment(
+        &mut self,
+        comment_id: crate::CommentId,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<()> = delete_comment_unatomic(comment_id, user_id);
+
+    #[instrument(skip_all)]
+    pub async fn create_venue(&mut self, form: crate::NewVenueForm) -> ApiResult<crate::VenueId>
+        = create_venue_unatomic(&form);
+
+    #[instrument(skip_all, fields(venue_id = %id))]
+    pub async fn find_venue(&mut self, id: crate::VenueId) -> ApiResult<crate::Venue>
+        = find_venue_inner(id);
+
+    #[instrument(skip_all, fields(%event_id, %new_author_id))]
+    pub async fn transfer_event(
+        &mut self,
+        event_id: evops_models::EventId,
+        new_author_id: evops_models::UserId,
+    ) -> ApiResult<()> = transfer_event_unatomic(event_id, new_author_id);
+
+    #[instrument(skip_all, fields(%tag_id, %new_owner_id))]
+    pub async fn transfer_tag(
+        &mut self,
+        tag_id: evops_models::TagId,
+        new_owner_id: evops_models::UserId,
+    ) -> ApiResult<()> = transfer_tag_unatomic(tag_id, new_owner_id);
+
+    /// Makes another user the author of every event of the user and returns
+    /// how many events that was.
+    #[instrument(skip_all, fields(%user_id, %new_author_id))]
+    pub async fn transfer_user_events(
+        &mut self,
+        user_id: evops_models::UserId,
+        new_author_id: evops_models::UserId,
+    ) -> ApiResult<usize> = transfer_user_events_unatomic(user_id, new_author_id);
+
+    /// Deletes the user along with their events and comments, see
+    /// [`crate::Database::delete_user`].
+    #[instrument(skip_all, fields(%user_id))]
+    pub async fn delete_user(
+        &mut self,
+        user_id: evops_models::UserId,
+    ) -> ApiResult<Vec<evops_models::EventImageId>> = delete_user_unatomic(user_id);
+
+    /// Deletes an event regardless of who authored it.
+    #[instrument(skip_all, fields(%event_id))]
+    pub async fn delete_event_as_admin(
+        &mut self,
+        event_id: evops_models::EventId,
+    ) -> ApiResult<evops_models::EventImageIds> = delete_event_as_admin_unatomic(event_id);
+
+    /// Deletes a tag regardless of who owns it, including tags without an
+    /// owner.
+    #[instrument(skip_all, fields(%tag_id))]
+    pub async fn delete_tag_as_admin(&mut self, tag_id: evops_models::TagId) -> ApiResult<()>
+        = delete_tag_as_admin_unatomic(tag_id);
+
+    #[instrument(skip_all)]
+    pub async fn integrity_report(&mut self) -> ApiResult<crate::IntegrityReport>
+        = integrity_report_inner();
+}
@@ -0,0 +1,443 @@
+//! Checks that [`MemoryStore`] follows the same rules as the Postgres
+//! backend, without needing a database.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use uuid::Uuid;
+
+use evops_db::{
+    AuthStore as _, EventFilter, EventPhase, EventSchedule, EventSort, EventStore as _,
+    EventTimezone, GeoPoint, GeoRadius, MemoryStore, TagFilter, TagMatch, TagStore as _, Total,
+    TotalCount, UserStore as _,
+};
+use evops_models::ApiError;
+
+async fn create_user(store: &MemoryStore, login: &str) -> evops_models::UserId {
+    let user_id = evops_models::UserId::new(Uuid::now_v7());
+    store
+        .create_user(
+            user_id,
+            &unsafe { evops_models::UserLogin::new_unchecked(login.to_owned()) },
+            &evops_models::UserPasswordHash::new("hash".to_owned()),
+            &unsafe { evops_models::UserDisplayName::new_unchecked(login.to_owned()) },
+        )
+        .await
+        .unwrap();
+    user_id
+}
+
+async fn create_tag(
+    store: &MemoryStore,
+    owner_id: evops_models::UserId,
+    name: &str,
+) -> evops_models::TagId {
+    let form = evops_models::NewTagForm {
+        name: unsafe { evops_models::TagName::new_unchecked(name.to_owned()) },
+        aliases: unsafe { evops_models::TagAliases::new_unchecked(Vec::new()) },
+    };
+    store.create_tag(form, owner_id).await.unwrap()
+}
+
+fn new_event_form(title: &str, tag_ids: &[evops_models::TagId]) -> evops_models::NewEventForm {
+    evops_models::NewEventForm {
+        title: unsafe { evops_models::EventTitle::new_unchecked(title.to_owned()) },
+        description: unsafe { evops_models::EventDescription::new_unchecked(String::new()) },
+        tag_ids: unsafe { evops_models::EventTagIds::new_unchecked(tag_ids.to_vec()) },
+    }
+}
+
+/// An hour in UTC starting at `starts_at`.
+fn schedule(starts_at: DateTime<Utc>) -> EventSchedule {
+    EventSchedule {
+        starts_at,
+        ends_at: starts_at + TimeDelta::hours(1),
+        timezone: EventTimezone::new("UTC".to_owned()),
+    }
+}
+
+fn tomorrow() -> EventSchedule {
+    self::schedule(Utc::now() + TimeDelta::days(1))
+}
+
+fn boost(tags: Vec<evops_models::TagId>) -> EventFilter {
+    EventFilter {
+        tags: TagFilter {
+            tags,
+            ..TagFilter::default()
+        },
+        ..EventFilter::default()
+    }
+}
+
+#[tokio::test]
+async fn logins_are_case_insensitive() {
+    let store = MemoryStore::new();
+    let user_id = self::create_user(&store, "Alice").await;
+
+    let login = unsafe { evops_models::UserLogin::new_unchecked("alice".to_owned()) };
+    let (found_id, _) = store.get_password_hash(&login).await.unwrap();
+    assert_eq!(found_id, user_id);
+
+    let result = {
+        store
+            .create_user(
+                evops_models::UserId::new(Uuid::now_v7()),
+                &login,
+                &evops_models::UserPasswordHash::new("hash".to_owned()),
+                &unsafe { evops_models::UserDisplayName::new_unchecked("alice".to_owned()) },
+            )
+            .await
+    };
+    assert!(matches!(result, Err(ApiError::AlreadyExists(_))));
+}
+
+#[tokio::test]
+async fn list_events_ranks_by_tags_and_paginates() {
+    let store = MemoryStore::new();
+    let author_id = self::create_user(&store, "alice").await;
+    let music = self::create_tag(&store, author_id, "music").await;
+    let mut event_ids = Vec::new();
+    for (title, tag_ids) in [
+        ("Lecture", vec![]),
+        ("Concert", vec![music]),
+        ("Talk", vec![]),
+    ] {
+        let form = self::new_event_form(title, &tag_ids);
+        event_ids.push(
+            store
+                .create_event(form, self::tomorrow(), author_id)
+                .await
+                .unwrap(),
+        );
+    }
+
+    let events = store
+        .list_events(
+            None,
+            None,
+            &self::boost(vec![music]),
+            EventSort::Relevance,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap();
+    let ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+    assert_eq!(ids, [event_ids[1], event_ids[2], event_ids[0]]);
+
+    let limit = Some(unsafe { evops_models::PgLimit::new_unchecked(2) });
+    let first_page = store
+        .list_events(
+            None,
+            limit,
+            &self::boost(vec![music]),
+            EventSort::Relevance,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap();
+    let second_page = store
+        .list_events(
+            first_page.next_cursor,
+            limit,
+            &self::boost(vec![music]),
+            EventSort::Relevance,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap();
+    let ids: Vec<_> = second_page.items.iter().map(|event| event.id).collect();
+    assert_eq!(ids, [event_ids[0]]);
+    assert!(second_page.next_cursor.is_none());
+}
+
+#[tokio::test]
+async fn list_events_filters_by_tags() {
+    let store = MemoryStore::new();
+    let author_id = self::create_user(&store, "alice").await;
+    let music = self::create_tag(&store, author_id, "music").await;
+    let outdoor = self::create_tag(&store, author_id, "outdoor").await;
+    let mut event_ids = Vec::new();
+    for (title, tag_ids) in [
+        ("Festival", vec![music, outdoor]),
+        ("Concert", vec![music]),
+        ("Lecture", vec![]),
+    ] {
+        let form = self::new_event_form(title, &tag_ids);
+        event_ids.push(
+            store
+                .create_event(form, self::tomorrow(), author_id)
+                .await
+                .unwrap(),
+        );
+    }
+
+    let tag_filter = TagFilter {
+        tags: vec![music],
+        mode: TagMatch::All,
+        exclude_tags: vec![outdoor],
+    };
+    let events = store
+        .list_events(
+            None,
+            None,
+            &EventFilter {
+                tags: tag_filter,
+                ..EventFilter::default()
+            },
+            EventSort::Newest,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap();
+    let ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+    assert_eq!(ids, [event_ids[1]]);
+}
+
+#[tokio::test]
+async fn list_events_paginates_oldest_first() {
+    let store = MemoryStore::new();
+    let author_id = self::create_user(&store, "alice").await;
+    let mut event_ids = Vec::new();
+    for title in ["First", "Second", "Third"] {
+        let form = self::new_event_form(title, &[]);
+        event_ids.push(
+            store
+                .create_event(form, self::tomorrow(), author_id)
+                .await
+                .unwrap(),
+        );
+    }
+
+    let limit = Some(unsafe { evops_models::PgLimit::new_unchecked(2) });
+    let mut cursor = None;
+    let mut ids = Vec::new();
+    loop {
+        let page = store
+            .list_events(
+                cursor,
+                limit,
+                &EventFilter::default(),
+                EventSort::Oldest,
+                TotalCount::Estimated,
+            )
+            .await
+            .unwrap();
+        assert_eq!(page.total, Some(Total::Estimated(3)));
+        assert_eq!(page.has_more, page.next_cursor.is_some());
+        ids.extend(page.items.iter().map(|event| event.id));
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(ids, event_ids);
+}
+
+#[tokio::test]
+async fn updated_events_sort_first_by_modification() {
+    let store = MemoryStore::new();
+    let author_id = self::create_user(&store, "alice").await;
+    let mut event_ids = Vec::new();
+    for title in ["First", "Second"] {
+        let form = self::new_event_form(title, &[]);
+        event_ids.push(
+            store
+                .create_event(form, self::tomorrow(), author_id)
+                .await
+                .unwrap(),
+        );
+    }
+    let form = evops_models::UpdateEventForm {
+        title: None,
+        description: Some(unsafe {
+            evops_models::EventDescription::new_unchecked("Moved indoors".to_owned())
+        }),
+        tag_ids: None,
+    };
+    store
+        .update_event(event_ids[0], author_id, form)
+        .await
+        .unwrap();
+
+    let events = {
+        store
+            .list_events(
+                None,
+                None,
+                &EventFilter::default(),
+                EventSort::RecentlyModified,
+                TotalCount::Skip,
+            )
+            .await
+            .unwrap()
+    };
+    let ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+    assert_eq!(ids, [event_ids[0], event_ids[1]]);
+}
+
+#[tokio::test]
+async fn list_events_filters_by_phase() {
+    let store = MemoryStore::new();
+    let author_id = self::create_user(&store, "alice").await;
+    let upcoming = {
+        let form = self::new_event_form("Upcoming", &[]);
+        store
+            .create_event(form, self::tomorrow(), author_id)
+            .await
+            .unwrap()
+    };
+    let past = {
+        let form = self::new_event_form("Past", &[]);
+        let schedule = self::schedule(Utc::now() - TimeDelta::days(1));
+        store.create_event(form, schedule, author_id).await.unwrap()
+    };
+    let backwards = {
+        let schedule = self::tomorrow();
+        EventSchedule {
+            ends_at: schedule.starts_at - TimeDelta::hours(1),
+            ..schedule
+        }
+    };
+    let form = self::new_event_form("Backwards", &[]);
+    let result = store.create_event(form, backwards.clone(), author_id).await;
+    assert!(matches!(result, Err(ApiError::InvalidArgument(_))));
+    let result = store
+        .set_event_schedule(upcoming, author_id, backwards)
+        .await;
+    assert!(matches!(result, Err(ApiError::InvalidArgument(_))));
+
+    for (phase, expected) in [
+        (EventPhase::Upcoming, vec![upcoming]),
+        (EventPhase::HappeningNow, vec![]),
+        (EventPhase::Past, vec![past]),
+    ] {
+        let filter = EventFilter {
+            phase: Some(phase),
+            ..EventFilter::default()
+        };
+        let events = {
+            store
+                .list_events(
+                    None,
+                    None,
+                    &filter,
+                    EventSort::EarliestStart,
+                    TotalCount::Skip,
+                )
+                .await
+                .unwrap()
+        };
+        let ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+        assert_eq!(ids, expected, "phase: {phase:?}");
+    }
+}
+
+#[tokio::test]
+async fn list_events_matches_search_terms_as_substrings() {
+    let store = MemoryStore::new();
+    let author_id = self::create_user(&store, "alice").await;
+    let mut event_ids = Vec::new();
+    for title in ["Jazz night", "Lecture", "Jazz, jazz and more jazz"] {
+        let form = self::new_event_form(title, &[]);
+        event_ids.push(
+            store
+                .create_event(form, self::tomorrow(), author_id)
+                .await
+                .unwrap(),
+        );
+    }
+
+    let filter = EventFilter {
+        search: Some("JAZZ".to_owned()),
+        ..EventFilter::default()
+    };
+    // Every match ranks the same, so relevance falls back to tag matches.
+    for sort in [EventSort::Relevance, EventSort::TagMatches] {
+        let events = store
+            .list_events(None, None, &filter, sort, TotalCount::Skip)
+            .await
+            .unwrap();
+        let ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+        assert_eq!(ids, [event_ids[2], event_ids[0]], "sort: {sort:?}");
+    }
+}
+
+#[tokio::test]
+async fn list_events_rejects_distance_filters() {
+    let store = MemoryStore::new();
+    let author_id = self::create_user(&store, "alice").await;
+    let form = self::new_event_form("Concert", &[]);
+    store
+        .create_event(form, self::tomorrow(), author_id)
+        .await
+        .unwrap();
+
+    let filter = EventFilter {
+        near: Some(GeoRadius {
+            center: GeoPoint {
+                latitude: 52.52,
+                longitude: 13.405,
+            },
+            radius_km: 50.0,
+        }),
+        ..EventFilter::default()
+    };
+    for sort in [EventSort::Relevance, EventSort::Nearest] {
+        let result = {
+            store
+                .list_events(None, None, &filter, sort, TotalCount::Skip)
+                .await
+        };
+        assert!(
+            matches!(result, Err(ApiError::InvalidArgument(_))),
+            "sort: {sort:?}",
+        );
+    }
+}
+
+#[tokio::test]
+async fn failed_create_event_leaves_no_event() {
+    let store = MemoryStore::new();
+    let author_id = self::create_user(&store, "alice").await;
+    let unknown_tag_id = evops_models::TagId::new(Uuid::now_v7());
+
+    let form = self::new_event_form("Concert", &[unknown_tag_id]);
+    let result = store.create_event(form, self::tomorrow(), author_id).await;
+
+    assert!(matches!(result, Err(ApiError::NotFound(_))));
+    assert!(
+        store
+            .list_events(
+                None,
+                None,
+                &EventFilter::default(),
+                EventSort::Relevance,
+                TotalCount::Skip
+            )
+            .await
+            .unwrap()
+            .items
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn only_owners_can_delete() {
+    let store = MemoryStore::new();
+    let owner_id = self::create_user(&store, "alice").await;
+    let other_id = self::create_user(&store, "bob").await;
+    let tag_id = self::create_tag(&store, owner_id, "music").await;
+    let form = self::new_event_form("Concert", &[tag_id]);
+    let event_id = store
+        .create_event(form, self::tomorrow(), owner_id)
+        .await
+        .unwrap();
+
+    let result = store.delete_event(event_id, other_id).await;
+    assert!(matches!(result, Err(ApiError::Forbidden(_))));
+    let result = store.delete_tag(tag_id, other_id).await;
+    assert!(matches!(result, Err(ApiError::Forbidden(_))));
+
+    store.delete_tag(tag_id, owner_id).await.unwrap();
+    let event = store.find_event(event_id).await.unwrap();
+    assert!(event.tags.as_ref().is_empty());
+}
@@ -0,0 +1,49 @@
+//! Checks which iCalendar recurrence rules [`RecurrenceRule`] accepts.
+
+use evops_db::RecurrenceRule;
+use evops_models::ApiError;
+
+#[test]
+fn rules_round_trip_in_canonical_form() {
+    for (rule, canonical) in [
+        ("FREQ=DAILY", "FREQ=DAILY"),
+        (
+            "RRULE:freq=weekly;byday=we,mo;interval=1",
+            "FREQ=WEEKLY;BYDAY=MO,WE",
+        ),
+        (
+            "FREQ=WEEKLY;INTERVAL=2;UNTIL=20301231T235959Z;WKST=SU",
+            "FREQ=WEEKLY;INTERVAL=2;UNTIL=20301231T235959Z;WKST=SU",
+        ),
+        ("FREQ=MONTHLY;COUNT=12", "FREQ=MONTHLY;COUNT=12"),
+        ("FREQ=DAILY;COUNT=10000", "FREQ=DAILY;COUNT=10000"),
+        ("FREQ=YEARLY;UNTIL=20350101", "FREQ=YEARLY;UNTIL=20350101"),
+    ] {
+        let parsed: RecurrenceRule = rule.parse().unwrap();
+        assert_eq!(parsed.to_string(), canonical);
+        assert_eq!(canonical.parse::<RecurrenceRule>().unwrap(), parsed);
+    }
+}
+
+#[test]
+fn unsupported_or_malformed_rules_are_rejected() {
+    for rule in [
+        "",
+        "INTERVAL=2",
+        "FREQ=HOURLY",
+        "FREQ=DAILY;FREQ=WEEKLY",
+        "FREQ=DAILY;INTERVAL=0",
+        "FREQ=DAILY;COUNT=10001",
+        "FREQ=DAILY;COUNT=3;UNTIL=20300101",
+        "FREQ=DAILY;UNTIL=20300101T000000",
+        "FREQ=MONTHLY;BYDAY=MO",
+        "FREQ=WEEKLY;BYDAY=1MO",
+        "FREQ=WEEKLY;BYSETPOS=1",
+    ] {
+        let result = rule.parse::<RecurrenceRule>();
+        assert!(
+            matches!(result, Err(ApiError::InvalidArgument(_))),
+            "{rule}: {result:?}",
+        );
+    }
+}
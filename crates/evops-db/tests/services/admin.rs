@@ -0,0 +1,199 @@
+use uuid::Uuid;
+
+use evops_db::test_util::TestDatabase;
+use evops_db::{AttendanceStatus, CommentId, NewCommentForm, TotalCount};
+use evops_models::ApiError;
+
+fn comment_form(parent_id: Option<CommentId>, body: &str) -> NewCommentForm {
+    NewCommentForm {
+        parent_id,
+        body: body.to_owned(),
+    }
+}
+
+#[tokio::test]
+async fn transfer_user_events_moves_every_event() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let new_author_id = db.create_test_user().await.unwrap();
+    let mut event_ids = Vec::new();
+    for title in ["Concert", "Lecture"] {
+        event_ids.push(db.create_test_event(user_id, title, &[]).await.unwrap());
+    }
+    let other_event_id = db
+        .create_test_event(new_author_id, "Talk", &[])
+        .await
+        .unwrap();
+
+    let transferred_count = db.transfer_user_events(user_id, new_author_id).await;
+
+    assert_eq!(transferred_count.unwrap(), 2);
+    for event_id in event_ids.into_iter().chain([other_event_id]) {
+        let event = db.find_event(event_id).await.unwrap();
+        assert_eq!(event.author.id, new_author_id);
+    }
+    let integrity_report = db.integrity_report().await.unwrap();
+    assert!(integrity_report.events_modified_before_created.is_empty());
+}
+
+#[tokio::test]
+async fn transfer_user_events_rejects_unknown_users() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let event_id = db.create_test_event(user_id, "Concert", &[]).await.unwrap();
+    let unknown_user_id = evops_models::UserId::new(Uuid::now_v7());
+
+    let result = db.transfer_user_events(user_id, unknown_user_id).await;
+
+    assert!(matches!(result, Err(ApiError::NotFound(_))));
+    let event = db.find_event(event_id).await.unwrap();
+    assert_eq!(event.author.id, user_id);
+}
+
+#[tokio::test]
+async fn delete_user_cascades_to_everything_of_the_user() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let other_user_id = db.create_test_user().await.unwrap();
+    let tag_id = db.create_test_tag(user_id, &[]).await.unwrap();
+    let event_id = db
+        .create_test_event(user_id, "Concert", &[tag_id])
+        .await
+        .unwrap();
+    let image_id = evops_models::EventImageId::new(Uuid::now_v7());
+    db.reserve_image(event_id, image_id, user_id).await.unwrap();
+    db.insert_refresh_token(&TestDatabase::new_refresh_token_hash(), user_id)
+        .await
+        .unwrap();
+
+    let other_event_id = db
+        .create_test_event(other_user_id, "Lecture", &[tag_id])
+        .await
+        .unwrap();
+    db.rsvp(other_event_id, user_id, AttendanceStatus::Going)
+        .await
+        .unwrap();
+    db.toggle_bookmark(other_event_id, user_id).await.unwrap();
+    db.toggle_reaction(other_event_id, user_id, "🎉")
+        .await
+        .unwrap();
+    let comment_id = db
+        .post_comment(other_event_id, user_id, self::comment_form(None, "See you"))
+        .await
+        .unwrap();
+    db.post_comment(
+        other_event_id,
+        other_user_id,
+        self::comment_form(Some(comment_id), "Likewise"),
+    )
+    .await
+    .unwrap();
+    db.post_comment(
+        other_event_id,
+        other_user_id,
+        self::comment_form(None, "Hi"),
+    )
+    .await
+    .unwrap();
+
+    let image_ids = db.delete_user(user_id).await.unwrap();
+
+    assert_eq!(image_ids, [image_id]);
+    assert!(matches!(
+        db.find_user(user_id).await,
+        Err(ApiError::NotFound(_))
+    ));
+    assert!(matches!(
+        db.find_event(event_id).await,
+        Err(ApiError::NotFound(_))
+    ));
+
+    let attendance = db.find_event_attendance(&[other_event_id]).await.unwrap();
+    assert_eq!(attendance[&other_event_id.into_inner()].going, 0);
+    let reactions = db.find_event_reactions(&[other_event_id]).await.unwrap();
+    assert!(reactions.is_empty());
+    // The reply went with the comment it answered.
+    let comments = {
+        db.list_comments(other_event_id, None, None, TotalCount::Exact)
+            .await
+            .unwrap()
+    };
+    assert_eq!(comments.items.len(), 1);
+
+    // The tag survives without an owner and stays on the other user's event.
+    let integrity_report = db.integrity_report().await.unwrap();
+    assert_eq!(integrity_report.tags_without_owner, [tag_id]);
+    assert!(integrity_report.unused_tags.is_empty());
+    let other_event = db.find_event(other_event_id).await.unwrap();
+    let tag_ids: Vec<_> = other_event.tags.as_ref().iter().map(|tag| tag.id).collect();
+    assert_eq!(tag_ids, [tag_id]);
+}
+
+#[tokio::test]
+async fn delete_user_rejects_unknown_users() {
+    let db = TestDatabase::new().await.unwrap();
+    let unknown_user_id = evops_models::UserId::new(Uuid::now_v7());
+
+    let result = db.delete_user(unknown_user_id).await;
+
+    assert!(matches!(result, Err(ApiError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn integrity_report_counts_each_inconsistency() {
+    let db = TestDatabase::new().await.unwrap();
+    let owner_id = db.create_test_user().await.unwrap();
+    let integrity_report = db.integrity_report().await.unwrap();
+    assert!(integrity_report.tags_without_owner.is_empty());
+    assert!(integrity_report.unused_tags.is_empty());
+
+    let used_tag_id = db.create_test_tag(owner_id, &[]).await.unwrap();
+    let used_tag = db.find_tag(used_tag_id).await.unwrap();
+    let unused_tag_id = {
+        db.create_test_tag(owner_id, &[used_tag.name.as_ref()])
+            .await
+            .unwrap()
+    };
+    let event_id = db
+        .create_test_event(owner_id, "Concert", &[used_tag_id])
+        .await
+        .unwrap();
+    let gapless_event_id = db
+        .create_test_event(owner_id, "Lecture", &[used_tag_id])
+        .await
+        .unwrap();
+    for _ in 0..2 {
+        let image_id = evops_models::EventImageId::new(Uuid::now_v7());
+        db.reserve_image(event_id, image_id, owner_id)
+            .await
+            .unwrap();
+    }
+    db.reserve_image(
+        gapless_event_id,
+        evops_models::EventImageId::new(Uuid::now_v7()),
+        owner_id,
+    )
+    .await
+    .unwrap();
+    db.batch_execute(&format!(
+        "DELETE FROM event_images WHERE event_id = '{event_id}' AND position = 1; \
+         UPDATE events SET modified_at = created_at - interval '1 hour' \
+         WHERE id = '{gapless_event_id}'; \
+         UPDATE tags SET owner_id = NULL WHERE id = '{unused_tag_id}';",
+    ))
+    .unwrap();
+
+    let integrity_report = db.integrity_report().await.unwrap();
+
+    assert_eq!(integrity_report.tags_without_owner, [unused_tag_id]);
+    assert_eq!(integrity_report.unused_tags, [unused_tag_id]);
+    assert_eq!(
+        integrity_report.aliases_shadowing_tags,
+        [(unused_tag_id, used_tag.name.as_ref().to_owned())],
+    );
+    assert_eq!(integrity_report.events_with_image_gaps, [event_id]);
+    assert_eq!(
+        integrity_report.events_modified_before_created,
+        [gapless_event_id],
+    );
+}
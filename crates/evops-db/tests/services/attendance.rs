@@ -0,0 +1,179 @@
+use evops_db::test_util::TestDatabase;
+use evops_db::{AttendanceStatus, EventAttendance};
+use evops_models::ApiError;
+
+async fn attendance(db: &TestDatabase, event_id: evops_models::EventId) -> EventAttendance {
+    let mut attendance = db.find_event_attendance(&[event_id]).await.unwrap();
+    attendance.remove(&event_id.into_inner()).unwrap()
+}
+
+#[tokio::test]
+async fn rsvp_waitlists_when_full_and_promotes_in_order() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Workshop", &[])
+        .await
+        .unwrap();
+    db.set_event_capacity(event_id, author_id, Some(2))
+        .await
+        .unwrap();
+    let mut user_ids = Vec::new();
+    for _ in 0..4 {
+        user_ids.push(db.create_test_user().await.unwrap());
+    }
+
+    let mut statuses = Vec::new();
+    for &user_id in &user_ids {
+        let status = {
+            db.rsvp(event_id, user_id, AttendanceStatus::Going)
+                .await
+                .unwrap()
+        };
+        statuses.push(status);
+    }
+    assert_eq!(
+        statuses,
+        [
+            AttendanceStatus::Going,
+            AttendanceStatus::Going,
+            AttendanceStatus::Waitlisted,
+            AttendanceStatus::Waitlisted,
+        ],
+    );
+    // Going again keeps the place on the waitlist.
+    let status = {
+        db.rsvp(event_id, user_ids[2], AttendanceStatus::Going)
+            .await
+            .unwrap()
+    };
+    assert_eq!(status, AttendanceStatus::Waitlisted);
+    let attendance = self::attendance(&db, event_id).await;
+    assert_eq!(
+        (attendance.going, attendance.waitlisted, attendance.capacity),
+        (2, 2, Some(2)),
+    );
+
+    db.rsvp(event_id, user_ids[0], AttendanceStatus::Declined)
+        .await
+        .unwrap();
+    let status = db.find_rsvp(event_id, user_ids[2]).await.unwrap();
+    assert_eq!(status, Some(AttendanceStatus::Going));
+    let status = db.find_rsvp(event_id, user_ids[3]).await.unwrap();
+    assert_eq!(status, Some(AttendanceStatus::Waitlisted));
+
+    db.cancel_rsvp(event_id, user_ids[1]).await.unwrap();
+    assert_eq!(db.find_rsvp(event_id, user_ids[1]).await.unwrap(), None);
+    let status = db.find_rsvp(event_id, user_ids[3]).await.unwrap();
+    assert_eq!(status, Some(AttendanceStatus::Going));
+    let attendance = self::attendance(&db, event_id).await;
+    assert_eq!((attendance.going, attendance.waitlisted), (2, 0));
+}
+
+#[tokio::test]
+async fn set_event_capacity_lets_waitlisted_users_in() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let other_user_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Workshop", &[])
+        .await
+        .unwrap();
+    db.set_event_capacity(event_id, author_id, Some(1))
+        .await
+        .unwrap();
+    let mut user_ids = Vec::new();
+    for _ in 0..3 {
+        let user_id = db.create_test_user().await.unwrap();
+        db.rsvp(event_id, user_id, AttendanceStatus::Going)
+            .await
+            .unwrap();
+        user_ids.push(user_id);
+    }
+
+    db.set_event_capacity(event_id, author_id, Some(2))
+        .await
+        .unwrap();
+    let attendance = self::attendance(&db, event_id).await;
+    assert_eq!((attendance.going, attendance.waitlisted), (2, 1));
+    let status = db.find_rsvp(event_id, user_ids[1]).await.unwrap();
+    assert_eq!(status, Some(AttendanceStatus::Going));
+
+    db.set_event_capacity(event_id, author_id, None)
+        .await
+        .unwrap();
+    let attendance = self::attendance(&db, event_id).await;
+    assert_eq!(
+        (attendance.going, attendance.waitlisted, attendance.capacity),
+        (3, 0, None),
+    );
+
+    let results = [
+        db.set_event_capacity(event_id, other_user_id, Some(5))
+            .await,
+        db.set_event_capacity(event_id, author_id, Some(0)).await,
+    ];
+    assert!(matches!(results[0], Err(ApiError::Forbidden(_))));
+    assert!(matches!(results[1], Err(ApiError::InvalidArgument(_))));
+}
+
+#[tokio::test]
+async fn rsvp_rejects_invalid_answers() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Workshop", &[])
+        .await
+        .unwrap();
+    db.delete_event(event_id, author_id).await.unwrap();
+    let other_event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+
+    let result = db
+        .rsvp(other_event_id, author_id, AttendanceStatus::Waitlisted)
+        .await;
+    assert!(matches!(result, Err(ApiError::InvalidArgument(_))));
+    let result = db.rsvp(event_id, author_id, AttendanceStatus::Going).await;
+    assert!(matches!(result, Err(ApiError::NotFound(_))));
+    let result = db.find_rsvp(event_id, author_id).await;
+    assert!(matches!(result, Err(ApiError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn find_event_attendance_counts_each_event() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+    let other_event_id = db
+        .create_test_event(author_id, "Workshop", &[])
+        .await
+        .unwrap();
+    let deleted_event_id = db.create_test_event(author_id, "Party", &[]).await.unwrap();
+    for status in [
+        AttendanceStatus::Going,
+        AttendanceStatus::Interested,
+        AttendanceStatus::Interested,
+        AttendanceStatus::Declined,
+    ] {
+        let user_id = db.create_test_user().await.unwrap();
+        db.rsvp(event_id, user_id, status).await.unwrap();
+    }
+
+    db.delete_event(deleted_event_id, author_id).await.unwrap();
+
+    let event_ids = [event_id, other_event_id, deleted_event_id];
+    let attendance = db.find_event_attendance(&event_ids).await.unwrap();
+
+    assert_eq!(attendance.len(), 2);
+    let counts = attendance[&event_id.into_inner()];
+    assert_eq!((counts.going, counts.interested), (1, 2));
+    assert_eq!(
+        attendance[&other_event_id.into_inner()],
+        EventAttendance::default()
+    );
+}
@@ -0,0 +1,121 @@
+use uuid::Uuid;
+
+use evops_db::test_util::TestDatabase;
+use evops_db::{Total, TotalCount};
+use evops_models::{ApiError, ApiResult};
+
+async fn sign_up_alice(db: &TestDatabase, user_id: evops_models::UserId) -> ApiResult<()> {
+    db.sign_up(
+        user_id,
+        &unsafe { evops_models::UserLogin::new_unchecked("alice".to_owned()) },
+        &evops_models::UserPasswordHash::new("hash".to_owned()),
+        &unsafe { evops_models::UserDisplayName::new_unchecked("Alice".to_owned()) },
+        &TestDatabase::new_refresh_token_hash(),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn sign_up_creates_user() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = evops_models::UserId::new(Uuid::now_v7());
+
+    self::sign_up_alice(&db, user_id).await.unwrap();
+
+    let user = db.find_user(user_id).await.unwrap();
+    assert_eq!(user.id, user_id);
+    assert_eq!(user.login.as_ref(), "alice");
+    assert_eq!(user.display_name.as_ref(), "Alice");
+}
+
+#[tokio::test]
+async fn sign_up_rejects_taken_login() {
+    let db = TestDatabase::new().await.unwrap();
+    let first_user_id = evops_models::UserId::new(Uuid::now_v7());
+    let second_user_id = evops_models::UserId::new(Uuid::now_v7());
+
+    self::sign_up_alice(&db, first_user_id).await.unwrap();
+    let result = self::sign_up_alice(&db, second_user_id).await;
+    assert!(matches!(
+        result,
+        Err(ApiError::AlreadyExists(message)) if message.contains("alice"),
+    ));
+    assert!(matches!(
+        db.find_user(second_user_id).await,
+        Err(ApiError::NotFound(_)),
+    ));
+}
+
+#[tokio::test]
+async fn get_password_hash_finds_user_by_login() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let login = db.find_user(user_id).await.unwrap().login;
+
+    let (found_id, _) = db.get_password_hash(&login).await.unwrap();
+    assert_eq!(found_id, user_id);
+}
+
+#[tokio::test]
+async fn get_password_hash_rejects_unknown_login() {
+    let db = TestDatabase::new().await.unwrap();
+    let login = unsafe { evops_models::UserLogin::new_unchecked("nobody".to_owned()) };
+
+    assert!(matches!(
+        db.get_password_hash(&login).await,
+        Err(ApiError::Forbidden(_)),
+    ));
+}
+
+#[tokio::test]
+async fn check_refresh_token_accepts_only_stored_tokens() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let stored = TestDatabase::new_refresh_token_hash();
+    db.insert_refresh_token(&stored, user_id).await.unwrap();
+
+    db.check_refresh_token(&stored).await.unwrap();
+    assert!(matches!(
+        db.check_refresh_token(&TestDatabase::new_refresh_token_hash())
+            .await,
+        Err(ApiError::Auth(_)),
+    ));
+}
+
+#[tokio::test]
+async fn revoked_refresh_token_is_rejected() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let token_hash = TestDatabase::new_refresh_token_hash();
+    db.insert_refresh_token(&token_hash, user_id).await.unwrap();
+
+    assert_eq!(db.revoke_all_refresh_tokens(user_id).await.unwrap(), 1);
+    assert!(matches!(
+        db.check_refresh_token(&token_hash).await,
+        Err(ApiError::Auth(_)),
+    ));
+}
+
+#[tokio::test]
+async fn list_users_paginates_by_id() {
+    let db = TestDatabase::new().await.unwrap();
+    let mut user_ids = Vec::new();
+    for _ in 0..3 {
+        user_ids.push(db.create_test_user().await.unwrap());
+    }
+    let limit = Some(unsafe { evops_models::PgLimit::new_unchecked(2) });
+
+    let first_page = db.list_users(None, limit, TotalCount::Exact).await.unwrap();
+    let ids: Vec<_> = first_page.items.iter().map(|user| user.id).collect();
+    assert_eq!(ids, user_ids[..2]);
+    assert!(first_page.has_more);
+    assert_eq!(first_page.total, Some(Total::Exact(3)));
+
+    let second_page = db
+        .list_users(first_page.next_cursor, limit, TotalCount::Skip)
+        .await
+        .unwrap();
+    let ids: Vec<_> = second_page.items.iter().map(|user| user.id).collect();
+    assert_eq!(ids, user_ids[2..]);
+    assert!(!second_page.has_more);
+}
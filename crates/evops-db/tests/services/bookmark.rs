@@ -0,0 +1,57 @@
+use evops_db::TotalCount;
+use evops_db::test_util::TestDatabase;
+use evops_models::ApiError;
+
+#[tokio::test]
+async fn list_bookmarked_events_pages_newest_bookmark_first() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let mut event_ids = Vec::new();
+    for title in ["Meetup", "Workshop", "Talk"] {
+        let event_id = db.create_test_event(author_id, title, &[]).await.unwrap();
+        assert!(db.toggle_bookmark(event_id, user_id).await.unwrap());
+        event_ids.push(event_id);
+    }
+    // Unbookmarked again.
+    let event_id = db.create_test_event(author_id, "Party", &[]).await.unwrap();
+    assert!(db.toggle_bookmark(event_id, user_id).await.unwrap());
+    assert!(!db.toggle_bookmark(event_id, user_id).await.unwrap());
+    // Someone else's.
+    db.toggle_bookmark(event_ids[0], author_id).await.unwrap();
+
+    let limit = Some(unsafe { evops_models::PgLimit::new_unchecked(2) });
+    let mut listed = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = {
+            db.list_bookmarked_events(user_id, cursor, limit, TotalCount::Exact)
+                .await
+                .unwrap()
+        };
+        assert_eq!(page.total, Some(evops_db::Total::Exact(3)));
+        listed.extend(page.items.into_iter().map(|event| event.id));
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    event_ids.reverse();
+    assert_eq!(listed, event_ids);
+}
+
+#[tokio::test]
+async fn toggle_bookmark_rejects_unknown_events() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+    db.delete_event(event_id, author_id).await.unwrap();
+
+    let result = db.toggle_bookmark(event_id, author_id).await;
+
+    assert!(matches!(result, Err(ApiError::NotFound(_))), "{result:?}");
+}
@@ -0,0 +1,132 @@
+use evops_db::test_util::TestDatabase;
+use evops_db::{AttendanceStatus, CheckInReport, CheckInTokenHash};
+use evops_models::ApiError;
+
+fn token_hash(byte: u8) -> CheckInTokenHash {
+    CheckInTokenHash::new(vec![byte; 32])
+}
+
+#[tokio::test]
+async fn check_in_tokens_are_redeemed_once() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Workshop", &[])
+        .await
+        .unwrap();
+    let mut user_ids = Vec::new();
+    for _ in 0..3 {
+        let user_id = db.create_test_user().await.unwrap();
+        db.rsvp(event_id, user_id, AttendanceStatus::Going)
+            .await
+            .unwrap();
+        user_ids.push(user_id);
+    }
+    // Attendees get their own tokens, the author can issue them too.
+    for (issuer_id, attendee_id, byte) in
+        [(user_ids[0], user_ids[0], 1), (author_id, user_ids[1], 2)]
+    {
+        db.issue_check_in_token(event_id, issuer_id, attendee_id, &self::token_hash(byte))
+            .await
+            .unwrap();
+    }
+    let result = {
+        db.issue_check_in_token(event_id, user_ids[0], user_ids[2], &self::token_hash(3))
+            .await
+    };
+    assert!(matches!(result, Err(ApiError::Forbidden(_))), "{result:?}");
+
+    let attendee_id = {
+        db.redeem_check_in_token(event_id, author_id, &self::token_hash(2))
+            .await
+            .unwrap()
+    };
+    assert_eq!(attendee_id, user_ids[1]);
+    let result = db
+        .redeem_check_in_token(event_id, author_id, &self::token_hash(2))
+        .await;
+    assert!(
+        matches!(result, Err(ApiError::AlreadyExists(_))),
+        "{result:?}"
+    );
+    let result = db
+        .redeem_check_in_token(event_id, author_id, &self::token_hash(3))
+        .await;
+    assert!(
+        matches!(result, Err(ApiError::InvalidArgument(_))),
+        "{result:?}"
+    );
+    let result = {
+        db.issue_check_in_token(event_id, user_ids[1], user_ids[1], &self::token_hash(4))
+            .await
+    };
+    assert!(
+        matches!(result, Err(ApiError::AlreadyExists(_))),
+        "{result:?}"
+    );
+
+    let report = db.check_in_report(event_id, author_id).await.unwrap();
+    assert_eq!(
+        report,
+        CheckInReport {
+            registered: 3,
+            checked_in: 1,
+        },
+    );
+}
+
+#[tokio::test]
+async fn check_in_tokens_need_attendees_going() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Workshop", &[])
+        .await
+        .unwrap();
+
+    let result = {
+        db.issue_check_in_token(event_id, user_id, user_id, &self::token_hash(1))
+            .await
+    };
+    assert!(
+        matches!(result, Err(ApiError::InvalidArgument(_))),
+        "{result:?}"
+    );
+
+    db.rsvp(event_id, user_id, AttendanceStatus::Going)
+        .await
+        .unwrap();
+    db.issue_check_in_token(event_id, user_id, user_id, &self::token_hash(1))
+        .await
+        .unwrap();
+    db.rsvp(event_id, user_id, AttendanceStatus::Declined)
+        .await
+        .unwrap();
+    let result = db
+        .redeem_check_in_token(event_id, author_id, &self::token_hash(1))
+        .await;
+    assert!(
+        matches!(result, Err(ApiError::InvalidArgument(_))),
+        "{result:?}"
+    );
+
+    db.rsvp(event_id, user_id, AttendanceStatus::Going)
+        .await
+        .unwrap();
+    let results = [
+        db.redeem_check_in_token(event_id, user_id, &self::token_hash(1))
+            .await
+            .map(|_| ()),
+        db.check_in_report(event_id, user_id).await.map(|_| ()),
+    ];
+    for result in results {
+        assert!(matches!(result, Err(ApiError::Forbidden(_))), "{result:?}");
+    }
+    let attendee_id = {
+        db.redeem_check_in_token(event_id, author_id, &self::token_hash(1))
+            .await
+            .unwrap()
+    };
+    assert_eq!(attendee_id, user_id);
+}
@@ -0,0 +1,197 @@
+use uuid::Uuid;
+
+use evops_db::test_util::TestDatabase;
+use evops_db::{CommentId, EventComment, NewCommentForm, TotalCount};
+use evops_models::ApiError;
+
+fn form(parent_id: Option<CommentId>, body: &str) -> NewCommentForm {
+    NewCommentForm {
+        parent_id,
+        body: body.to_owned(),
+    }
+}
+
+/// The comment IDs of the thread, depth first.
+fn flatten(comment: &EventComment) -> Vec<CommentId> {
+    std::iter::once(comment.id)
+        .chain(comment.replies.iter().flat_map(self::flatten))
+        .collect()
+}
+
+#[tokio::test]
+async fn list_comments_pages_through_threads() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+    let other_event_id = db
+        .create_test_event(author_id, "Workshop", &[])
+        .await
+        .unwrap();
+
+    let first = db
+        .post_comment(event_id, user_id, self::form(None, "First"))
+        .await
+        .unwrap();
+    let second = db
+        .post_comment(event_id, author_id, self::form(None, "Second"))
+        .await
+        .unwrap();
+    let reply = {
+        db.post_comment(event_id, author_id, self::form(Some(first), "Reply"))
+            .await
+            .unwrap()
+    };
+    let nested_reply = {
+        db.post_comment(event_id, user_id, self::form(Some(reply), "Nested"))
+            .await
+            .unwrap()
+    };
+    let other_reply = {
+        db.post_comment(event_id, user_id, self::form(Some(first), "Other reply"))
+            .await
+            .unwrap()
+    };
+    db.post_comment(other_event_id, user_id, self::form(None, "Elsewhere"))
+        .await
+        .unwrap();
+
+    let limit = Some(unsafe { evops_models::PgLimit::new_unchecked(1) });
+    let page = {
+        db.list_comments(event_id, None, limit, TotalCount::Exact)
+            .await
+            .unwrap()
+    };
+    assert_eq!(page.total, Some(evops_db::Total::Exact(2)));
+    assert!(page.has_more);
+    let [thread] = &page.items[..] else {
+        panic!("{:?}", page.items);
+    };
+    assert_eq!(
+        self::flatten(thread),
+        [first, reply, nested_reply, other_reply]
+    );
+    assert_eq!(thread.body.as_deref(), Some("First"));
+    assert_eq!(thread.replies[0].replies[0].parent_id, Some(reply));
+
+    let page = {
+        db.list_comments(event_id, page.next_cursor, limit, TotalCount::Skip)
+            .await
+            .unwrap()
+    };
+    assert!(!page.has_more);
+    let thread_ids: Vec<_> = page.items.iter().map(self::flatten).collect();
+    assert_eq!(thread_ids, [vec![second]]);
+}
+
+#[tokio::test]
+async fn comments_are_edited_by_their_author_only() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+    let comment_id = db
+        .post_comment(event_id, user_id, self::form(None, "Hi"))
+        .await
+        .unwrap();
+
+    db.edit_comment(comment_id, user_id, "Hello".to_owned())
+        .await
+        .unwrap();
+    let comment = db.find_comment(comment_id).await.unwrap();
+    assert_eq!(comment.body.as_deref(), Some("Hello"));
+    assert!(comment.edited_at.is_some());
+
+    let results = [
+        db.edit_comment(comment_id, author_id, "Bye".to_owned())
+            .await,
+        db.edit_comment(comment_id, user_id, "  ".to_owned()).await,
+        db.edit_comment(CommentId::new(Uuid::now_v7()), user_id, "Bye".to_owned())
+            .await,
+    ];
+    assert!(matches!(results[0], Err(ApiError::Forbidden(_))));
+    assert!(matches!(results[1], Err(ApiError::InvalidArgument(_))));
+    assert!(matches!(results[2], Err(ApiError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn delete_comment_keeps_replies_and_allows_the_event_author() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let other_user_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+    let comment_id = db
+        .post_comment(event_id, user_id, self::form(None, "Spam"))
+        .await
+        .unwrap();
+    let reply_id = {
+        db.post_comment(
+            event_id,
+            other_user_id,
+            self::form(Some(comment_id), "Reply"),
+        )
+        .await
+        .unwrap()
+    };
+
+    let result = db.delete_comment(comment_id, other_user_id).await;
+    assert!(matches!(result, Err(ApiError::Forbidden(_))));
+    db.delete_comment(comment_id, author_id).await.unwrap();
+
+    let comment = db.find_comment(comment_id).await.unwrap();
+    assert_eq!(comment.body, None);
+    assert!(comment.deleted_at.is_some());
+    assert_eq!(self::flatten(&comment), [comment_id, reply_id]);
+    let results = [
+        db.edit_comment(comment_id, user_id, "Sorry".to_owned())
+            .await,
+        db.post_comment(event_id, other_user_id, self::form(Some(comment_id), "Hm"))
+            .await
+            .map(|_| ()),
+    ];
+    for result in results {
+        assert!(
+            matches!(result, Err(ApiError::InvalidArgument(_))),
+            "{result:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn post_comment_rejects_parents_on_other_events() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+    let other_event_id = db
+        .create_test_event(author_id, "Workshop", &[])
+        .await
+        .unwrap();
+    let comment_id = db
+        .post_comment(event_id, author_id, self::form(None, "Hi"))
+        .await
+        .unwrap();
+
+    let result = {
+        db.post_comment(
+            other_event_id,
+            author_id,
+            self::form(Some(comment_id), "Hi"),
+        )
+        .await
+    };
+
+    assert!(matches!(result, Err(ApiError::NotFound(_))), "{result:?}");
+}
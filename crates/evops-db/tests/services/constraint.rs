@@ -0,0 +1,105 @@
+//! Checks that every kind of constraint violation reaches the caller as a
+//! precise [`ApiError`].
+
+use chrono::{TimeDelta, Utc};
+use uuid::Uuid;
+
+use evops_db::test_util::TestDatabase;
+use evops_models::ApiError;
+
+#[tokio::test]
+async fn unique_violation_names_the_taken_value() {
+    let db = TestDatabase::new().await.unwrap();
+    let owner_id = db.create_test_user().await.unwrap();
+    let tag = db
+        .find_tag(db.create_test_tag(owner_id, &[]).await.unwrap())
+        .await
+        .unwrap();
+    let expected = format!("Tag with name {} already exists.", tag.name.as_ref());
+
+    let form = evops_models::NewTagForm {
+        name: tag.name,
+        aliases: unsafe { evops_models::TagAliases::new_unchecked(Vec::new()) },
+    };
+    let result = db.create_tag(form, owner_id).await;
+
+    assert!(matches!(result, Err(ApiError::AlreadyExists(message)) if message == expected));
+}
+
+#[tokio::test]
+async fn missing_reference_is_not_found() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let unknown_tag_id = evops_models::TagId::new(Uuid::now_v7());
+
+    let result = {
+        db.create_test_event(author_id, "Concert", &[unknown_tag_id])
+            .await
+    };
+
+    let expected = format!("No tag with ID {unknown_tag_id} found.");
+    assert!(matches!(result, Err(ApiError::NotFound(message)) if message == expected));
+}
+
+#[tokio::test]
+async fn deleting_a_referenced_row_is_an_invalid_argument() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+    // No table of the schema keeps events from being deleted, so the test
+    // brings its own.
+    db.batch_execute(&format!(
+        "CREATE TABLE event_archive (event_id uuid REFERENCES events (id)); \
+         INSERT INTO event_archive VALUES ('{event_id}');",
+    ))
+    .unwrap();
+
+    let result = db.delete_event(event_id, author_id).await;
+
+    let expected = format!("Event {event_id} is still referenced from event_archive.");
+    assert!(matches!(result, Err(ApiError::InvalidArgument(message)) if message == expected));
+}
+
+#[tokio::test]
+async fn check_violation_explains_the_rule() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let starts_at = Utc::now() + TimeDelta::days(1);
+
+    let result = {
+        db.create_test_event_at(
+            author_id,
+            "Concert",
+            &[],
+            starts_at,
+            starts_at - TimeDelta::hours(1),
+        )
+        .await
+    };
+
+    let expected = "An event can't end before it starts.";
+    assert!(matches!(result, Err(ApiError::InvalidArgument(message)) if message == expected));
+}
+
+#[tokio::test]
+async fn not_null_violation_names_the_column() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    // The services always fill in the description, so a trigger takes it
+    // away again.
+    db.batch_execute(
+        "CREATE FUNCTION drop_description() RETURNS trigger LANGUAGE plpgsql AS $$ \
+         BEGIN NEW.description := NULL; RETURN NEW; END $$; \
+         CREATE TRIGGER drop_description BEFORE INSERT ON events \
+         FOR EACH ROW EXECUTE FUNCTION drop_description();",
+    )
+    .unwrap();
+
+    let result = db.create_test_event(author_id, "Concert", &[]).await;
+
+    let expected = "The event description must be set.";
+    assert!(matches!(result, Err(ApiError::InvalidArgument(message)) if message == expected));
+}
@@ -0,0 +1,1120 @@
+use chrono::{SubsecRound as _, TimeDelta, Utc};
+use uuid::Uuid;
+
+use evops_db::test_util::TestDatabase;
+use evops_db::{
+    Cursor, EventFilter, EventPhase, EventSchedule, EventSort, EventTimezone, HighlightOptions,
+    TagFilter, TagMatch, TimeRange, Total, TotalCount,
+};
+use evops_models::ApiError;
+
+fn limit(value: i64) -> Option<evops_models::PgLimit> {
+    Some(unsafe { evops_models::PgLimit::new_unchecked(value) })
+}
+
+fn boost(tags: Vec<evops_models::TagId>) -> EventFilter {
+    EventFilter {
+        tags: TagFilter {
+            tags,
+            ..TagFilter::default()
+        },
+        ..EventFilter::default()
+    }
+}
+
+fn search(term: &str) -> EventFilter {
+    EventFilter {
+        search: Some(term.to_owned()),
+        ..EventFilter::default()
+    }
+}
+
+fn update_form(
+    title: Option<&str>,
+    tag_ids: Option<Vec<evops_models::TagId>>,
+) -> evops_models::UpdateEventForm {
+    evops_models::UpdateEventForm {
+        title: title
+            .map(|title| unsafe { evops_models::EventTitle::new_unchecked(title.to_owned()) }),
+        description: None,
+        tag_ids: tag_ids
+            .map(|tag_ids| unsafe { evops_models::EventTagIds::new_unchecked(tag_ids) }),
+    }
+}
+
+fn new_event_form() -> evops_models::NewEventForm {
+    evops_models::NewEventForm {
+        title: unsafe { evops_models::EventTitle::new_unchecked("Concert".to_owned()) },
+        description: unsafe { evops_models::EventDescription::new_unchecked(String::new()) },
+        tag_ids: unsafe { evops_models::EventTagIds::new_unchecked(Vec::new()) },
+    }
+}
+
+fn schedule(hours: i64, timezone: &str) -> EventSchedule {
+    let starts_at = Utc::now() + TimeDelta::days(1);
+    EventSchedule {
+        starts_at,
+        ends_at: starts_at + TimeDelta::hours(hours),
+        timezone: EventTimezone::new(timezone.to_owned()),
+    }
+}
+
+#[tokio::test]
+async fn create_event_stores_fields_and_tags() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let tag_id = db.create_test_tag(author_id, &["alias"]).await.unwrap();
+
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[tag_id])
+        .await
+        .unwrap();
+
+    let event = db.find_event(event_id).await.unwrap();
+    assert_eq!(event.id, event_id);
+    assert_eq!(event.author.id, author_id);
+    assert_eq!(event.title.as_ref(), "Concert");
+    assert_eq!(event.created_at, event.modified_at);
+    let tag_ids: Vec<_> = event.tags.as_ref().iter().map(|tag| tag.id).collect();
+    assert_eq!(tag_ids, [tag_id]);
+    assert!(event.image_ids.into_inner().is_empty());
+}
+
+#[tokio::test]
+async fn create_event_with_unknown_tag_is_rolled_back() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let unknown_tag_id = evops_models::TagId::new(Uuid::now_v7());
+
+    let result = db
+        .create_test_event(author_id, "Concert", &[unknown_tag_id])
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(ApiError::NotFound(message)) if message.contains(&unknown_tag_id.to_string()),
+    ));
+    let events = db
+        .list_events(
+            None,
+            None,
+            &EventFilter::default(),
+            EventSort::Relevance,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap();
+    assert!(events.items.is_empty());
+}
+
+#[tokio::test]
+async fn create_event_stores_schedule() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let schedule = self::schedule(2, "Europe/Moscow");
+    let starts_at = schedule.starts_at;
+
+    let event_id = db
+        .create_event(self::new_event_form(), schedule, author_id)
+        .await
+        .unwrap();
+
+    let schedule = db.find_event_schedule(event_id).await.unwrap();
+    assert_eq!(schedule.starts_at, starts_at.trunc_subsecs(6));
+    assert_eq!(schedule.ends_at - schedule.starts_at, TimeDelta::hours(2));
+    assert_eq!(schedule.timezone.as_ref(), "Europe/Moscow");
+}
+
+#[tokio::test]
+async fn create_event_rejects_invalid_schedules() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+
+    for schedule in [
+        self::schedule(-1, "UTC"),
+        self::schedule(1, "Mars/Olympus_Mons"),
+        self::schedule(1, "EST"),
+        self::schedule(1, "posix/Europe/Berlin"),
+    ] {
+        let result = {
+            db.create_event(self::new_event_form(), schedule, author_id)
+                .await
+        };
+        assert!(
+            matches!(result, Err(ApiError::InvalidArgument(_))),
+            "{result:?}",
+        );
+    }
+}
+
+#[tokio::test]
+async fn list_events_paginates_from_newest() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let mut event_ids = Vec::new();
+    for title in ["First", "Second", "Third"] {
+        event_ids.push(db.create_test_event(author_id, title, &[]).await.unwrap());
+    }
+
+    let first_page = db
+        .list_events(
+            None,
+            self::limit(2),
+            &EventFilter::default(),
+            EventSort::Newest,
+            TotalCount::Exact,
+        )
+        .await
+        .unwrap();
+    let ids: Vec<_> = first_page.items.iter().map(|event| event.id).collect();
+    assert_eq!(ids, [event_ids[2], event_ids[1]]);
+    assert!(first_page.has_more);
+    assert_eq!(first_page.total, Some(Total::Exact(3)));
+
+    let cursor: Cursor = first_page.next_cursor.unwrap().to_string().parse().unwrap();
+    let second_page = {
+        db.list_events(
+            Some(cursor),
+            self::limit(2),
+            &EventFilter::default(),
+            EventSort::Newest,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap()
+    };
+    let ids: Vec<_> = second_page.items.iter().map(|event| event.id).collect();
+    assert_eq!(ids, [event_ids[0]]);
+    assert!(!second_page.has_more);
+    assert!(second_page.next_cursor.is_none());
+    assert_eq!(second_page.total, None);
+}
+
+#[tokio::test]
+async fn list_events_knows_a_full_page_is_the_last() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    for title in ["First", "Second"] {
+        db.create_test_event(author_id, title, &[]).await.unwrap();
+    }
+
+    let page = {
+        db.list_events(
+            None,
+            self::limit(2),
+            &EventFilter::default(),
+            EventSort::Newest,
+            TotalCount::Estimated,
+        )
+        .await
+        .unwrap()
+    };
+
+    assert_eq!(page.items.len(), 2);
+    assert!(!page.has_more);
+    assert!(page.next_cursor.is_none());
+    assert!(matches!(page.total, Some(Total::Estimated(_))));
+}
+
+#[tokio::test]
+async fn list_events_sorts_by_modification_and_age() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let mut event_ids = Vec::new();
+    for title in ["First", "Second", "Third"] {
+        event_ids.push(db.create_test_event(author_id, title, &[]).await.unwrap());
+    }
+    db.update_event(
+        event_ids[0],
+        author_id,
+        self::update_form(Some("Edited"), None),
+    )
+    .await
+    .unwrap();
+
+    let page = {
+        db.list_events(
+            None,
+            None,
+            &EventFilter::default(),
+            EventSort::RecentlyModified,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap()
+    };
+    let ids: Vec<_> = page.items.iter().map(|event| event.id).collect();
+    assert_eq!(ids, [event_ids[0], event_ids[2], event_ids[1]]);
+
+    let first_page = {
+        db.list_events(
+            None,
+            self::limit(2),
+            &EventFilter::default(),
+            EventSort::Oldest,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap()
+    };
+    let second_page = {
+        db.list_events(
+            first_page.next_cursor.clone(),
+            self::limit(2),
+            &EventFilter::default(),
+            EventSort::Oldest,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap()
+    };
+    let ids: Vec<_> = {
+        (first_page.items.iter())
+            .chain(&second_page.items)
+            .map(|event| event.id)
+            .collect()
+    };
+    assert_eq!(ids, event_ids);
+
+    let result = {
+        db.list_events(
+            first_page.next_cursor,
+            None,
+            &EventFilter::default(),
+            EventSort::Newest,
+            TotalCount::Skip,
+        )
+        .await
+    };
+    assert!(matches!(result, Err(ApiError::InvalidArgument(_))));
+}
+
+#[tokio::test]
+async fn editing_tags_marks_event_as_modified() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let tag_id = db.create_test_tag(author_id, &[]).await.unwrap();
+    let first = db.create_test_event(author_id, "First", &[]).await.unwrap();
+    let second = db
+        .create_test_event(author_id, "Second", &[])
+        .await
+        .unwrap();
+
+    db.update_event(
+        first,
+        author_id,
+        self::update_form(None, Some(vec![tag_id])),
+    )
+    .await
+    .unwrap();
+
+    let event = db.find_event(first).await.unwrap();
+    assert!(event.modified_at > event.created_at);
+    let page = {
+        db.list_events(
+            None,
+            None,
+            &EventFilter::default(),
+            EventSort::RecentlyModified,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap()
+    };
+    let ids: Vec<_> = page.items.iter().map(|event| event.id).collect();
+    assert_eq!(ids, [first, second]);
+}
+
+#[tokio::test]
+async fn list_events_keeps_tag_order_across_pages() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let music = db.create_test_tag(author_id, &[]).await.unwrap();
+    let tagged = db
+        .create_test_event(author_id, "Concert", &[music])
+        .await
+        .unwrap();
+    let untagged = db
+        .create_test_event(author_id, "Lecture", &[])
+        .await
+        .unwrap();
+
+    let mut cursor = None;
+    let mut event_ids = Vec::new();
+    loop {
+        let page = {
+            db.list_events(
+                cursor,
+                self::limit(1),
+                &self::boost(vec![music]),
+                EventSort::Relevance,
+                TotalCount::Skip,
+            )
+            .await
+            .unwrap()
+        };
+        event_ids.extend(page.items.iter().map(|event| event.id));
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    assert_eq!(event_ids, [tagged, untagged]);
+}
+
+#[tokio::test]
+async fn list_events_rejects_cursors_of_other_listings() {
+    let db = TestDatabase::new().await.unwrap();
+    let owner_id = db.create_test_user().await.unwrap();
+    for _ in 0..2 {
+        db.create_test_tag(owner_id, &[]).await.unwrap();
+    }
+    let tags_page = db
+        .list_tags(None, self::limit(1), TotalCount::Skip)
+        .await
+        .unwrap();
+
+    let result = {
+        db.list_events(
+            tags_page.next_cursor,
+            None,
+            &EventFilter::default(),
+            EventSort::Relevance,
+            TotalCount::Skip,
+        )
+        .await
+    };
+
+    assert!(matches!(result, Err(ApiError::InvalidArgument(_))));
+    assert!(matches!(
+        "not a cursor".parse::<Cursor>(),
+        Err(ApiError::InvalidArgument(_)),
+    ));
+}
+
+#[tokio::test]
+async fn list_events_ranks_events_by_matching_tags() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let music = db.create_test_tag(author_id, &[]).await.unwrap();
+    let outdoor = db.create_test_tag(author_id, &[]).await.unwrap();
+    let both = db
+        .create_test_event(author_id, "Festival", &[music, outdoor])
+        .await
+        .unwrap();
+    let none = db
+        .create_test_event(author_id, "Lecture", &[])
+        .await
+        .unwrap();
+    let one = db
+        .create_test_event(author_id, "Concert", &[music])
+        .await
+        .unwrap();
+
+    let events = db
+        .list_events(
+            None,
+            None,
+            &self::boost(vec![music, outdoor]),
+            EventSort::Relevance,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap();
+
+    let event_ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+    assert_eq!(event_ids, [both, one, none]);
+}
+
+#[tokio::test]
+async fn list_events_filters_by_tag_match_mode() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let music = db.create_test_tag(author_id, &[]).await.unwrap();
+    let outdoor = db.create_test_tag(author_id, &[]).await.unwrap();
+    let both = db
+        .create_test_event(author_id, "Festival", &[music, outdoor])
+        .await
+        .unwrap();
+    let one = db
+        .create_test_event(author_id, "Concert", &[music])
+        .await
+        .unwrap();
+    db.create_test_event(author_id, "Lecture", &[])
+        .await
+        .unwrap();
+
+    for (mode, exclude_tags, expected) in [
+        (TagMatch::Any, vec![], vec![both, one]),
+        (TagMatch::All, vec![], vec![both]),
+        (TagMatch::Any, vec![outdoor], vec![one]),
+    ] {
+        let tag_filter = TagFilter {
+            tags: vec![music, outdoor],
+            mode,
+            exclude_tags,
+        };
+        let events = {
+            db.list_events(
+                None,
+                None,
+                &EventFilter {
+                    tags: tag_filter,
+                    ..EventFilter::default()
+                },
+                EventSort::Relevance,
+                TotalCount::Skip,
+            )
+            .await
+            .unwrap()
+        };
+        let event_ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+        assert_eq!(event_ids, expected, "mode: {mode:?}");
+    }
+}
+
+#[tokio::test]
+async fn list_events_filters_by_author_and_dates() {
+    let db = TestDatabase::new().await.unwrap();
+    let alice = db.create_test_user().await.unwrap();
+    let bob = db.create_test_user().await.unwrap();
+    let first = db.create_test_event(alice, "First", &[]).await.unwrap();
+    let second = db.create_test_event(bob, "Second", &[]).await.unwrap();
+    let third = db.create_test_event(alice, "Third", &[]).await.unwrap();
+    let second_created_at = db.find_event(second).await.unwrap().created_at;
+
+    for (filter, expected) in [
+        (
+            EventFilter {
+                author_ids: vec![alice],
+                ..EventFilter::default()
+            },
+            vec![first, third],
+        ),
+        (
+            EventFilter {
+                created: TimeRange {
+                    since: Some(second_created_at),
+                    until: None,
+                },
+                ..EventFilter::default()
+            },
+            vec![second, third],
+        ),
+        (
+            EventFilter {
+                author_ids: vec![alice],
+                created: TimeRange {
+                    since: None,
+                    until: Some(second_created_at),
+                },
+                ..EventFilter::default()
+            },
+            vec![first],
+        ),
+    ] {
+        let events = {
+            db.list_events(None, None, &filter, EventSort::Oldest, TotalCount::Skip)
+                .await
+                .unwrap()
+        };
+        let event_ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+        assert_eq!(event_ids, expected, "filter: {filter:?}");
+    }
+}
+
+#[tokio::test]
+async fn list_events_filters_by_phase_and_sorts_by_start() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let now = Utc::now();
+    let hour = TimeDelta::hours(1);
+    let mut event_ids = Vec::new();
+    for (title, starts_at, ends_at) in [
+        ("Long ago", now - 10 * hour, now - 9 * hour),
+        ("Yesterday", now - 24 * hour, now - 23 * hour),
+        ("Ongoing", now - hour, now + hour),
+        ("Tomorrow", now + 24 * hour, now + 25 * hour),
+        ("Soon", now + hour, now + 2 * hour),
+    ] {
+        let event_id = {
+            db.create_test_event_at(author_id, title, &[], starts_at, ends_at)
+                .await
+                .unwrap()
+        };
+        event_ids.push(event_id);
+    }
+
+    for (phase, sort, expected) in [
+        (
+            EventPhase::Upcoming,
+            EventSort::EarliestStart,
+            vec![event_ids[4], event_ids[3]],
+        ),
+        (
+            EventPhase::HappeningNow,
+            EventSort::EarliestStart,
+            vec![event_ids[2]],
+        ),
+        (
+            EventPhase::Past,
+            EventSort::LatestStart,
+            vec![event_ids[0], event_ids[1]],
+        ),
+    ] {
+        let filter = EventFilter {
+            phase: Some(phase),
+            ..EventFilter::default()
+        };
+        let events = {
+            db.list_events(None, None, &filter, sort, TotalCount::Skip)
+                .await
+                .unwrap()
+        };
+        let ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+        assert_eq!(ids, expected, "phase: {phase:?}");
+    }
+}
+
+#[tokio::test]
+async fn list_events_searches_title_and_description() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let concert = db
+        .create_test_event(author_id, "Jazz concert", &[])
+        .await
+        .unwrap();
+    db.create_test_event(author_id, "Lecture", &[])
+        .await
+        .unwrap();
+
+    let events = {
+        db.list_events(
+            None,
+            None,
+            &self::search("jazz"),
+            EventSort::Relevance,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap()
+    };
+
+    let event_ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+    assert_eq!(event_ids, [concert]);
+}
+
+#[tokio::test]
+async fn list_events_supports_phrases_and_negation() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let jazz_concert = db
+        .create_test_event(author_id, "Jazz concert", &[])
+        .await
+        .unwrap();
+    db.create_test_event(author_id, "Jazz lecture", &[])
+        .await
+        .unwrap();
+    let rock_concert = db
+        .create_test_event(author_id, "Rock concert", &[])
+        .await
+        .unwrap();
+
+    for (search, expected) in [
+        ("jazz -lecture", jazz_concert),
+        ("\"rock concert\"", rock_concert),
+    ] {
+        let events = {
+            db.list_events(
+                None,
+                None,
+                &self::search(search),
+                EventSort::Relevance,
+                TotalCount::Skip,
+            )
+            .await
+            .unwrap()
+        };
+        let event_ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+        assert_eq!(event_ids, [expected], "search: {search}");
+    }
+}
+
+#[tokio::test]
+async fn list_events_tolerates_typos() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let jazz_concert = db
+        .create_test_event(author_id, "Jazz concert", &[])
+        .await
+        .unwrap();
+    db.create_test_event(author_id, "Lecture", &[])
+        .await
+        .unwrap();
+
+    let events = {
+        db.list_events(
+            None,
+            None,
+            &self::search("concrt"),
+            EventSort::Relevance,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap()
+    };
+
+    let event_ids: Vec<_> = events.items.iter().map(|event| event.id).collect();
+    assert_eq!(event_ids, [jazz_concert]);
+}
+
+#[tokio::test]
+async fn search_events_highlights_matches() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let concert = db
+        .create_test_event(author_id, "Jazz concert", &[])
+        .await
+        .unwrap();
+    let highlight = HighlightOptions {
+        start_marker: "[".to_owned(),
+        stop_marker: "]".to_owned(),
+        ..HighlightOptions::default()
+    };
+
+    let hits = {
+        db.search_events(
+            "jazz".to_owned(),
+            None,
+            None,
+            &EventFilter::default(),
+            &highlight,
+            TotalCount::Skip,
+        )
+        .await
+        .unwrap()
+    };
+
+    assert_eq!(hits.items.len(), 1);
+    assert_eq!(hits.items[0].event.id, concert);
+    assert_eq!(hits.items[0].title_highlight, "[Jazz] concert");
+    assert!(hits.total.is_none());
+}
+
+#[tokio::test]
+async fn search_events_counts_all_matches() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    for title in ["Jazz concert", "Jazz brunch", "Lecture"] {
+        db.create_test_event(author_id, title, &[]).await.unwrap();
+    }
+
+    let hits = {
+        db.search_events(
+            "jazz".to_owned(),
+            None,
+            self::limit(1),
+            &EventFilter::default(),
+            &HighlightOptions::default(),
+            TotalCount::Exact,
+        )
+        .await
+        .unwrap()
+    };
+
+    assert_eq!(hits.items.len(), 1);
+    assert!(hits.has_more);
+    assert_eq!(hits.total, Some(Total::Exact(2)));
+}
+
+#[tokio::test]
+async fn tag_facets_count_matching_events() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let music = db.create_test_tag(author_id, &[]).await.unwrap();
+    let outdoor = db.create_test_tag(author_id, &[]).await.unwrap();
+    db.create_test_event(author_id, "Jazz concert", &[music, outdoor])
+        .await
+        .unwrap();
+    db.create_test_event(author_id, "Rock concert", &[music])
+        .await
+        .unwrap();
+    db.create_test_event(author_id, "Lecture", &[outdoor])
+        .await
+        .unwrap();
+
+    let facets = db.tag_facets(&self::search("concert"), None).await.unwrap();
+    let counts: Vec<_> = {
+        facets
+            .iter()
+            .map(|facet| (facet.tag_id, facet.event_count))
+            .collect()
+    };
+    assert_eq!(counts, [(music, 2), (outdoor, 1)]);
+
+    let outdoor_events = EventFilter {
+        tags: TagFilter {
+            tags: vec![outdoor],
+            mode: TagMatch::Any,
+            ..TagFilter::default()
+        },
+        ..EventFilter::default()
+    };
+    let facets = db.tag_facets(&outdoor_events, None).await.unwrap();
+    let counts: Vec<_> = {
+        facets
+            .iter()
+            .map(|facet| (facet.tag_id, facet.event_count))
+            .collect()
+    };
+    assert_eq!(counts, [(outdoor, 2), (music, 1)]);
+    let listed = {
+        db.list_events(
+            None,
+            None,
+            &outdoor_events,
+            EventSort::Relevance,
+            TotalCount::Exact,
+        )
+        .await
+        .unwrap()
+        .total
+    };
+    assert_eq!(listed, Some(Total::Exact(facets[0].event_count)));
+
+    let facets = {
+        db.tag_facets(&EventFilter::default(), self::limit(1))
+            .await
+            .unwrap()
+    };
+    assert_eq!(facets.len(), 1);
+}
+
+#[tokio::test]
+async fn tag_facets_follow_tag_match_modes() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let music = db.create_test_tag(author_id, &[]).await.unwrap();
+    let outdoor = db.create_test_tag(author_id, &[]).await.unwrap();
+    db.create_test_event(author_id, "Festival", &[music, outdoor])
+        .await
+        .unwrap();
+    db.create_test_event(author_id, "Concert", &[music])
+        .await
+        .unwrap();
+    db.create_test_event(author_id, "Picnic", &[outdoor])
+        .await
+        .unwrap();
+
+    for (tag_filter, expected) in [
+        (
+            TagFilter {
+                tags: vec![music, outdoor],
+                mode: TagMatch::All,
+                exclude_tags: vec![],
+            },
+            vec![(music, 1), (outdoor, 1)],
+        ),
+        (
+            TagFilter {
+                tags: vec![],
+                mode: TagMatch::Boost,
+                exclude_tags: vec![outdoor],
+            },
+            vec![(music, 1)],
+        ),
+    ] {
+        let filter = EventFilter {
+            tags: tag_filter,
+            ..EventFilter::default()
+        };
+        let facets = db.tag_facets(&filter, None).await.unwrap();
+        let counts: Vec<_> = {
+            facets
+                .iter()
+                .map(|facet| (facet.tag_id, facet.event_count))
+                .collect()
+        };
+        assert_eq!(counts, expected, "filter: {:?}", filter.tags);
+    }
+}
+
+#[tokio::test]
+async fn tag_facets_follow_author_and_date_filters() {
+    let db = TestDatabase::new().await.unwrap();
+    let alice = db.create_test_user().await.unwrap();
+    let bob = db.create_test_user().await.unwrap();
+    let music = db.create_test_tag(alice, &[]).await.unwrap();
+    let outdoor = db.create_test_tag(alice, &[]).await.unwrap();
+    db.create_test_event(alice, "Concert", &[music])
+        .await
+        .unwrap();
+    let festival = {
+        db.create_test_event(bob, "Festival", &[music, outdoor])
+            .await
+            .unwrap()
+    };
+    let festival_created_at = db.find_event(festival).await.unwrap().created_at;
+
+    for (filter, expected) in [
+        (
+            EventFilter {
+                author_ids: vec![alice],
+                ..EventFilter::default()
+            },
+            vec![(music, 1)],
+        ),
+        (
+            EventFilter {
+                created: TimeRange {
+                    since: Some(festival_created_at),
+                    until: None,
+                },
+                ..EventFilter::default()
+            },
+            vec![(music, 1), (outdoor, 1)],
+        ),
+    ] {
+        let facets = db.tag_facets(&filter, None).await.unwrap();
+        let counts: Vec<_> = {
+            facets
+                .iter()
+                .map(|facet| (facet.tag_id, facet.event_count))
+                .collect()
+        };
+        assert_eq!(counts, expected);
+    }
+}
+
+#[tokio::test]
+async fn update_event_replaces_title_and_tags() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let old_tag = db.create_test_tag(author_id, &[]).await.unwrap();
+    let new_tag = db.create_test_tag(author_id, &[]).await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[old_tag])
+        .await
+        .unwrap();
+
+    db.update_event(
+        event_id,
+        author_id,
+        self::update_form(Some("Gig"), Some(vec![new_tag])),
+    )
+    .await
+    .unwrap();
+
+    let event = db.find_event(event_id).await.unwrap();
+    assert_eq!(event.title.as_ref(), "Gig");
+    let tag_ids: Vec<_> = event.tags.as_ref().iter().map(|tag| tag.id).collect();
+    assert_eq!(tag_ids, [new_tag]);
+}
+
+#[tokio::test]
+async fn set_event_schedule_moves_event_and_marks_it_modified() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+    let event = db.find_event(event_id).await.unwrap();
+
+    let schedule = self::schedule(3, "Europe/Berlin");
+    db.set_event_schedule(event_id, author_id, schedule.clone())
+        .await
+        .unwrap();
+
+    let moved = db.find_event_schedule(event_id).await.unwrap();
+    assert_eq!(moved.starts_at, schedule.starts_at.trunc_subsecs(6));
+    assert_eq!(moved.ends_at - moved.starts_at, TimeDelta::hours(3));
+    assert_eq!(moved.timezone.as_ref(), "Europe/Berlin");
+    let modified = db.find_event(event_id).await.unwrap();
+    assert!(modified.modified_at > event.modified_at);
+}
+
+#[tokio::test]
+async fn set_event_schedule_rejects_end_before_start() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+    let schedule = db.find_event_schedule(event_id).await.unwrap();
+
+    let backwards = EventSchedule {
+        ends_at: schedule.starts_at - TimeDelta::minutes(1),
+        ..schedule.clone()
+    };
+    let result = db.set_event_schedule(event_id, author_id, backwards).await;
+
+    assert!(matches!(result, Err(ApiError::InvalidArgument(_))));
+    let unchanged = db.find_event_schedule(event_id).await.unwrap();
+    assert_eq!(unchanged, schedule);
+}
+
+#[tokio::test]
+async fn set_event_schedule_rejects_unknown_time_zones() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+    let schedule = db.find_event_schedule(event_id).await.unwrap();
+
+    for timezone in ["Mars/Olympus_Mons", "EST", "UTC+3"] {
+        let moved = EventSchedule {
+            timezone: EventTimezone::new(timezone.to_owned()),
+            ..schedule.clone()
+        };
+        let result = db.set_event_schedule(event_id, author_id, moved).await;
+
+        let expected = format!("{timezone} isn't an IANA time zone.");
+        assert!(
+            matches!(result, Err(ApiError::InvalidArgument(message)) if message == expected),
+            "{timezone}",
+        );
+    }
+    let unchanged = db.find_event_schedule(event_id).await.unwrap();
+    assert_eq!(unchanged, schedule);
+}
+
+#[tokio::test]
+async fn set_event_schedule_is_forbidden_for_other_users() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let other_user_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+
+    let result = {
+        db.set_event_schedule(event_id, other_user_id, self::schedule(1, "UTC"))
+            .await
+    };
+
+    assert!(matches!(result, Err(ApiError::Forbidden(_))));
+}
+
+#[tokio::test]
+async fn update_event_is_forbidden_for_other_users() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let other_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+
+    let result = db
+        .update_event(event_id, other_id, self::update_form(Some("Gig"), None))
+        .await;
+
+    assert!(matches!(result, Err(ApiError::Forbidden(_))));
+    let event = db.find_event(event_id).await.unwrap();
+    assert_eq!(event.title.as_ref(), "Concert");
+}
+
+#[tokio::test]
+async fn delete_event_returns_its_images() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let tag_id = db.create_test_tag(author_id, &[]).await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[tag_id])
+        .await
+        .unwrap();
+    let image_id = evops_models::EventImageId::new(Uuid::now_v7());
+    db.reserve_image(event_id, image_id, author_id)
+        .await
+        .unwrap();
+
+    let image_ids = db.delete_event(event_id, author_id).await.unwrap();
+
+    assert_eq!(image_ids.into_inner(), [image_id]);
+    assert!(matches!(
+        db.find_event(event_id).await,
+        Err(ApiError::NotFound(_))
+    ));
+    db.find_tag(tag_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn delete_event_is_forbidden_for_other_users() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let other_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+
+    let result = db.delete_event(event_id, other_id).await;
+
+    assert!(matches!(result, Err(ApiError::Forbidden(_))));
+    db.find_event(event_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn reserve_image_appends_images_in_order() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+    let image_ids: Vec<_> = {
+        (0..3)
+            .map(|_| evops_models::EventImageId::new(Uuid::now_v7()))
+            .collect()
+    };
+
+    for &image_id in &image_ids {
+        db.reserve_image(event_id, image_id, author_id)
+            .await
+            .unwrap();
+    }
+
+    let event = db.find_event(event_id).await.unwrap();
+    assert_eq!(event.image_ids.into_inner(), image_ids);
+    assert!(event.modified_at > event.created_at);
+}
+
+#[tokio::test]
+async fn reserve_image_rejects_duplicate_image() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+    let image_id = evops_models::EventImageId::new(Uuid::now_v7());
+    db.reserve_image(event_id, image_id, author_id)
+        .await
+        .unwrap();
+
+    let result = db.reserve_image(event_id, image_id, author_id).await;
+
+    assert!(matches!(result, Err(ApiError::AlreadyExists(_))));
+}
+
+#[tokio::test]
+async fn reserve_image_is_forbidden_for_other_users() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let other_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+
+    let image_id = evops_models::EventImageId::new(Uuid::now_v7());
+    let result = db.reserve_image(event_id, image_id, other_id).await;
+
+    assert!(matches!(result, Err(ApiError::Forbidden(_))));
+}
@@ -0,0 +1,332 @@
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+use evops_db::test_util::TestDatabase;
+use evops_db::{
+    EventFilter, EventSchedule, EventTimezone, OccurrenceOverride, Recurrence, TimeRange,
+};
+use evops_models::ApiError;
+
+fn at(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| date.and_hms_opt(hour, 0, 0))
+        .unwrap()
+        .and_utc()
+}
+
+fn window(since: DateTime<Utc>, until: DateTime<Utc>) -> TimeRange {
+    TimeRange {
+        since: Some(since),
+        until: Some(until),
+    }
+}
+
+fn recurrence(rule: &str, exception_dates: Vec<DateTime<Utc>>) -> Recurrence {
+    Recurrence {
+        rule: rule.parse().unwrap(),
+        exception_dates,
+    }
+}
+
+/// The occurrences in `window`, by event and where the rule puts them.
+async fn listed(
+    db: &TestDatabase,
+    window: &TimeRange,
+) -> Vec<(evops_models::EventId, DateTime<Utc>)> {
+    db.list_occurrences(window, &EventFilter::default())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|occurrence| (occurrence.event.id, occurrence.occurs_at))
+        .collect()
+}
+
+#[tokio::test]
+async fn list_occurrences_expands_rules_with_exceptions_and_overrides() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    // A Monday.
+    let starts_at = self::at(2030, 1, 7, 18);
+    let club = {
+        db.create_test_event_at(
+            author_id,
+            "Club",
+            &[],
+            starts_at,
+            starts_at + TimeDelta::hours(2),
+        )
+        .await
+        .unwrap()
+    };
+    let one_off = {
+        let starts_at = self::at(2030, 1, 10, 12);
+        db.create_test_event_at(author_id, "Talk", &[], starts_at, starts_at)
+            .await
+            .unwrap()
+    };
+    let rule = "FREQ=WEEKLY;BYDAY=MO,WE;COUNT=5";
+    let recurrence = self::recurrence(rule, vec![self::at(2030, 1, 9, 18)]);
+    db.set_event_recurrence(club, author_id, Some(recurrence.clone()))
+        .await
+        .unwrap();
+    let form = OccurrenceOverride {
+        title: Some(unsafe { evops_models::EventTitle::new_unchecked("Finals".to_owned()) }),
+        starts_at: Some(self::at(2030, 1, 14, 19)),
+        ..OccurrenceOverride::default()
+    };
+    db.override_occurrence(club, author_id, self::at(2030, 1, 14, 18), form)
+        .await
+        .unwrap();
+
+    let window = self::window(self::at(2030, 1, 1, 0), self::at(2030, 2, 1, 0));
+    let occurrences = {
+        db.list_occurrences(&window, &EventFilter::default())
+            .await
+            .unwrap()
+    };
+
+    let listed: Vec<_> = {
+        occurrences
+            .iter()
+            .map(|occurrence| (occurrence.event.id, occurrence.occurs_at))
+            .collect()
+    };
+    assert_eq!(
+        listed,
+        [
+            (club, self::at(2030, 1, 7, 18)),
+            (one_off, self::at(2030, 1, 10, 12)),
+            (club, self::at(2030, 1, 14, 18)),
+            (club, self::at(2030, 1, 16, 18)),
+            (club, self::at(2030, 1, 21, 18)),
+        ],
+    );
+    let moved = &occurrences[2];
+    assert_eq!(moved.event.title.as_ref(), "Finals");
+    assert_eq!(moved.schedule.starts_at, self::at(2030, 1, 14, 19));
+    assert_eq!(moved.schedule.ends_at, self::at(2030, 1, 14, 21));
+    assert_eq!(occurrences[3].event.title.as_ref(), "Club");
+    assert_eq!(
+        db.find_event_recurrence(club).await.unwrap(),
+        Some(recurrence)
+    );
+    assert_eq!(db.find_event_recurrence(one_off).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn list_occurrences_keeps_the_wall_clock_time_across_dst() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    // 18:00 in Berlin, the Monday before the clocks go forward.
+    let starts_at = self::at(2030, 3, 25, 17);
+    let form = evops_models::NewEventForm {
+        title: unsafe { evops_models::EventTitle::new_unchecked("Club".to_owned()) },
+        description: unsafe { evops_models::EventDescription::new_unchecked(String::new()) },
+        tag_ids: unsafe { evops_models::EventTagIds::new_unchecked(Vec::new()) },
+    };
+    let schedule = EventSchedule {
+        starts_at,
+        ends_at: starts_at + TimeDelta::hours(1),
+        timezone: EventTimezone::new("Europe/Berlin".to_owned()),
+    };
+    let event_id = db.create_event(form, schedule, author_id).await.unwrap();
+    let recurrence = self::recurrence("FREQ=WEEKLY", Vec::new());
+    db.set_event_recurrence(event_id, author_id, Some(recurrence))
+        .await
+        .unwrap();
+
+    let window = self::window(self::at(2030, 3, 20, 0), self::at(2030, 4, 5, 0));
+    let occurrences = {
+        db.list_occurrences(&window, &EventFilter::default())
+            .await
+            .unwrap()
+    };
+
+    let starts: Vec<_> = occurrences.iter().map(|it| it.schedule.starts_at).collect();
+    assert_eq!(
+        starts,
+        [self::at(2030, 3, 25, 17), self::at(2030, 4, 1, 16)]
+    );
+}
+
+#[tokio::test]
+async fn list_occurrences_follows_bounded_series_to_their_end() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let starts_at = self::at(2030, 1, 7, 18);
+    let mut event_ids = Vec::new();
+    for rule in ["FREQ=WEEKLY;COUNT=2", "FREQ=WEEKLY;UNTIL=20300114"] {
+        let event_id = {
+            db.create_test_event_at(author_id, "Club", &[], starts_at, starts_at)
+                .await
+                .unwrap()
+        };
+        let recurrence = self::recurrence(rule, Vec::new());
+        db.set_event_recurrence(event_id, author_id, Some(recurrence))
+            .await
+            .unwrap();
+        event_ids.push(event_id);
+    }
+    let january = self::window(self::at(2030, 1, 14, 0), self::at(2030, 2, 1, 0));
+    assert_eq!(
+        self::listed(&db, &january).await,
+        [
+            (event_ids[0], self::at(2030, 1, 14, 18)),
+            (event_ids[1], self::at(2030, 1, 14, 18)),
+        ],
+    );
+    let march = self::window(self::at(2030, 3, 1, 0), self::at(2030, 4, 1, 0));
+    assert!(self::listed(&db, &march).await.is_empty());
+
+    // Moving the first occurrence moves the end of the series with it.
+    let moved = EventSchedule {
+        starts_at: self::at(2030, 3, 4, 18),
+        ends_at: self::at(2030, 3, 4, 18),
+        timezone: EventTimezone::new("UTC".to_owned()),
+    };
+    db.set_event_schedule(event_ids[0], author_id, moved)
+        .await
+        .unwrap();
+    assert_eq!(
+        self::listed(&db, &march).await,
+        [
+            (event_ids[0], self::at(2030, 3, 4, 18)),
+            (event_ids[0], self::at(2030, 3, 11, 18)),
+        ],
+    );
+}
+
+#[tokio::test]
+async fn list_occurrences_finds_the_window_in_long_running_series() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let mut event_ids = Vec::new();
+    for (starts_at, rule) in [
+        (self::at(2000, 1, 1, 18), "FREQ=DAILY;INTERVAL=3"),
+        // A Monday.
+        (
+            self::at(2000, 1, 3, 19),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=WE,SA",
+        ),
+        (self::at(2000, 1, 31, 20), "FREQ=MONTHLY"),
+    ] {
+        let event_id = {
+            db.create_test_event_at(author_id, "Club", &[], starts_at, starts_at)
+                .await
+                .unwrap()
+        };
+        let recurrence = self::recurrence(rule, Vec::new());
+        db.set_event_recurrence(event_id, author_id, Some(recurrence))
+            .await
+            .unwrap();
+        event_ids.push(event_id);
+    }
+    let [daily, weekly, monthly] = event_ids[..] else {
+        unreachable!();
+    };
+
+    let window = self::window(self::at(2030, 3, 25, 0), self::at(2030, 4, 8, 0));
+    assert_eq!(
+        self::listed(&db, &window).await,
+        [
+            (daily, self::at(2030, 3, 27, 18)),
+            (daily, self::at(2030, 3, 30, 18)),
+            (monthly, self::at(2030, 3, 31, 20)),
+            (daily, self::at(2030, 4, 2, 18)),
+            (weekly, self::at(2030, 4, 3, 19)),
+            (daily, self::at(2030, 4, 5, 18)),
+            (weekly, self::at(2030, 4, 6, 19)),
+        ],
+    );
+}
+
+#[tokio::test]
+async fn set_event_recurrence_marks_the_event_as_modified() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db.create_test_event(author_id, "Club", &[]).await.unwrap();
+    let event = db.find_event(event_id).await.unwrap();
+
+    let recurrence = self::recurrence("FREQ=WEEKLY", Vec::new());
+    db.set_event_recurrence(event_id, author_id, Some(recurrence))
+        .await
+        .unwrap();
+    let recurring = db.find_event(event_id).await.unwrap();
+    assert!(recurring.modified_at > event.modified_at);
+
+    db.set_event_recurrence(event_id, author_id, None)
+        .await
+        .unwrap();
+    let one_off = db.find_event(event_id).await.unwrap();
+    assert!(one_off.modified_at > recurring.modified_at);
+}
+
+#[tokio::test]
+async fn override_occurrence_rejects_dates_off_the_rule() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let other_user_id = db.create_test_user().await.unwrap();
+    let starts_at = self::at(2030, 1, 7, 18);
+    let event_id = {
+        db.create_test_event_at(author_id, "Club", &[], starts_at, starts_at)
+            .await
+            .unwrap()
+    };
+
+    let result = {
+        db.override_occurrence(
+            event_id,
+            author_id,
+            starts_at,
+            OccurrenceOverride::default(),
+        )
+        .await
+    };
+    assert!(matches!(result, Err(ApiError::InvalidArgument(_))));
+
+    let recurrence = self::recurrence("FREQ=DAILY", Vec::new());
+    db.set_event_recurrence(event_id, author_id, Some(recurrence))
+        .await
+        .unwrap();
+    for (user_id, occurs_at, expected) in [
+        (author_id, starts_at + TimeDelta::hours(1), "not found"),
+        (other_user_id, starts_at, "forbidden"),
+    ] {
+        let result = {
+            db.override_occurrence(event_id, user_id, occurs_at, OccurrenceOverride::default())
+                .await
+        };
+        let matched = match result {
+            Err(ApiError::NotFound(_)) => "not found",
+            Err(ApiError::Forbidden(_)) => "forbidden",
+            _ => "something else",
+        };
+        assert_eq!(matched, expected, "{result:?}");
+    }
+}
+
+#[tokio::test]
+async fn list_occurrences_requires_a_bounded_window() {
+    let db = TestDatabase::new().await.unwrap();
+
+    let window = TimeRange {
+        since: Some(Utc::now()),
+        until: None,
+    };
+    let result = db.list_occurrences(&window, &EventFilter::default()).await;
+
+    assert!(matches!(result, Err(ApiError::InvalidArgument(_))));
+}
+
+#[tokio::test]
+async fn list_occurrences_rejects_windows_wider_than_a_year() {
+    let db = TestDatabase::new().await.unwrap();
+
+    let since = self::at(2030, 1, 1, 0);
+    let year = self::window(since, since + TimeDelta::days(366));
+    assert!(self::listed(&db, &year).await.is_empty());
+    let wider = self::window(since, since + TimeDelta::days(366) + TimeDelta::seconds(1));
+    let result = db.list_occurrences(&wider, &EventFilter::default()).await;
+
+    let expected = "The window can't be wider than 366 days.";
+    assert!(matches!(result, Err(ApiError::InvalidArgument(message)) if message == expected));
+}
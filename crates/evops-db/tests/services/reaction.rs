@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+
+use evops_db::test_util::TestDatabase;
+use evops_models::ApiError;
+
+#[tokio::test]
+async fn reactions_are_counted_per_event() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+    let other_event_id = db
+        .create_test_event(author_id, "Workshop", &[])
+        .await
+        .unwrap();
+
+    for (user_id, reaction) in [
+        (author_id, "🎉"),
+        (user_id, "🎉"),
+        (user_id, ":fire:"),
+        (user_id, "👀"),
+    ] {
+        assert!(
+            db.toggle_reaction(event_id, user_id, reaction)
+                .await
+                .unwrap()
+        );
+    }
+    assert!(!db.toggle_reaction(event_id, user_id, "👀").await.unwrap());
+
+    let reactions = {
+        db.find_event_reactions(&[event_id, other_event_id])
+            .await
+            .unwrap()
+    };
+    let expected = BTreeMap::from([(":fire:".to_owned(), 1), ("🎉".to_owned(), 2)]);
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(reactions[&event_id.into_inner()], expected);
+}
+
+#[tokio::test]
+async fn toggle_reaction_rejects_invalid_reactions() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+
+    for reaction in ["", "thumbs up", &"a".repeat(33)] {
+        let result = db.toggle_reaction(event_id, author_id, reaction).await;
+        assert!(
+            matches!(result, Err(ApiError::InvalidArgument(_))),
+            "{reaction:?}: {result:?}",
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use uuid::Uuid;
+
+use evops_db::test_util::TestDatabase;
+use evops_db::{Total, TotalCount};
+use evops_models::ApiError;
+
+#[tokio::test]
+async fn create_tag_stores_aliases() {
+    let db = TestDatabase::new().await.unwrap();
+    let owner_id = db.create_test_user().await.unwrap();
+
+    let tag_id = db
+        .create_test_tag(owner_id, &["gig", "live"])
+        .await
+        .unwrap();
+
+    let tag = db.find_tag(tag_id).await.unwrap();
+    assert_eq!(tag.id, tag_id);
+    let mut aliases: Vec<_> = tag
+        .aliases
+        .as_ref()
+        .iter()
+        .map(AsRef::<str>::as_ref)
+        .collect();
+    aliases.sort_unstable();
+    assert_eq!(aliases, ["gig", "live"]);
+}
+
+#[tokio::test]
+async fn create_tag_rejects_taken_name() {
+    let db = TestDatabase::new().await.unwrap();
+    let owner_id = db.create_test_user().await.unwrap();
+    let tag = db
+        .find_tag(db.create_test_tag(owner_id, &[]).await.unwrap())
+        .await
+        .unwrap();
+
+    let form = evops_models::NewTagForm {
+        name: tag.name,
+        aliases: unsafe { evops_models::TagAliases::new_unchecked(Vec::new()) },
+    };
+    let result = db.create_tag(form, owner_id).await;
+
+    assert!(matches!(result, Err(ApiError::AlreadyExists(_))));
+}
+
+#[tokio::test]
+async fn delete_tag_detaches_it_from_events() {
+    let db = TestDatabase::new().await.unwrap();
+    let owner_id = db.create_test_user().await.unwrap();
+    let tag_id = db.create_test_tag(owner_id, &["alias"]).await.unwrap();
+    let event_id = db
+        .create_test_event(owner_id, "Concert", &[tag_id])
+        .await
+        .unwrap();
+
+    db.delete_tag(tag_id, owner_id).await.unwrap();
+
+    assert!(matches!(
+        db.find_tag(tag_id).await,
+        Err(ApiError::NotFound(_))
+    ));
+    let event = db.find_event(event_id).await.unwrap();
+    assert!(event.tags.as_ref().is_empty());
+}
+
+#[tokio::test]
+async fn delete_tag_is_forbidden_for_other_users() {
+    let db = TestDatabase::new().await.unwrap();
+    let owner_id = db.create_test_user().await.unwrap();
+    let other_id = db.create_test_user().await.unwrap();
+    let tag_id = db.create_test_tag(owner_id, &[]).await.unwrap();
+
+    let result = db.delete_tag(tag_id, other_id).await;
+
+    assert!(matches!(result, Err(ApiError::Forbidden(_))));
+    db.find_tag(tag_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn delete_tag_reports_unknown_tag() {
+    let db = TestDatabase::new().await.unwrap();
+    let owner_id = db.create_test_user().await.unwrap();
+
+    let result = db
+        .delete_tag(evops_models::TagId::new(Uuid::now_v7()), owner_id)
+        .await;
+
+    assert!(matches!(result, Err(ApiError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn list_tags_paginates_by_id() {
+    let db = TestDatabase::new().await.unwrap();
+    let owner_id = db.create_test_user().await.unwrap();
+    let mut tag_ids = Vec::new();
+    for _ in 0..3 {
+        tag_ids.push(db.create_test_tag(owner_id, &[]).await.unwrap());
+    }
+    let limit = Some(unsafe { evops_models::PgLimit::new_unchecked(2) });
+
+    let first_page = db.list_tags(None, limit, TotalCount::Exact).await.unwrap();
+    let ids: Vec<_> = first_page.items.iter().map(|tag| tag.id).collect();
+    assert_eq!(ids, tag_ids[..2]);
+    assert_eq!(first_page.total, Some(Total::Exact(3)));
+
+    let second_page = db
+        .list_tags(first_page.next_cursor, limit, TotalCount::Skip)
+        .await
+        .unwrap();
+    let ids: Vec<_> = second_page.items.iter().map(|tag| tag.id).collect();
+    assert_eq!(ids, tag_ids[2..]);
+    assert!(second_page.next_cursor.is_none());
+}
@@ -0,0 +1,172 @@
+use chrono::{TimeDelta, Utc};
+use uuid::Uuid;
+
+use evops_db::scoped_futures::ScopedFutureExt as _;
+use evops_db::test_util::TestDatabase;
+use evops_db::{EventSchedule, EventTimezone, TotalCount};
+use evops_models::ApiError;
+
+fn new_tag_form(name: &str) -> evops_models::NewTagForm {
+    evops_models::NewTagForm {
+        name: unsafe { evops_models::TagName::new_unchecked(name.to_owned()) },
+        aliases: unsafe { evops_models::TagAliases::new_unchecked(Vec::new()) },
+    }
+}
+
+fn new_event_form(tag_ids: Vec<evops_models::TagId>) -> evops_models::NewEventForm {
+    evops_models::NewEventForm {
+        title: unsafe { evops_models::EventTitle::new_unchecked("Concert".to_owned()) },
+        description: unsafe { evops_models::EventDescription::new_unchecked(String::new()) },
+        tag_ids: unsafe { evops_models::EventTagIds::new_unchecked(tag_ids) },
+    }
+}
+
+fn schedule() -> EventSchedule {
+    let starts_at = Utc::now() + TimeDelta::days(1);
+    EventSchedule {
+        starts_at,
+        ends_at: starts_at + TimeDelta::hours(1),
+        timezone: EventTimezone::new("UTC".to_owned()),
+    }
+}
+
+#[tokio::test]
+async fn transaction_commits_all_operations() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+
+    let (tag_id, event_id) = {
+        db.transaction(move |tx| {
+            async move {
+                let tag_id = tx.create_tag(self::new_tag_form("music"), user_id).await?;
+                let event_id = {
+                    let form = self::new_event_form(vec![tag_id]);
+                    tx.create_event(form, self::schedule(), user_id).await?
+                };
+                Ok((tag_id, event_id))
+            }
+            .scope_boxed()
+        })
+        .await
+        .unwrap()
+    };
+
+    let event = db.find_event(event_id).await.unwrap();
+    let tag_ids: Vec<_> = event.tags.as_ref().iter().map(|tag| tag.id).collect();
+    assert_eq!(tag_ids, [tag_id]);
+}
+
+#[tokio::test]
+async fn transaction_rolls_back_on_error() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    let unknown_event_id = evops_models::EventId::new(Uuid::now_v7());
+
+    let result = {
+        db.transaction(move |tx| {
+            async move {
+                tx.create_tag(self::new_tag_form("music"), user_id).await?;
+                tx.delete_event(unknown_event_id, user_id).await?;
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+    };
+
+    assert!(matches!(result, Err(ApiError::NotFound(_))));
+    assert!(
+        db.list_tags(None, None, TotalCount::Skip)
+            .await
+            .unwrap()
+            .items
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn transaction_rolls_back_admin_operations() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let new_author_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Concert", &[])
+        .await
+        .unwrap();
+    let unknown_tag_id = evops_models::TagId::new(Uuid::now_v7());
+
+    let result = {
+        db.transaction(move |tx| {
+            async move {
+                tx.transfer_event(event_id, new_author_id).await?;
+                tx.revoke_all_refresh_tokens(author_id).await?;
+                tx.delete_tag_as_admin(unknown_tag_id).await?;
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+    };
+
+    assert!(matches!(result, Err(ApiError::NotFound(_))));
+    let event = db.find_event(event_id).await.unwrap();
+    assert_eq!(event.author.id, author_id);
+}
+
+const SERIALIZATION_FAILURE: &str =
+    "RAISE EXCEPTION 'could not serialize access' USING ERRCODE = 'serialization_failure'";
+
+/// Puts a view in front of the `users` table that runs `failure`, a PL/pgSQL
+/// statement, on each of the first `reads` rows read through it.
+fn fail_user_reads(db: &TestDatabase, reads: u32, failure: &str) {
+    db.batch_execute(&format!(
+        "CREATE SEQUENCE user_reads; \
+         CREATE FUNCTION fail_user_read() RETURNS boolean LANGUAGE plpgsql AS $$ \
+         BEGIN \
+             IF nextval('user_reads') <= {reads} THEN {failure}; END IF; \
+             RETURN true; \
+         END $$; \
+         ALTER TABLE users RENAME TO users_table; \
+         CREATE VIEW users AS SELECT * FROM users_table WHERE fail_user_read();",
+    ))
+    .unwrap();
+}
+
+async fn find_user_in_transaction(
+    db: &TestDatabase,
+    user_id: evops_models::UserId,
+) -> evops_models::ApiResult<evops_models::User> {
+    db.transaction(move |tx| async move { tx.find_user(user_id).await }.scope_boxed())
+        .await
+}
+
+#[tokio::test]
+async fn transaction_retries_reads_after_serialization_failures() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    self::fail_user_reads(&db, 1, SERIALIZATION_FAILURE);
+
+    let user = self::find_user_in_transaction(&db, user_id).await.unwrap();
+    assert_eq!(user.id, user_id);
+}
+
+#[tokio::test]
+async fn transaction_retries_reads_after_losing_the_connection() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    self::fail_user_reads(&db, 1, "PERFORM pg_terminate_backend(pg_backend_pid())");
+
+    let user = self::find_user_in_transaction(&db, user_id).await.unwrap();
+    assert_eq!(user.id, user_id);
+}
+
+#[tokio::test]
+async fn transaction_gives_up_after_the_last_attempt() {
+    let db = TestDatabase::new().await.unwrap();
+    let user_id = db.create_test_user().await.unwrap();
+    // As many failures as the default retry policy makes attempts.
+    self::fail_user_reads(&db, 3, SERIALIZATION_FAILURE);
+
+    let result = self::find_user_in_transaction(&db, user_id).await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,245 @@
+use evops_db::test_util::TestDatabase;
+use evops_db::{
+    EventFilter, EventLocation, EventSort, GeoPoint, GeoRadius, NewVenueForm, TotalCount, VenueId,
+};
+use evops_models::ApiError;
+use uuid::Uuid;
+
+const BERLIN: GeoPoint = GeoPoint {
+    latitude: 52.5200,
+    longitude: 13.4050,
+};
+const POTSDAM: GeoPoint = GeoPoint {
+    latitude: 52.3906,
+    longitude: 13.0645,
+};
+const HAMBURG: GeoPoint = GeoPoint {
+    latitude: 53.5511,
+    longitude: 9.9937,
+};
+
+fn venue_form(name: &str, location: GeoPoint) -> NewVenueForm {
+    NewVenueForm {
+        name: name.to_owned(),
+        address: format!("{name} Hauptbahnhof"),
+        location,
+    }
+}
+
+#[tokio::test]
+async fn create_venue_round_trips() {
+    let db = TestDatabase::new().await.unwrap();
+
+    let venue_id = db
+        .create_venue(self::venue_form("Berlin", BERLIN))
+        .await
+        .unwrap();
+    let venue = db.find_venue(venue_id).await.unwrap();
+
+    assert_eq!(venue.id, venue_id);
+    assert_eq!(venue.name, "Berlin");
+    assert_eq!(venue.address, "Berlin Hauptbahnhof");
+    assert_eq!(venue.location, BERLIN);
+    let result = db.find_venue(VenueId::new(Uuid::now_v7())).await;
+    assert!(matches!(result, Err(ApiError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn create_venue_rejects_invalid_coordinates() {
+    let db = TestDatabase::new().await.unwrap();
+
+    for location in [
+        GeoPoint {
+            latitude: 90.5,
+            longitude: 0.0,
+        },
+        GeoPoint {
+            latitude: 0.0,
+            longitude: -181.0,
+        },
+        GeoPoint {
+            latitude: f64::NAN,
+            longitude: 0.0,
+        },
+    ] {
+        let result = db.create_venue(self::venue_form("Nowhere", location)).await;
+        assert!(
+            matches!(result, Err(ApiError::InvalidArgument(_))),
+            "{location:?}: {result:?}",
+        );
+    }
+}
+
+#[tokio::test]
+async fn set_event_location_round_trips() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let other_user_id = db.create_test_user().await.unwrap();
+    let event_id = db
+        .create_test_event(author_id, "Meetup", &[])
+        .await
+        .unwrap();
+    let venue_id = db
+        .create_venue(self::venue_form("Berlin", BERLIN))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        db.find_event_location(event_id).await.unwrap(),
+        EventLocation::default(),
+    );
+    let location = EventLocation {
+        venue_id: Some(venue_id),
+        online_url: Some("https://meet.example.com/meetup".parse().unwrap()),
+    };
+    let event = db.find_event(event_id).await.unwrap();
+    db.set_event_location(event_id, author_id, location.clone())
+        .await
+        .unwrap();
+    assert_eq!(db.find_event_location(event_id).await.unwrap(), location);
+    let modified = db.find_event(event_id).await.unwrap();
+    assert!(modified.modified_at > event.modified_at);
+
+    let results = [
+        db.set_event_location(event_id, other_user_id, location.clone())
+            .await,
+        db.set_event_location(
+            event_id,
+            author_id,
+            EventLocation {
+                venue_id: Some(VenueId::new(Uuid::now_v7())),
+                online_url: None,
+            },
+        )
+        .await,
+        db.set_event_location(
+            event_id,
+            author_id,
+            EventLocation {
+                venue_id: None,
+                online_url: Some("ftp://example.com/meetup".parse().unwrap()),
+            },
+        )
+        .await,
+    ];
+    assert!(matches!(results[0], Err(ApiError::Forbidden(_))));
+    assert!(matches!(results[1], Err(ApiError::NotFound(_))));
+    assert!(matches!(results[2], Err(ApiError::InvalidArgument(_))));
+    assert_eq!(db.find_event_location(event_id).await.unwrap(), location);
+}
+
+#[tokio::test]
+async fn list_events_filters_by_distance_and_sorts_nearest_first() {
+    let db = TestDatabase::new().await.unwrap();
+    let author_id = db.create_test_user().await.unwrap();
+    let mut event_ids = Vec::new();
+    for (name, location) in [
+        ("Hamburg", HAMBURG),
+        ("Potsdam", POTSDAM),
+        ("Berlin", BERLIN),
+    ] {
+        let event_id = db.create_test_event(author_id, name, &[]).await.unwrap();
+        let venue_id = db
+            .create_venue(self::venue_form(name, location))
+            .await
+            .unwrap();
+        let location = EventLocation {
+            venue_id: Some(venue_id),
+            online_url: None,
+        };
+        db.set_event_location(event_id, author_id, location)
+            .await
+            .unwrap();
+        event_ids.push(event_id);
+    }
+    let [hamburg, potsdam, berlin] = event_ids[..] else {
+        unreachable!();
+    };
+    // Online only, so never near anything.
+    db.create_test_event(author_id, "Webinar", &[])
+        .await
+        .unwrap();
+
+    let filter = EventFilter {
+        near: Some(GeoRadius {
+            center: BERLIN,
+            radius_km: 50.0,
+        }),
+        ..EventFilter::default()
+    };
+    let limit = Some(unsafe { evops_models::PgLimit::new_unchecked(1) });
+    let mut listed = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = {
+            db.list_events(
+                cursor,
+                limit,
+                &filter,
+                EventSort::Nearest,
+                TotalCount::Exact,
+            )
+            .await
+            .unwrap()
+        };
+        assert_eq!(page.total, Some(evops_db::Total::Exact(2)));
+        listed.extend(page.items.into_iter().map(|event| event.id));
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(listed, [berlin, potsdam]);
+
+    let filter = EventFilter {
+        near: Some(GeoRadius {
+            center: BERLIN,
+            radius_km: 300.0,
+        }),
+        ..EventFilter::default()
+    };
+    let page = {
+        db.list_events(None, None, &filter, EventSort::Nearest, TotalCount::Skip)
+            .await
+            .unwrap()
+    };
+    let listed: Vec<_> = page.items.into_iter().map(|event| event.id).collect();
+    assert_eq!(listed, [berlin, potsdam, hamburg]);
+}
+
+#[tokio::test]
+async fn list_events_rejects_invalid_distance_searches() {
+    let db = TestDatabase::new().await.unwrap();
+
+    let results = [
+        db.list_events(
+            None,
+            None,
+            &EventFilter::default(),
+            EventSort::Nearest,
+            TotalCount::Skip,
+        )
+        .await,
+        db.list_events(
+            None,
+            None,
+            &EventFilter {
+                near: Some(GeoRadius {
+                    center: BERLIN,
+                    radius_km: -1.0,
+                }),
+                ..EventFilter::default()
+            },
+            EventSort::Relevance,
+            TotalCount::Skip,
+        )
+        .await,
+    ];
+
+    for result in results {
+        assert!(
+            matches!(result, Err(ApiError::InvalidArgument(_))),
+            "{result:?}"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Checks the TLS modes against a Postgres serving a self-signed certificate.
+//!
+//! The tests are ignored by default. Run them with `cargo test -- --ignored`
+//! once `TEST_TLS_DATABASE_URL` points at such a server, e.g. a local one
+//! with `ssl = on` and a certificate made with
+//! `openssl req -x509 -newkey rsa:2048 -nodes -subj /CN=localhost`, and
+//! `TEST_TLS_ROOT_CERT` at that certificate.
+
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use url::Url;
+
+use evops_db::scoped_futures::ScopedFutureExt as _;
+use evops_db::{Database, DatabaseConfig, MigrationMode, TlsMode};
+
+fn required_env_var(name: &str) -> String {
+    env::var(name).unwrap_or_else(|_| panic!("{name} must be set to run the TLS tests"))
+}
+
+fn tls_config(mode: TlsMode) -> DatabaseConfig {
+    let url: Url = {
+        self::required_env_var("TEST_TLS_DATABASE_URL")
+            .parse()
+            .expect("TEST_TLS_DATABASE_URL")
+    };
+    let mut config = DatabaseConfig::new(url);
+    config.tls.mode = mode;
+    // The pool opens connections lazily, so only the first query shows
+    // whether the handshake succeeds.
+    config.migrations = MigrationMode::Skip;
+    config.pool.max_size = 1;
+    config.pool.acquire_timeout = Duration::from_secs(5);
+    config
+}
+
+/// Opens a pooled connection by running an empty transaction.
+async fn connect(config: &DatabaseConfig) -> bool {
+    let db = Database::establish_connection(config).await.unwrap();
+    db.transaction(|_| async { Ok(()) }.scope_boxed())
+        .await
+        .is_ok()
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_TLS_DATABASE_URL"]
+async fn require_accepts_a_self_signed_certificate() {
+    let config = self::tls_config(TlsMode::Require);
+    assert!(self::connect(&config).await);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_TLS_DATABASE_URL"]
+async fn verify_full_rejects_a_self_signed_certificate() {
+    let config = self::tls_config(TlsMode::VerifyFull);
+    assert!(!self::connect(&config).await);
+}
+
+#[tokio::test]
+#[ignore = "needs TEST_TLS_DATABASE_URL and TEST_TLS_ROOT_CERT"]
+async fn verify_full_accepts_a_trusted_self_signed_certificate() {
+    let mut config = self::tls_config(TlsMode::VerifyFull);
+    config.tls.root_cert = Some(PathBuf::from(self::required_env_var("TEST_TLS_ROOT_CERT")));
+    assert!(self::connect(&config).await);
+}